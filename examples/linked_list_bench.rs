@@ -0,0 +1,67 @@
+// Rough timing of the three doubly linked lists, run with
+// cargo run --release --example linked_list_bench
+
+use std::{collections::LinkedList, hint::black_box, time::Instant};
+
+use data_structures::linear_data_structures::linked_lists::{
+    double::DoubleLinkedList, raw_double::RawDoubleLinkedList,
+};
+
+const ELEMENTS: u64 = 200_000;
+const ROUNDS: u32 = 5;
+
+fn time<F: FnMut()>(name: &str, mut run: F) {
+    let start = Instant::now();
+
+    for _ in 0..ROUNDS {
+        run();
+    }
+
+    println!("{:<28} {:>10.2?} per round", name, start.elapsed() / ROUNDS);
+}
+
+fn main() {
+    println!("push_back {} elements, sum them, pop them all\n", ELEMENTS);
+
+    time("DoubleLinkedList (Rc)", || {
+        let mut list = DoubleLinkedList::new();
+        for i in 0..ELEMENTS {
+            list.push_back(i);
+        }
+
+        let sum: u64 = list.iter().map(|x| *x).sum();
+        black_box(sum);
+
+        while let Some(value) = list.pop_back() {
+            black_box(value);
+        }
+    });
+
+    time("RawDoubleLinkedList", || {
+        let mut list = RawDoubleLinkedList::new();
+        for i in 0..ELEMENTS {
+            list.push_back(i);
+        }
+
+        let sum: u64 = list.iter().sum();
+        black_box(sum);
+
+        while let Some(value) = list.pop_back() {
+            black_box(value);
+        }
+    });
+
+    time("std::collections::LinkedList", || {
+        let mut list = LinkedList::new();
+        for i in 0..ELEMENTS {
+            list.push_back(i);
+        }
+
+        let sum: u64 = list.iter().sum();
+        black_box(sum);
+
+        while let Some(value) = list.pop_back() {
+            black_box(value);
+        }
+    });
+}
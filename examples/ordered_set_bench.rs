@@ -0,0 +1,79 @@
+// Rough timing of every search tree through the OrderedSet trait, so each one runs the
+// exact same code, run with
+// cargo run --release --example ordered_set_bench
+//
+// The B-trees run at a few orders to show what wider nodes buy over the binary trees
+
+use std::{hint::black_box, time::Instant};
+
+use data_structures::non_linear_data_structures::trees::{
+    arena_red_black_tree::ArenaRedBlackTree, avl_tree::AvlTree, b_plus_tree::BPlusTree,
+    b_tree::BTree, binary_search_tree::BinarySearchTree, ordered_set::OrderedSet,
+    red_black_tree::RedBlackTree,
+};
+
+const ELEMENTS: u64 = 200_000;
+const ROUNDS: u32 = 5;
+
+fn time<F: FnMut()>(name: &str, mut run: F) {
+    let start = Instant::now();
+
+    for _ in 0..ROUNDS {
+        run();
+    }
+
+    println!("{:<28} {:>10.2?} per round", name, start.elapsed() / ROUNDS);
+}
+
+// Same scattered keys every run, a few repeat which exercises the duplicate path too.
+// Scattered matters for the plain BST, sorted keys would turn it into a list
+fn keys() -> Vec<u64> {
+    (0..ELEMENTS)
+        .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15) % (ELEMENTS * 4))
+        .collect()
+}
+
+fn run<S: OrderedSet<u64>>(keys: &[u64]) {
+    let mut set = S::default();
+    for &key in keys {
+        black_box(set.insert(key));
+    }
+
+    let found = keys.iter().filter(|key| set.contains(key)).count();
+    black_box(found);
+
+    let sum: u64 = set.iter().map(|x| *x).sum();
+    black_box(sum);
+
+    // a thousand narrow windows spread over the key space
+    for low in (0..ELEMENTS * 4).step_by(ELEMENTS as usize * 4 / 1000) {
+        let count = set.range(low..low + 100).count();
+        black_box(count);
+    }
+
+    black_box((set.min().map(|x| *x), set.max().map(|x| *x)));
+
+    for key in keys {
+        black_box(set.remove(key));
+    }
+}
+
+fn main() {
+    let keys = keys();
+
+    println!(
+        "insert {} scattered keys, look them all up, sum them, 1000 range scans, remove them all\n",
+        ELEMENTS
+    );
+
+    time("BinarySearchTree", || run::<BinarySearchTree<u64>>(&keys));
+    time("AvlTree", || run::<AvlTree<u64>>(&keys));
+    time("RedBlackTree (Rc)", || run::<RedBlackTree<u64>>(&keys));
+    time("ArenaRedBlackTree", || run::<ArenaRedBlackTree<u64>>(&keys));
+    time("BTree, B = 4", || run::<BTree<u64, (), 4>>(&keys));
+    time("BTree, B = 16", || run::<BTree<u64, (), 16>>(&keys));
+    time("BTree, B = 64", || run::<BTree<u64, (), 64>>(&keys));
+    time("BPlusTree, B = 4", || run::<BPlusTree<u64, (), 4>>(&keys));
+    time("BPlusTree, B = 16", || run::<BPlusTree<u64, (), 16>>(&keys));
+    time("BPlusTree, B = 64", || run::<BPlusTree<u64, (), 64>>(&keys));
+}
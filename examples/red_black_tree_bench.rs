@@ -0,0 +1,85 @@
+// Rough timing of the Rc red-black tree against the arena one, run with
+// cargo run --release --example red_black_tree_bench
+
+use std::{collections::BTreeSet, hint::black_box, time::Instant};
+
+use data_structures::non_linear_data_structures::trees::{
+    arena_red_black_tree::ArenaRedBlackTree, red_black_tree::RedBlackTree,
+};
+
+const ELEMENTS: u64 = 200_000;
+const ROUNDS: u32 = 5;
+
+fn time<F: FnMut()>(name: &str, mut run: F) {
+    let start = Instant::now();
+
+    for _ in 0..ROUNDS {
+        run();
+    }
+
+    println!("{:<28} {:>10.2?} per round", name, start.elapsed() / ROUNDS);
+}
+
+// Same scattered keys every run, a few repeat which exercises the duplicate path too
+fn keys() -> Vec<u64> {
+    (0..ELEMENTS)
+        .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15) % (ELEMENTS * 4))
+        .collect()
+}
+
+fn main() {
+    let keys = keys();
+
+    println!("insert {} scattered keys, look them all up, sum them, delete them all\n", ELEMENTS);
+
+    time("RedBlackTree (Rc)", || {
+        let mut tree = RedBlackTree::new();
+        for &key in &keys {
+            tree.insert(key);
+        }
+
+        let found = keys.iter().filter(|key| tree.contains(*key)).count();
+        black_box(found);
+
+        let sum: u64 = tree.iter().map(|x| *x).sum();
+        black_box(sum);
+
+        for &key in &keys {
+            black_box(tree.delete(key));
+        }
+    });
+
+    time("ArenaRedBlackTree", || {
+        let mut tree = ArenaRedBlackTree::new();
+        for &key in &keys {
+            tree.insert(key);
+        }
+
+        let found = keys.iter().filter(|key| tree.contains(*key)).count();
+        black_box(found);
+
+        let sum: u64 = tree.iter().sum();
+        black_box(sum);
+
+        for key in &keys {
+            black_box(tree.delete(key));
+        }
+    });
+
+    time("std::collections::BTreeSet", || {
+        let mut tree = BTreeSet::new();
+        for &key in &keys {
+            tree.insert(key);
+        }
+
+        let found = keys.iter().filter(|key| tree.contains(*key)).count();
+        black_box(found);
+
+        let sum: u64 = tree.iter().sum();
+        black_box(sum);
+
+        for key in &keys {
+            black_box(tree.remove(key));
+        }
+    });
+}
@@ -1,2 +1,7 @@
 pub mod linear_data_structures;
-pub mod non_linear_data_structures;
\ No newline at end of file
+pub mod non_linear_data_structures;
+
+mod node_ref;
+
+#[cfg(test)]
+mod property;
\ No newline at end of file
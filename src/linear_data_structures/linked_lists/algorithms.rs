@@ -0,0 +1,675 @@
+// Classic interview algorithms on top of the lists in this module
+// Everything here relinks the nodes that already exist instead of copying values
+// into a Vec, so the extra space stays O(1)
+
+use std::{
+    cell::{Ref, RefCell},
+    rc::Rc,
+};
+
+use super::double::{self, DoubleLinkedList};
+use super::singular::{self, SingularLinkedList};
+
+type Link<T> = Rc<RefCell<double::Node<T>>>;
+
+///////////// SINGULAR LINKED LIST /////////////
+
+impl<T> SingularLinkedList<T> {
+    // Tortoise and hare, when the fast pointer reaches the end the slow one is in the middle
+    // For even lengths the second of the two middle nodes is returned
+    pub fn middle(&self) -> Option<&T> {
+        let mut slow = self.head.as_deref()?;
+        let mut fast = self.head.as_deref();
+
+        while let Some(next) = fast.and_then(|node| node.next.as_deref()) {
+            slow = slow.next.as_deref().unwrap();
+            fast = next.next.as_deref();
+        }
+
+        Some(&slow.data)
+    }
+
+    // k = 0 is the last element, the leading pointer runs k nodes ahead
+    pub fn kth_from_end(&self, k: usize) -> Option<&T> {
+        let mut lead = self.head.as_deref();
+
+        for _ in 0..k {
+            lead = lead?.next.as_deref();
+        }
+
+        let mut lead = lead?;
+        let mut trail = self.head.as_deref()?;
+
+        while let Some(next) = lead.next.as_deref() {
+            lead = next;
+            trail = trail.next.as_deref().unwrap();
+        }
+
+        Some(&trail.data)
+    }
+
+    // Moves the last k nodes to the front, k larger than the list wraps around
+    pub fn rotate_right(&mut self, k: usize) {
+        let length = self.iter().count();
+
+        if length == 0 || k.is_multiple_of(length) {
+            return;
+        }
+
+        let split_at = length - k % length;
+
+        let mut new_tail = self.head.as_mut().unwrap();
+        for _ in 1..split_at {
+            new_tail = new_tail.next.as_mut().unwrap();
+        }
+
+        let mut new_head = new_tail.next.take();
+        let old_head = self.head.take();
+
+        let mut last = new_head.as_mut().unwrap();
+        while let Some(ref mut next) = last.next {
+            last = next;
+        }
+
+        last.next = old_head;
+        self.head = new_head;
+    }
+
+    // Moves the first k nodes to the back
+    pub fn rotate_left(&mut self, k: usize) {
+        let length = self.iter().count();
+
+        if length != 0 {
+            self.rotate_right(length - k % length);
+        }
+    }
+
+    fn reverse_chain(mut current: Option<Box<singular::Node<T>>>) -> Option<Box<singular::Node<T>>> {
+        let mut prev = None;
+
+        while let Some(mut boxed_node) = current {
+            current = boxed_node.next.take();
+            boxed_node.next = prev;
+            prev = Some(boxed_node);
+        }
+
+        prev
+    }
+}
+
+impl<T: Eq> SingularLinkedList<T> {
+    // Reverses the second half in place, compares it with the first half and then
+    // puts it back, so the list looks untouched afterwards
+    pub fn is_palindrome(&mut self) -> bool {
+        let length = self.iter().count();
+
+        if length < 2 {
+            return true;
+        }
+
+        let mut first_half_end = self.head.as_mut().unwrap();
+        for _ in 1..length.div_ceil(2) {
+            first_half_end = first_half_end.next.as_mut().unwrap();
+        }
+
+        let second_half = Self::reverse_chain(first_half_end.next.take());
+
+        let mut result = true;
+        let mut left = self.head.as_deref();
+        let mut right = second_half.as_deref();
+
+        while let (Some(l), Some(r)) = (left, right) {
+            if l.data != r.data {
+                result = false;
+                break;
+            }
+
+            left = l.next.as_deref();
+            right = r.next.as_deref();
+        }
+
+        let mut first_half_end = self.head.as_mut().unwrap();
+        for _ in 1..length.div_ceil(2) {
+            first_half_end = first_half_end.next.as_mut().unwrap();
+        }
+        first_half_end.next = Self::reverse_chain(second_half);
+
+        result
+    }
+
+    // Keeps the first occurrence of every value, O(n^2) time but no hash set
+    pub fn remove_duplicates(&mut self) {
+        let mut current = self.head.as_mut();
+
+        while let Some(node) = current {
+            let singular::Node { data, next } = &mut **node;
+            let mut link = next;
+
+            while link.is_some() {
+                if link.as_ref().unwrap().data == *data {
+                    let removed = link.take().unwrap();
+                    *link = removed.next;
+                } else {
+                    link = &mut link.as_mut().unwrap().next;
+                }
+            }
+
+            current = node.next.as_mut();
+        }
+    }
+}
+
+impl<T: Ord> SingularLinkedList<T> {
+    // Stable partition, everything smaller than the pivot comes first
+    pub fn partition(&mut self, pivot: &T) {
+        let mut smaller: Option<Box<singular::Node<T>>> = None;
+        let mut smaller_tail = &mut smaller;
+        let mut rest: Option<Box<singular::Node<T>>> = None;
+        let mut rest_tail = &mut rest;
+
+        let mut current = self.head.take();
+
+        while let Some(mut boxed_node) = current {
+            current = boxed_node.next.take();
+
+            if boxed_node.data < *pivot {
+                smaller_tail = &mut smaller_tail.insert(boxed_node).next;
+            } else {
+                rest_tail = &mut rest_tail.insert(boxed_node).next;
+            }
+        }
+
+        *smaller_tail = rest;
+        self.head = smaller;
+    }
+}
+
+///////////// DOUBLE LINKED LIST /////////////
+
+fn next_of<T>(node: &Link<T>) -> Option<Link<T>> {
+    node.borrow().next.clone()
+}
+
+fn prev_of<T>(node: &Link<T>) -> Option<Link<T>> {
+    node.borrow().prev.as_ref().and_then(|weak| weak.upgrade())
+}
+
+fn nth_node<T>(head: &Link<T>, n: usize) -> Link<T> {
+    let mut current = head.clone();
+
+    for _ in 0..n {
+        current = next_of(&current).unwrap();
+    }
+
+    current
+}
+
+fn tail_of<T>(list: &DoubleLinkedList<T>) -> Option<Link<T>> {
+    list.tail.as_ref().and_then(|weak| weak.upgrade())
+}
+
+impl<T> DoubleLinkedList<T> {
+    // Cuts `node` out of the chain, the caller makes sure it is not the head
+    fn unlink(&mut self, node: &Link<T>) {
+        let prev = prev_of(node);
+        let next = node.borrow_mut().next.take();
+        node.borrow_mut().prev = None;
+
+        match next {
+            Some(ref next_node) => next_node.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev.as_ref().map(Rc::downgrade),
+        }
+
+        if let Some(prev_node) = prev {
+            prev_node.borrow_mut().next = next;
+        }
+
+        self.len -= 1;
+    }
+
+    // Moves the last k nodes to the front, k larger than the list wraps around
+    pub fn rotate_right(&mut self, k: usize) {
+        let length = self.len;
+
+        if length == 0 || k.is_multiple_of(length) {
+            return;
+        }
+
+        let head = self.head.clone().unwrap();
+        let new_tail = nth_node(&head, length - k % length - 1);
+        let old_tail = tail_of(self).unwrap();
+
+        let new_head = new_tail.borrow_mut().next.take().unwrap();
+        new_head.borrow_mut().prev = None;
+
+        old_tail.borrow_mut().next = Some(head.clone());
+        head.borrow_mut().prev = Some(Rc::downgrade(&old_tail));
+
+        self.head = Some(new_head);
+        self.tail = Some(Rc::downgrade(&new_tail));
+    }
+
+    // Moves the first k nodes to the back
+    pub fn rotate_left(&mut self, k: usize) {
+        let length = self.len;
+
+        if length != 0 {
+            self.rotate_right(length - k % length);
+        }
+    }
+}
+
+impl<T> DoubleLinkedList<T> {
+    // For even lengths the second of the two middle nodes is returned
+    pub fn middle(&self) -> Option<Ref<'_, T>> {
+        let mut slow = self.head.as_deref()?;
+        let mut fast = Some(slow);
+
+        while let Some(next) = fast.and_then(double::next_ref) {
+            slow = double::next_ref(slow).unwrap();
+            fast = double::next_ref(next);
+        }
+
+        Some(Ref::map(slow.borrow(), |node| &node.data))
+    }
+
+    // k = 0 is the last element, with a tail pointer this is just k steps backwards
+    pub fn kth_from_end(&self, k: usize) -> Option<Ref<'_, T>> {
+        let mut current = self.tail_ref();
+
+        for _ in 0..k {
+            current = double::prev_ref(current?);
+        }
+
+        Some(Ref::map(current?.borrow(), |node| &node.data))
+    }
+}
+
+impl<T: Eq> DoubleLinkedList<T> {
+    // The prev links let us walk inwards from both ends, nothing has to be reversed
+    pub fn is_palindrome(&self) -> bool {
+        let (mut left, mut right) = match (self.head.clone(), tail_of(self)) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return true,
+        };
+
+        for _ in 0..self.len / 2 {
+            if left.borrow().data != right.borrow().data {
+                return false;
+            }
+
+            left = next_of(&left).unwrap();
+            right = prev_of(&right).unwrap();
+        }
+
+        true
+    }
+
+    // Keeps the first occurrence of every value, O(n^2) time but no hash set
+    pub fn remove_duplicates(&mut self) {
+        let mut current = self.head.clone();
+
+        while let Some(node) = current {
+            let mut runner = next_of(&node);
+
+            while let Some(candidate) = runner {
+                runner = next_of(&candidate);
+
+                if candidate.borrow().data == node.borrow().data {
+                    self.unlink(&candidate);
+                }
+            }
+
+            current = next_of(&node);
+        }
+    }
+}
+
+impl<T: Ord> DoubleLinkedList<T> {
+    // Stable partition, everything smaller than the pivot comes first
+    pub fn partition(&mut self, pivot: &T) {
+        let mut smaller: (Option<Link<T>>, Option<Link<T>>) = (None, None);
+        let mut rest: (Option<Link<T>>, Option<Link<T>>) = (None, None);
+
+        let mut current = self.head.take();
+
+        while let Some(node) = current {
+            current = node.borrow_mut().next.take();
+            node.borrow_mut().prev = None;
+
+            let (head, tail) = if node.borrow().data < *pivot {
+                &mut smaller
+            } else {
+                &mut rest
+            };
+
+            match tail.take() {
+                Some(tail_node) => {
+                    node.borrow_mut().prev = Some(Rc::downgrade(&tail_node));
+                    tail_node.borrow_mut().next = Some(node.clone());
+                }
+                None => *head = Some(node.clone()),
+            }
+
+            *tail = Some(node);
+        }
+
+        self.tail = rest.1.as_ref().or(smaller.1.as_ref()).map(Rc::downgrade);
+
+        match smaller {
+            (Some(head), Some(tail)) => {
+                if let Some(ref rest_head) = rest.0 {
+                    rest_head.borrow_mut().prev = Some(Rc::downgrade(&tail));
+                }
+
+                tail.borrow_mut().next = rest.0;
+                self.head = Some(head);
+            }
+            _ => self.head = rest.0,
+        }
+    }
+}
+
+///////////// INDEX BASED LIST /////////////
+
+// The Box and Rc lists can't express a cycle without leaking or fighting the borrow checker,
+// so cycle detection works on a list where links are plain indices into a Vec
+
+struct IndexedNode<T> {
+    data: T,
+    next: Option<usize>,
+}
+
+pub struct IndexedLinkedList<T> {
+    nodes: Vec<IndexedNode<T>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+}
+
+impl<T> IndexedLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            head: None,
+            tail: None,
+        }
+    }
+
+    // Appends after the last pushed node and returns the index of the new node. That is the
+    // last pushed node even after `link` moved the end of the list or closed it into a cycle,
+    // its link gets overwritten, so build the list with push first and link it up after
+    pub fn push(&mut self, data: T) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(IndexedNode { data, next: None });
+
+        match self.tail {
+            Some(tail) => self.nodes[tail].next = Some(index),
+            None => self.head = Some(index),
+        }
+
+        self.tail = Some(index);
+        index
+    }
+
+    // Points `from` at any node, pointing it backwards is how a cycle is made
+    pub fn link(&mut self, from: usize, to: Option<usize>) {
+        // a dangling link would only blow up later, halfway through cycle detection
+        assert!(from < self.nodes.len(), "link from {} but the list has {} nodes", from, self.nodes.len());
+        if let Some(to) = to {
+            assert!(to < self.nodes.len(), "link to {} but the list has {} nodes", to, self.nodes.len());
+        }
+
+        self.nodes[from].next = to;
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.nodes.get(index).map(|node| &node.data)
+    }
+
+    fn step(&self, index: usize) -> Option<usize> {
+        self.nodes[index].next
+    }
+
+    // Floyd's algorithm, returns where the hare and the tortoise first meet
+    fn meeting_point(&self) -> Option<usize> {
+        let mut slow = self.head?;
+        let mut fast = self.head?;
+
+        loop {
+            slow = self.step(slow)?;
+            fast = self.step(self.step(fast)?)?;
+
+            if slow == fast {
+                return Some(fast);
+            }
+        }
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.meeting_point().is_some()
+    }
+
+    // Index of the first node inside the cycle, the head and the meeting point are
+    // the same distance away from it
+    pub fn cycle_start(&self) -> Option<usize> {
+        let mut meeting = self.meeting_point()?;
+        let mut from_head = self.head?;
+
+        while from_head != meeting {
+            from_head = self.step(from_head).unwrap();
+            meeting = self.step(meeting).unwrap();
+        }
+
+        Some(from_head)
+    }
+
+    pub fn cycle_length(&self) -> Option<usize> {
+        let meeting = self.meeting_point()?;
+        let mut current = self.step(meeting).unwrap();
+        let mut length = 1;
+
+        while current != meeting {
+            current = self.step(current).unwrap();
+            length += 1;
+        }
+
+        Some(length)
+    }
+}
+
+impl<T> Default for IndexedLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn singular_from(values: &[i32]) -> SingularLinkedList<i32> {
+        let mut list = SingularLinkedList::new();
+        for &value in values.iter().rev() {
+            list.insert_at_beginning(value);
+        }
+        list
+    }
+
+    fn double_from(values: &[i32]) -> DoubleLinkedList<i32> {
+        let mut list = DoubleLinkedList::new();
+        for &value in values {
+            list.insert_at_end(value);
+        }
+        list
+    }
+
+    fn singular_values(list: &SingularLinkedList<i32>) -> Vec<i32> {
+        list.iter().cloned().collect()
+    }
+
+    // Reads the list forwards and then backwards through the prev links
+    fn double_values(list: &DoubleLinkedList<i32>) -> Vec<i32> {
+        let forward: Vec<i32> = list.iter().map(|x| *x).collect();
+
+        let mut backward = vec![];
+        let mut current = tail_of(list);
+
+        while let Some(node) = current {
+            backward.push(node.borrow().data);
+            current = prev_of(&node);
+        }
+
+        backward.reverse();
+        assert_eq!(forward, backward, "prev links are out of sync");
+        assert_eq!(forward.len(), list.len());
+        forward
+    }
+
+    #[test]
+    fn test_singular_middle() {
+        assert_eq!(singular_from(&[]).middle(), None);
+        assert_eq!(singular_from(&[1]).middle(), Some(&1));
+        assert_eq!(singular_from(&[1, 2, 3]).middle(), Some(&2));
+        assert_eq!(singular_from(&[1, 2, 3, 4]).middle(), Some(&3));
+    }
+
+    #[test]
+    fn test_singular_kth_from_end() {
+        let list = singular_from(&[1, 2, 3, 4, 5]);
+        assert_eq!(list.kth_from_end(0), Some(&5));
+        assert_eq!(list.kth_from_end(4), Some(&1));
+        assert_eq!(list.kth_from_end(5), None);
+    }
+
+    #[test]
+    fn test_singular_palindrome_restores_list() {
+        let mut list = singular_from(&[1, 2, 3, 2, 1]);
+        assert!(list.is_palindrome());
+        assert_eq!(singular_values(&list), vec![1, 2, 3, 2, 1]);
+
+        let mut list = singular_from(&[1, 2, 2, 1]);
+        assert!(list.is_palindrome());
+
+        let mut list = singular_from(&[1, 2, 3, 1]);
+        assert!(!list.is_palindrome());
+        assert_eq!(singular_values(&list), vec![1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_singular_rotate() {
+        let mut list = singular_from(&[1, 2, 3, 4, 5]);
+        list.rotate_right(2);
+        assert_eq!(singular_values(&list), vec![4, 5, 1, 2, 3]);
+
+        list.rotate_left(7);
+        assert_eq!(singular_values(&list), vec![1, 2, 3, 4, 5]);
+
+        list.rotate_right(5);
+        assert_eq!(singular_values(&list), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_singular_remove_duplicates() {
+        let mut list = singular_from(&[3, 1, 3, 2, 1, 3]);
+        list.remove_duplicates();
+        assert_eq!(singular_values(&list), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_singular_partition() {
+        let mut list = singular_from(&[3, 5, 8, 5, 10, 2, 1]);
+        list.partition(&5);
+        assert_eq!(singular_values(&list), vec![3, 2, 1, 5, 8, 5, 10]);
+    }
+
+    #[test]
+    fn test_double_middle_and_kth() {
+        let list = double_from(&[1, 2, 3, 4]);
+        assert_eq!(list.middle().as_deref(), Some(&3));
+        assert_eq!(list.kth_from_end(1).as_deref(), Some(&3));
+        assert!(list.kth_from_end(4).is_none());
+        assert!(double_from(&[]).middle().is_none());
+    }
+
+    #[test]
+    fn test_double_palindrome() {
+        assert!(double_from(&[]).is_palindrome());
+        assert!(double_from(&[1, 2, 1]).is_palindrome());
+        assert!(double_from(&[4, 4]).is_palindrome());
+        assert!(!double_from(&[1, 2, 3]).is_palindrome());
+    }
+
+    #[test]
+    fn test_double_rotate_keeps_prev_links() {
+        let mut list = double_from(&[1, 2, 3, 4, 5]);
+        list.rotate_right(2);
+        assert_eq!(double_values(&list), vec![4, 5, 1, 2, 3]);
+
+        list.rotate_left(1);
+        assert_eq!(double_values(&list), vec![5, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_double_remove_duplicates_keeps_prev_links() {
+        let mut list = double_from(&[1, 2, 1, 3, 2, 2]);
+        list.remove_duplicates();
+        assert_eq!(double_values(&list), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_double_partition_keeps_prev_links() {
+        let mut list = double_from(&[7, 1, 9, 3, 5]);
+        list.partition(&5);
+        assert_eq!(double_values(&list), vec![1, 3, 7, 9, 5]);
+
+        let mut list = double_from(&[7, 9]);
+        list.partition(&1);
+        assert_eq!(double_values(&list), vec![7, 9]);
+    }
+
+    #[test]
+    fn test_indexed_without_cycle() {
+        let mut list = IndexedLinkedList::new();
+        for i in 0..5 {
+            list.push(i);
+        }
+
+        assert!(!list.has_cycle());
+        assert_eq!(list.cycle_start(), None);
+        assert_eq!(list.cycle_length(), None);
+    }
+
+    #[test]
+    fn test_indexed_cycle_detection() {
+        let mut list = IndexedLinkedList::new();
+        let nodes: Vec<usize> = (0..6).map(|i| list.push(i * 10)).collect();
+
+        // 0 -> 10 -> 20 -> 30 -> 40 -> 50 -> back to 20
+        list.link(nodes[5], Some(nodes[2]));
+
+        assert!(list.has_cycle());
+        assert_eq!(list.cycle_start(), Some(nodes[2]));
+        assert_eq!(list.get(list.cycle_start().unwrap()), Some(&20));
+        assert_eq!(list.cycle_length(), Some(4));
+    }
+
+    #[test]
+    fn test_indexed_self_loop() {
+        let mut list = IndexedLinkedList::new();
+        let only = list.push("a");
+        list.link(only, Some(only));
+
+        assert_eq!(list.cycle_start(), Some(only));
+        assert_eq!(list.cycle_length(), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "link to 3 but the list has 3 nodes")]
+    fn test_indexed_link_out_of_range() {
+        let mut list = IndexedLinkedList::new();
+        for i in 0..3 {
+            list.push(i);
+        }
+        list.link(2, Some(3));
+    }
+}
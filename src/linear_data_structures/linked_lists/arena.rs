@@ -0,0 +1,895 @@
+// Doubly linked list where the nodes live next to each other in one Vec and the links are
+// u32 indices into it. No Rc, no RefCell and no unsafe, removing a node just puts its slot
+// on a free list so the next insert can reuse it.
+// Every insert hands back a Handle, the slot index plus a generation counter. The generation
+// is bumped whenever a slot is freed, so a handle to a removed value can never point at
+// whatever got stored in that slot later.
+
+use core::fmt;
+use std::fmt::Debug;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const NIL: u32 = u32::MAX; // index used as "no node", like None for the Box/Rc lists
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Slot<T> {
+    value: Option<T>, // None while the slot sits on the free list
+    prev: u32,
+    next: u32, // for free slots this links the free list
+    generation: u32,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "UncheckedArena<T>",
+        bound(deserialize = "T: Deserialize<'de>")
+    )
+)]
+pub struct ArenaLinkedList<T> {
+    slots: Vec<Slot<T>>,
+    head: u32,
+    tail: u32,
+    free: u32,
+    len: usize,
+}
+
+// Same fields as ArenaLinkedList, deserialized as is and only turned into a list once the
+// links check out. Without this a bad index in the input would panic on the first access
+// and a cycle would make every walk over the list loop forever.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct UncheckedArena<T> {
+    slots: Vec<Slot<T>>,
+    head: u32,
+    tail: u32,
+    free: u32,
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<UncheckedArena<T>> for ArenaLinkedList<T> {
+    type Error = String;
+
+    fn try_from(raw: UncheckedArena<T>) -> Result<Self, Self::Error> {
+        let UncheckedArena {
+            slots,
+            head,
+            tail,
+            free,
+            len,
+        } = raw;
+
+        if slots.len() > NIL as usize {
+            return Err(format!("{} slots, the limit is {}", slots.len(), NIL));
+        }
+
+        // Every slot has to show up exactly once, either in the list or on the free list
+        let mut seen = vec![false; slots.len()];
+        let mut visit = |index: u32, what: &str| match seen.get_mut(index as usize) {
+            None => Err(format!("{} points at slot {} which does not exist", what, index)),
+            Some(true) => Err(format!("slot {} is reached twice", index)),
+            Some(seen) => {
+                *seen = true;
+                Ok(())
+            }
+        };
+
+        let (mut prev, mut current, mut live) = (NIL, head, 0);
+        while current != NIL {
+            visit(current, "the list")?;
+            let slot = &slots[current as usize];
+
+            if slot.value.is_none() {
+                return Err(format!("slot {} is linked but has no value", current));
+            }
+            if slot.prev != prev {
+                return Err(format!("slot {} has prev {} instead of {}", current, slot.prev, prev));
+            }
+
+            (prev, current, live) = (current, slot.next, live + 1);
+        }
+
+        if prev != tail {
+            return Err(format!("tail is {} but the list ends at {}", tail, prev));
+        }
+        if live != len {
+            return Err(format!("len is {} but the list has {} values", len, live));
+        }
+
+        let mut current = free;
+        while current != NIL {
+            visit(current, "the free list")?;
+            let slot = &slots[current as usize];
+
+            if slot.value.is_some() {
+                return Err(format!("slot {} is on the free list but holds a value", current));
+            }
+
+            current = slot.next;
+        }
+
+        if let Some(index) = seen.iter().position(|&seen| !seen) {
+            return Err(format!("slot {} is neither in the list nor free", index));
+        }
+
+        Ok(Self {
+            slots,
+            head,
+            tail,
+            free,
+            len,
+        })
+    }
+}
+
+impl<T> ArenaLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            free: NIL,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Index of the slot if the handle still refers to a live value
+    fn resolve(&self, handle: Handle) -> Option<u32> {
+        let slot = self.slots.get(handle.index as usize)?;
+
+        if slot.generation == handle.generation && slot.value.is_some() {
+            Some(handle.index)
+        } else {
+            None
+        }
+    }
+
+    fn handle_of(&self, index: u32) -> Option<Handle> {
+        if index == NIL {
+            return None;
+        }
+
+        Some(Handle {
+            index,
+            generation: self.slots[index as usize].generation,
+        })
+    }
+
+    // Takes a slot from the free list (or grows the Vec) and stores the value in it,
+    // the slot is not linked into the list yet
+    fn allocate(&mut self, value: T) -> u32 {
+        if self.free != NIL {
+            let index = self.free;
+            let slot = &mut self.slots[index as usize];
+            self.free = slot.next;
+            slot.value = Some(value);
+            return index;
+        }
+
+        let index = u32::try_from(self.slots.len())
+            .ok()
+            .filter(|&index| index != NIL)
+            .expect("arena linked list is limited to u32::MAX - 1 slots");
+
+        self.slots.push(Slot {
+            value: Some(value),
+            prev: NIL,
+            next: NIL,
+            generation: 0,
+        });
+
+        index
+    }
+
+    // Puts the detached slot `index` between `prev` and `next`, either of them may be NIL
+    fn link(&mut self, index: u32, prev: u32, next: u32) {
+        self.slots[index as usize].prev = prev;
+        self.slots[index as usize].next = next;
+
+        match prev {
+            NIL => self.head = index,
+            _ => self.slots[prev as usize].next = index,
+        }
+
+        match next {
+            NIL => self.tail = index,
+            _ => self.slots[next as usize].prev = index,
+        }
+    }
+
+    fn unlink(&mut self, index: u32) {
+        let Slot { prev, next, .. } = self.slots[index as usize];
+
+        match prev {
+            NIL => self.head = next,
+            _ => self.slots[prev as usize].next = next,
+        }
+
+        match next {
+            NIL => self.tail = prev,
+            _ => self.slots[next as usize].prev = prev,
+        }
+    }
+
+    fn insert_between(&mut self, value: T, prev: u32, next: u32) -> Handle {
+        let index = self.allocate(value);
+        self.link(index, prev, next);
+        self.len += 1;
+        self.handle_of(index).unwrap()
+    }
+
+    fn remove_index(&mut self, index: u32) -> T {
+        self.unlink(index);
+        self.len -= 1;
+
+        let slot = &mut self.slots[index as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.prev = NIL;
+        slot.next = self.free;
+        self.free = index;
+
+        slot.value.take().unwrap()
+    }
+
+    pub fn push_front(&mut self, value: T) -> Handle {
+        self.insert_between(value, NIL, self.head)
+    }
+
+    pub fn push_back(&mut self, value: T) -> Handle {
+        self.insert_between(value, self.tail, NIL)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        match self.head {
+            NIL => None,
+            head => Some(self.remove_index(head)),
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        match self.tail {
+            NIL => None,
+            tail => Some(self.remove_index(tail)),
+        }
+    }
+
+    // Returns None when the handle was already removed
+    pub fn insert_before(&mut self, handle: Handle, value: T) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        let prev = self.slots[index as usize].prev;
+        Some(self.insert_between(value, prev, index))
+    }
+
+    pub fn insert_after(&mut self, handle: Handle, value: T) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        let next = self.slots[index as usize].next;
+        Some(self.insert_between(value, index, next))
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let index = self.resolve(handle)?;
+        Some(self.remove_index(index))
+    }
+
+    // The handle stays the same, only the links change
+    pub fn move_to_front(&mut self, handle: Handle) -> bool {
+        match self.resolve(handle) {
+            Some(index) => {
+                if index != self.head {
+                    self.unlink(index);
+                    self.link(index, NIL, self.head);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn move_to_back(&mut self, handle: Handle) -> bool {
+        match self.resolve(handle) {
+            Some(index) => {
+                if index != self.tail {
+                    self.unlink(index);
+                    self.link(index, self.tail, NIL);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.resolve(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        let index = self.resolve(handle)?;
+        self.slots[index as usize].value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        let index = self.resolve(handle)?;
+        self.slots[index as usize].value.as_mut()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.front_handle().and_then(|handle| self.get(handle))
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.back_handle().and_then(|handle| self.get(handle))
+    }
+
+    pub fn front_handle(&self) -> Option<Handle> {
+        self.handle_of(self.head)
+    }
+
+    pub fn back_handle(&self) -> Option<Handle> {
+        self.handle_of(self.tail)
+    }
+
+    pub fn next_handle(&self, handle: Handle) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        self.handle_of(self.slots[index as usize].next)
+    }
+
+    pub fn prev_handle(&self, handle: Handle) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        self.handle_of(self.slots[index as usize].prev)
+    }
+
+    // Drops every value but keeps the allocation, old handles all become invalid
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            next: self.head,
+            next_back: self.tail,
+            remaining: self.len,
+        }
+    }
+
+    // Same order as `iter`, but paired with the handle of every value
+    pub fn handles(&self) -> Handles<'_, T> {
+        Handles { inner: self.iter() }
+    }
+}
+
+impl<T> Default for ArenaLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for ArenaLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+
+        for value in iter {
+            list.push_back(value);
+        }
+
+        list
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a ArenaLinkedList<T>,
+    next: u32,
+    next_back: u32,
+    remaining: usize, // stops the two ends from walking past each other
+}
+
+pub struct Handles<'a, T> {
+    inner: Iter<'a, T>,
+}
+
+impl<T> Iter<'_, T> {
+    // Both directions hand out slot indices, the Iterator impls turn them into values
+    fn advance(&mut self) -> Option<u32> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        let index = self.next;
+        self.next = self.list.slots[index as usize].next;
+        Some(index)
+    }
+
+    fn advance_back(&mut self) -> Option<u32> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        let index = self.next_back;
+        self.next_back = self.list.slots[index as usize].prev;
+        Some(index)
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let list = self.list;
+        self.advance()
+            .and_then(|index| list.slots[index as usize].value.as_ref())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let list = self.list;
+        self.advance_back()
+            .and_then(|index| list.slots[index as usize].value.as_ref())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> Iterator for Handles<'a, T> {
+    type Item = (Handle, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let list = self.inner.list;
+        let index = self.inner.advance()?;
+        Some((list.handle_of(index)?, list.slots[index as usize].value.as_ref()?))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Handles<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let list = self.inner.list;
+        let index = self.inner.advance_back()?;
+        Some((list.handle_of(index)?, list.slots[index as usize].value.as_ref()?))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArenaLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Debug> fmt::Display for ArenaLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_list = f.debug_list();
+
+        if self.is_empty() {
+            debug_list.entry(&"Empty");
+        } else {
+            debug_list.entries(self.iter());
+        }
+
+        debug_list.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property::{check, ensure_eq, shrink_value, Operation, Rng};
+    use std::collections::VecDeque;
+
+    fn values(list: &ArenaLinkedList<i32>) -> Vec<i32> {
+        let forward: Vec<i32> = list.iter().copied().collect();
+        let mut backward: Vec<i32> = list.iter().rev().copied().collect();
+        backward.reverse();
+
+        assert_eq!(forward, backward, "prev links are out of sync");
+        assert_eq!(forward.len(), list.len());
+        forward
+    }
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut list = ArenaLinkedList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+
+        assert_eq!(values(&list), vec![1, 2, 3]);
+        assert_eq!(format!("{}", list), "[1, 2, 3]");
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(format!("{}", list), "[\"Empty\"]");
+    }
+
+    #[test]
+    fn test_remove_by_handle() {
+        let mut list = ArenaLinkedList::new();
+        let a = list.push_back(1);
+        let b = list.push_back(2);
+        let c = list.push_back(3);
+
+        assert_eq!(list.remove(b), Some(2));
+        assert_eq!(values(&list), vec![1, 3]);
+
+        assert_eq!(list.remove(a), Some(1));
+        assert_eq!(list.remove(c), Some(3));
+        assert!(list.is_empty());
+        assert_eq!(list.front_handle(), None);
+    }
+
+    #[test]
+    fn test_stale_handle_is_rejected() {
+        let mut list = ArenaLinkedList::new();
+        let old = list.push_back(1);
+        list.remove(old);
+
+        // reuses the freed slot, but with a new generation
+        let new = list.push_back(2);
+
+        assert_eq!(list.get(old), None);
+        assert_eq!(list.remove(old), None);
+        assert!(!list.move_to_front(old));
+        assert_eq!(list.insert_after(old, 5), None);
+        assert_eq!(list.get(new), Some(&2));
+        assert_eq!(list.slots.len(), 1);
+    }
+
+    #[test]
+    fn test_insert_before_and_after() {
+        let mut list = ArenaLinkedList::new();
+        let middle = list.push_back(2);
+        list.insert_before(middle, 1).unwrap();
+        let last = list.insert_after(middle, 4).unwrap();
+        list.insert_before(last, 3).unwrap();
+        list.insert_after(last, 5).unwrap();
+
+        assert_eq!(values(&list), vec![1, 2, 3, 4, 5]);
+        assert_eq!(list.back(), Some(&5));
+    }
+
+    #[test]
+    fn test_move_to_front_and_back() {
+        let mut list = ArenaLinkedList::new();
+        let handles: Vec<Handle> = (1..=4).map(|i| list.push_back(i)).collect();
+
+        assert!(list.move_to_front(handles[2]));
+        assert_eq!(values(&list), vec![3, 1, 2, 4]);
+
+        assert!(list.move_to_front(handles[2]));
+        assert!(list.move_to_back(handles[0]));
+        assert_eq!(values(&list), vec![3, 2, 4, 1]);
+
+        assert!(list.move_to_front(handles[0]));
+        assert_eq!(values(&list), vec![1, 3, 2, 4]);
+        assert_eq!(list.get(handles[0]), Some(&1));
+    }
+
+    #[test]
+    fn test_handle_navigation() {
+        let list: ArenaLinkedList<i32> = (1..=3).collect();
+        let first = list.front_handle().unwrap();
+        let second = list.next_handle(first).unwrap();
+
+        assert_eq!(list.get(second), Some(&2));
+        assert_eq!(list.prev_handle(second), Some(first));
+        assert_eq!(list.prev_handle(first), None);
+
+        let pairs: Vec<(Handle, i32)> = list.handles().map(|(h, v)| (h, *v)).collect();
+        assert_eq!(pairs[1], (second, 2));
+        assert_eq!(pairs.len(), 3);
+    }
+
+    #[test]
+    fn test_get_mut_and_clear() {
+        let mut list = ArenaLinkedList::new();
+        let handle = list.push_back(String::from("a"));
+        list.get_mut(handle).unwrap().push('b');
+        assert_eq!(list.front().map(String::as_str), Some("ab"));
+
+        list.clear();
+        assert!(list.is_empty());
+        assert!(!list.contains(handle));
+
+        list.push_back(String::from("c"));
+        assert_eq!(list.slots.len(), 1);
+    }
+
+    #[test]
+    fn test_clone_and_send() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let mut list: ArenaLinkedList<i32> = (1..=3).collect();
+        let handle = list.front_handle().unwrap();
+        let copy = list.clone();
+        list.remove(handle);
+
+        assert_send(&copy);
+        assert_eq!(values(&copy), vec![1, 2, 3]);
+        assert_eq!(copy.get(handle), Some(&1));
+        assert_eq!(values(&list), vec![2, 3]);
+    }
+
+    // LRU cache on top of the list, the motivating use case
+    #[test]
+    fn test_lru_usage() {
+        use std::collections::HashMap;
+
+        let capacity = 2;
+        let mut order: ArenaLinkedList<&str> = ArenaLinkedList::new();
+        let mut lookup: HashMap<&str, Handle> = HashMap::new();
+
+        for key in ["a", "b", "a", "c", "b"] {
+            match lookup.get(key) {
+                Some(&handle) => {
+                    order.move_to_front(handle);
+                }
+                None => {
+                    if order.len() == capacity {
+                        let evicted = order.pop_back().unwrap();
+                        lookup.remove(evicted);
+                    }
+                    lookup.insert(key, order.push_front(key));
+                }
+            }
+        }
+
+        assert_eq!(order.iter().copied().collect::<Vec<_>>(), vec!["b", "c"]);
+    }
+
+    // Positions pick an element of the model modulo its length, so every handle operation
+    // hits a live node. Removed handles are kept around to check they stay dead
+    #[derive(Clone, Debug)]
+    enum Op {
+        PushFront(i32),
+        PushBack(i32),
+        PopFront,
+        PopBack,
+        InsertBefore(usize, i32),
+        InsertAfter(usize, i32),
+        Remove(usize),
+        MoveToFront(usize),
+        MoveToBack(usize),
+        Set(usize, i32),
+        Clear,
+    }
+
+    impl Operation for Op {
+        fn generate(rng: &mut Rng) -> Self {
+            let position = rng.below(64);
+
+            match rng.below(22) {
+                0..=3 => Op::PushFront(rng.value(9)),
+                4..=7 => Op::PushBack(rng.value(9)),
+                8 | 9 => Op::PopFront,
+                10 | 11 => Op::PopBack,
+                12 => Op::InsertBefore(position, rng.value(9)),
+                13 => Op::InsertAfter(position, rng.value(9)),
+                14..=16 => Op::Remove(position),
+                17 => Op::MoveToFront(position),
+                18 => Op::MoveToBack(position),
+                19 | 20 => Op::Set(position, rng.value(9)),
+                _ => Op::Clear,
+            }
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            match *self {
+                Op::PushFront(value) => shrink_value(value).into_iter().map(Op::PushFront).collect(),
+                Op::PushBack(value) => shrink_value(value).into_iter().map(Op::PushBack).collect(),
+                _ => vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_vec_deque() {
+        check::<Op, _>("ArenaLinkedList against VecDeque", |ops| {
+            let mut list = ArenaLinkedList::new();
+            let mut model: VecDeque<(Handle, i32)> = VecDeque::new();
+            let mut dead: Vec<Handle> = vec![];
+
+            for (step, op) in ops.iter().enumerate() {
+                let pick = |position: usize| position % model.len().max(1);
+
+                match *op {
+                    Op::PushFront(value) => model.push_front((list.push_front(value), value)),
+                    Op::PushBack(value) => model.push_back((list.push_back(value), value)),
+                    Op::PopFront => {
+                        let expected = model.pop_front();
+                        dead.extend(expected.map(|(handle, _)| handle));
+                        ensure_eq!(list.pop_front(), expected.map(|(_, value)| value), "step {} pop_front", step);
+                    }
+                    Op::PopBack => {
+                        let expected = model.pop_back();
+                        dead.extend(expected.map(|(handle, _)| handle));
+                        ensure_eq!(list.pop_back(), expected.map(|(_, value)| value), "step {} pop_back", step);
+                    }
+                    Op::InsertBefore(position, value) if !model.is_empty() => {
+                        let index = pick(position);
+                        let handle = list.insert_before(model[index].0, value);
+                        ensure_eq!(handle.is_some(), true, "step {} insert_before", step);
+                        model.insert(index, (handle.unwrap(), value));
+                    }
+                    Op::InsertAfter(position, value) if !model.is_empty() => {
+                        let index = pick(position);
+                        let handle = list.insert_after(model[index].0, value);
+                        ensure_eq!(handle.is_some(), true, "step {} insert_after", step);
+                        model.insert(index + 1, (handle.unwrap(), value));
+                    }
+                    Op::Remove(position) if !model.is_empty() => {
+                        let (handle, value) = model.remove(pick(position)).unwrap();
+                        dead.push(handle);
+                        ensure_eq!(list.remove(handle), Some(value), "step {} remove", step);
+                    }
+                    Op::MoveToFront(position) if !model.is_empty() => {
+                        let entry = model.remove(pick(position)).unwrap();
+                        ensure_eq!(list.move_to_front(entry.0), true, "step {} move_to_front", step);
+                        model.push_front(entry);
+                    }
+                    Op::MoveToBack(position) if !model.is_empty() => {
+                        let entry = model.remove(pick(position)).unwrap();
+                        ensure_eq!(list.move_to_back(entry.0), true, "step {} move_to_back", step);
+                        model.push_back(entry);
+                    }
+                    Op::Set(position, value) if !model.is_empty() => {
+                        let index = pick(position);
+                        *list.get_mut(model[index].0).unwrap() = value;
+                        model[index].1 = value;
+                    }
+                    Op::Clear => {
+                        list.clear();
+                        dead.extend(model.drain(..).map(|(handle, _)| handle));
+                    }
+                    _ => {}
+                }
+
+                let expected: Vec<i32> = model.iter().map(|&(_, value)| value).collect();
+                let mut backward: Vec<i32> = list.iter().rev().copied().collect();
+                backward.reverse();
+
+                ensure_eq!(list.len(), model.len(), "step {} len", step);
+                ensure_eq!(list.iter().copied().collect::<Vec<_>>(), expected, "step {} contents", step);
+                ensure_eq!(backward, expected, "step {} backward", step);
+                ensure_eq!(
+                    list.handles().map(|(handle, _)| handle).collect::<Vec<_>>(),
+                    model.iter().map(|&(handle, _)| handle).collect::<Vec<_>>(),
+                    "step {} handles",
+                    step
+                );
+
+                for &(handle, value) in &model {
+                    ensure_eq!(list.get(handle), Some(&value), "step {} get", step);
+                }
+
+                for &handle in &dead {
+                    ensure_eq!(list.contains(handle), false, "step {} stale handle", step);
+                }
+            }
+
+            Ok(())
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use super::*;
+        use serde_json::{json, Value};
+
+        // A list with a freed slot in the middle, so the free list is not empty
+        fn sample() -> (ArenaLinkedList<i32>, Handle) {
+            let mut list: ArenaLinkedList<i32> = (1..=4).collect();
+            let second = list.next_handle(list.front_handle().unwrap()).unwrap();
+            list.remove(second);
+
+            let back = list.back_handle().unwrap();
+            (list, back)
+        }
+
+        fn rejects(edit: impl FnOnce(&mut Value)) -> String {
+            let mut value = serde_json::to_value(&sample().0).unwrap();
+            edit(&mut value);
+
+            match serde_json::from_value::<ArenaLinkedList<i32>>(value) {
+                Ok(list) => panic!("accepted a broken list: {}", list),
+                Err(error) => error.to_string(),
+            }
+        }
+
+        #[test]
+        fn test_round_trip() {
+            let (list, back) = sample();
+            let json = serde_json::to_string(&list).unwrap();
+            let mut copy: ArenaLinkedList<i32> = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(values(&copy), vec![1, 3, 4]);
+            assert_eq!(copy.get(back), Some(&4));
+
+            // The free slot is reused just like in the original
+            let handle = copy.push_front(0);
+            assert_eq!(copy.slots.len(), 4);
+            assert_eq!(values(&copy), vec![0, 1, 3, 4]);
+            assert_eq!(copy.get(handle), Some(&0));
+        }
+
+        #[test]
+        fn test_rejects_out_of_range_link() {
+            let error = rejects(|value| value["slots"][0]["next"] = json!(9));
+            assert!(error.contains("slot 9 which does not exist"), "{}", error);
+        }
+
+        #[test]
+        fn test_rejects_cycle() {
+            let error = rejects(|value| value["slots"][3]["next"] = json!(0));
+            assert!(error.contains("reached twice"), "{}", error);
+        }
+
+        #[test]
+        fn test_rejects_wrong_prev_and_tail() {
+            let error = rejects(|value| value["slots"][2]["prev"] = json!(3));
+            assert!(error.contains("prev 3 instead of 0"), "{}", error);
+
+            let error = rejects(|value| value["tail"] = json!(2));
+            assert!(error.contains("tail is 2"), "{}", error);
+        }
+
+        #[test]
+        fn test_rejects_wrong_len() {
+            let error = rejects(|value| value["len"] = json!(4));
+            assert!(error.contains("len is 4"), "{}", error);
+        }
+
+        #[test]
+        fn test_rejects_broken_free_list() {
+            let error = rejects(|value| value["slots"][1]["value"] = json!(2));
+            assert!(error.contains("free list but holds a value"), "{}", error);
+
+            let error = rejects(|value| value["free"] = json!(u32::MAX));
+            assert!(error.contains("slot 1 is neither"), "{}", error);
+
+            let error = rejects(|value| value["slots"][0]["value"] = Value::Null);
+            assert!(error.contains("linked but has no value"), "{}", error);
+        }
+    }
+}
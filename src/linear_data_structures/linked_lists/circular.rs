@@ -1,8 +1,13 @@
+use core::fmt;
 use std::{
-    cell::RefCell,
+    cell::{Ref, RefCell, RefMut},
+    fmt::Debug,
     rc::{Rc, Weak},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
+use crate::node_ref::node_ref;
+
 #[derive(Clone)]
 struct Node<T> {
     data: T,
@@ -12,19 +17,35 @@ struct Node<T> {
 
 // Weak and RefCell are used to not create reference cycles
 // I did not expect them to be this different
+// The prev links are Weak, but following next all the way around still comes back to head,
+// so the ring itself is a strong cycle. Whoever takes nodes out (delete, clear, drop) has to
+// cut the link that points back or the nodes are never freed.
 
 pub struct CircularLinkedList<T> {
     head: Option<Rc<RefCell<Node<T>>>>,
-    length: u64,
+    length: usize,
+    id: u64, // lets handles prove they came from this list
 }
 
-impl<T: Eq + Clone> CircularLinkedList<T> {
-    pub fn new() -> Self {
-        Self {
-            head: None,
-            length: 0,
+// Points at one node so it can be read or removed in O(1) without searching the ring.
+// The Weak stops working on its own once the node is removed and freed
+pub struct NodeHandle<T> {
+    node: Weak<RefCell<Node<T>>>,
+    list_id: u64,
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        NodeHandle {
+            node: self.node.clone(),
+            list_id: self.list_id,
         }
     }
+}
+
+static NEXT_LIST_ID: AtomicU64 = AtomicU64::new(0);
+
+impl<T: Eq + Clone> CircularLinkedList<T> {
 
     pub fn insert_at_end(&mut self, data: T) {
         match &self.head {
@@ -141,6 +162,8 @@ impl<T: Eq + Clone> CircularLinkedList<T> {
                 let next = current.borrow().next.clone().unwrap();
 
                 if Rc::ptr_eq(&current, &next) {
+                    // the only node points at itself, that link has to go too
+                    current.borrow_mut().next = None;
                     self.head = None;
                 } else {
                     prev.borrow_mut().next = Some(next.clone());
@@ -199,9 +222,308 @@ impl<T: Eq + Clone> CircularLinkedList<T> {
     }
 }
 
+impl<T> CircularLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            length: 0,
+            id: NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    // The end of the ring is just behind the head
+    pub fn push_back(&mut self, data: T) {
+        self.insert_with_handle(data);
+    }
+
+    // Same as push_back, then the head steps back onto the new node
+    pub fn push_front(&mut self, data: T) {
+        self.insert_with_handle(data);
+        self.rotate_backward(1);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head_node = self.head.clone()?;
+        Some(self.unlink(head_node))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail_node = self.head.as_ref()?.borrow().prev.as_ref().and_then(|weak| weak.upgrade())?;
+        Some(self.unlink(tail_node))
+    }
+
+    // Moving the head around the ring is all a rotation is, no node gets touched
+    pub fn rotate_forward(&mut self, k: usize) {
+        if self.length == 0 {
+            return;
+        }
+
+        let mut current = self.head.clone().unwrap();
+
+        for _ in 0..k % self.length {
+            let next = current.borrow().next.clone().unwrap();
+            current = next;
+        }
+
+        self.head = Some(current);
+    }
+
+    pub fn rotate_backward(&mut self, k: usize) {
+        if self.length == 0 {
+            return;
+        }
+
+        let mut current = self.head.clone().unwrap();
+
+        for _ in 0..k % self.length {
+            let prev = current.borrow().prev.as_ref().and_then(|weak| weak.upgrade()).unwrap();
+            current = prev;
+        }
+
+        self.head = Some(current);
+    }
+
+    // Counts k nodes starting at the head (the head itself is 1), removes the k-th and
+    // makes the node after it the new head. Calling it again with the same k plays the
+    // Josephus elimination game, k = 0 is treated like k = 1
+    pub fn advance_and_remove(&mut self, k: usize) -> Option<T> {
+        if self.length == 0 {
+            return None;
+        }
+
+        self.rotate_forward(k.saturating_sub(1));
+
+        let removed = self.head.take().unwrap();
+        Some(self.unlink(removed))
+    }
+
+    // Takes `node` out of the ring and hands back its data, if it was the head the
+    // next node takes over
+    fn unlink(&mut self, node: Rc<RefCell<Node<T>>>) -> T {
+        let next = node.borrow_mut().next.take().unwrap();
+        let prev = node.borrow_mut().prev.take().and_then(|weak| weak.upgrade()).unwrap();
+
+        if Rc::ptr_eq(&node, &next) {
+            self.head = None;
+        } else {
+            prev.borrow_mut().next = Some(next.clone());
+            next.borrow_mut().prev = Some(Rc::downgrade(&prev));
+
+            if self.head.as_ref().is_none_or(|head| Rc::ptr_eq(head, &node)) {
+                self.head = Some(next.clone());
+            }
+        }
+
+        self.length -= 1;
+
+        // with both links cut (and the neighbours released, for a single node they are the
+        // node itself) the local is the last strong reference
+        drop(prev);
+        drop(next);
+        match Rc::try_unwrap(node) {
+            Ok(cell) => cell.into_inner().data,
+            Err(_) => unreachable!("a removed node is not referenced by the ring anymore"),
+        }
+    }
+
+    // Inserts just behind the head (the end of the ring) and returns a handle to the new node
+    pub fn insert_with_handle(&mut self, data: T) -> NodeHandle<T> {
+        let node = Rc::new(RefCell::new(Node {
+            data,
+            next: None,
+            prev: None,
+        }));
+
+        match self.head.clone() {
+            Some(head_node) => {
+                let tail_node = head_node.borrow().prev.as_ref().and_then(|weak| weak.upgrade()).unwrap();
+
+                node.borrow_mut().prev = Some(Rc::downgrade(&tail_node));
+                node.borrow_mut().next = Some(head_node.clone());
+                tail_node.borrow_mut().next = Some(node.clone());
+                head_node.borrow_mut().prev = Some(Rc::downgrade(&node));
+            }
+            None => {
+                node.borrow_mut().next = Some(node.clone());
+                node.borrow_mut().prev = Some(Rc::downgrade(&node));
+                self.head = Some(node.clone());
+            }
+        }
+
+        self.length += 1;
+
+        NodeHandle {
+            node: Rc::downgrade(&node),
+            list_id: self.id,
+        }
+    }
+
+    pub fn head_handle(&self) -> Option<NodeHandle<T>> {
+        self.head.as_ref().map(|head_node| NodeHandle {
+            node: Rc::downgrade(head_node),
+            list_id: self.id,
+        })
+    }
+
+    // A live node reached through one of our own handles is always part of this ring
+    fn resolve(&self, handle: &NodeHandle<T>) -> Option<Rc<RefCell<Node<T>>>> {
+        if handle.list_id != self.id {
+            return None;
+        }
+
+        handle.node.upgrade()
+    }
+
+    pub fn contains_node(&self, handle: &NodeHandle<T>) -> bool {
+        self.resolve(handle).is_some()
+    }
+
+    pub fn get(&self, handle: &NodeHandle<T>) -> Option<Ref<'_, T>> {
+        // SAFETY: resolve only upgrades handles of nodes still in this ring, and the ring
+        // keeps them alive while it's borrowed
+        let node: &RefCell<Node<T>> = unsafe { node_ref(Rc::as_ptr(&self.resolve(handle)?)) };
+        Some(Ref::map(node.borrow(), |node| &node.data))
+    }
+
+    pub fn get_mut(&mut self, handle: &NodeHandle<T>) -> Option<RefMut<'_, T>> {
+        // SAFETY: as in get
+        let node: &RefCell<Node<T>> = unsafe { node_ref(Rc::as_ptr(&self.resolve(handle)?)) };
+        Some(RefMut::map(node.borrow_mut(), |node| &mut node.data))
+    }
+
+    // O(1), the handle already knows both neighbours through the node
+    pub fn remove_node(&mut self, handle: &NodeHandle<T>) -> Option<T> {
+        let node = self.resolve(handle)?;
+        Some(self.unlink(node))
+    }
+
+    // One lap around the ring starting at the head
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            remaining: self.length,
+        }
+    }
+
+    // Keeps going around the ring forever, or yields nothing when the list is empty
+    pub fn cycle_iter(&self) -> CycleIter<'_, T> {
+        CycleIter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        if let Some(head_node) = self.head.take() {
+            let tail_node = head_node.borrow().prev.as_ref().and_then(|weak| weak.upgrade());
+
+            if let Some(tail_node) = tail_node {
+                tail_node.borrow_mut().next = None;
+            }
+
+            // unlinking one node at a time, dropping the head alone would free the
+            // chain recursively and can run out of stack on long lists
+            let mut current = Some(head_node);
+
+            while let Some(node) = current {
+                current = node.borrow_mut().next.take();
+            }
+        }
+
+        self.length = 0;
+    }
+}
+
+impl<T> Drop for CircularLinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for CircularLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Same idea as the iterators in double.rs, the guards point straight into the nodes
+
+// SAFETY: every node is owned by the ring and the links are only rewritten by methods taking
+// `&mut self`, so for as long as `node` is borrowed out of the list the next one stays alive
+fn next_ref<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    node.borrow().next.as_ref().map(|rc| unsafe { node_ref(Rc::as_ptr(rc)) })
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a RefCell<Node<T>>>,
+    remaining: usize, // the ring never ends on its own, so count the lap
+}
+
+pub struct CycleIter<'a, T> {
+    next: Option<&'a RefCell<Node<T>>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next.take().map(|current| {
+            self.next = next_ref(current);
+            Ref::map(current.borrow(), |node| &node.data)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> Iterator for CycleIter<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|current| {
+            self.next = next_ref(current);
+            Ref::map(current.borrow(), |node| &node.data)
+        })
+    }
+}
+
+impl<T: Debug> fmt::Display for CircularLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_list = f.debug_list();
+
+        if self.is_empty() {
+            debug_list.entry(&"Empty");
+        } else {
+            for data in self.iter() {
+                debug_list.entry(&*data);
+            }
+        }
+
+        debug_list.finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::linear_data_structures::linked_lists::model::{check_list, ModelList};
 
     #[test]
     fn test_insert_at_beginning_single_element() {
@@ -490,4 +812,289 @@ mod tests {
         assert!(list.head.is_none());
         assert_eq!(list.length, 0);
     }
+
+    use std::cell::Cell;
+
+    // Counts how many values are alive, every clone is one more and every drop one less
+    #[derive(Debug)]
+    struct Tracked {
+        id: i32,
+        live: Rc<Cell<i64>>,
+    }
+
+    impl Tracked {
+        fn new(id: i32, live: &Rc<Cell<i64>>) -> Self {
+            live.set(live.get() + 1);
+            Tracked {
+                id,
+                live: live.clone(),
+            }
+        }
+    }
+
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            Tracked::new(self.id, &self.live)
+        }
+    }
+
+    impl PartialEq for Tracked {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for Tracked {}
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.live.set(self.live.get() - 1);
+        }
+    }
+
+    fn tracked_list(ids: &[i32], live: &Rc<Cell<i64>>) -> CircularLinkedList<Tracked> {
+        let mut list = CircularLinkedList::new();
+        for &id in ids {
+            list.insert_at_end(Tracked::new(id, live));
+        }
+        list
+    }
+
+    #[test]
+    fn test_drop_frees_every_node() {
+        let live = Rc::new(Cell::new(0));
+
+        let list = tracked_list(&[1, 2, 3], &live);
+        assert_eq!(live.get(), 3);
+
+        drop(list);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn test_single_node_ring_is_freed() {
+        let live = Rc::new(Cell::new(0));
+
+        drop(tracked_list(&[1], &live));
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn test_delete_frees_the_removed_node() {
+        let live = Rc::new(Cell::new(0));
+        let mut list = tracked_list(&[1, 2, 3], &live);
+
+        list.delete(Tracked::new(2, &live));
+        assert_eq!(live.get(), 2);
+
+        list.delete(Tracked::new(1, &live));
+        list.delete(Tracked::new(3, &live));
+        assert_eq!(live.get(), 0);
+        assert!(list.head.is_none());
+    }
+
+    #[test]
+    fn test_no_leak_after_insert_reverse_and_drop() {
+        let live = Rc::new(Cell::new(0));
+
+        {
+            let mut list = tracked_list(&[1, 2], &live);
+            list.insert_at_beginning(Tracked::new(0, &live));
+            assert!(list.insert_after(Tracked::new(1, &live), Tracked::new(5, &live)));
+            list.reverse();
+            list.delete(Tracked::new(5, &live));
+            assert_eq!(live.get(), 3);
+        }
+
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn test_clear_and_reuse() {
+        let live = Rc::new(Cell::new(0));
+        let mut list = tracked_list(&[1, 2, 3], &live);
+
+        list.clear();
+        assert_eq!(live.get(), 0);
+        assert_eq!(list.length, 0);
+        assert!(list.head.is_none());
+
+        list.insert_at_end(Tracked::new(4, &live));
+        assert_eq!(list.length, 1);
+        assert_eq!(live.get(), 1);
+    }
+
+    #[test]
+    fn test_long_ring_drops_without_recursion() {
+        let mut list = CircularLinkedList::new();
+        for i in 0..200_000 {
+            list.insert_at_beginning(i);
+        }
+        drop(list);
+    }
+
+    fn ring(values: &[i32]) -> CircularLinkedList<i32> {
+        let mut list = CircularLinkedList::new();
+        for &value in values {
+            list.insert_at_end(value);
+        }
+        list
+    }
+
+    fn lap(list: &CircularLinkedList<i32>) -> Vec<i32> {
+        list.iter().map(|data| *data).collect()
+    }
+
+    #[test]
+    fn test_iter_and_display() {
+        let list = ring(&[1, 2, 3]);
+        assert_eq!(lap(&list), vec![1, 2, 3]);
+        assert_eq!(list.iter().len(), 3);
+        assert_eq!(format!("{}", list), "[1, 2, 3]");
+
+        let empty = CircularLinkedList::<i32>::new();
+        assert_eq!(lap(&empty), Vec::<i32>::new());
+        assert_eq!(format!("{}", empty), "[\"Empty\"]");
+    }
+
+    #[test]
+    fn test_cycle_iter_wraps_around() {
+        let list = ring(&[1, 2, 3]);
+        let values: Vec<i32> = list.cycle_iter().take(7).map(|data| *data).collect();
+        assert_eq!(values, vec![1, 2, 3, 1, 2, 3, 1]);
+
+        let empty = CircularLinkedList::<i32>::new();
+        assert!(empty.cycle_iter().next().is_none());
+    }
+
+    #[test]
+    fn test_rotate_forward_and_backward() {
+        let mut list = ring(&[1, 2, 3, 4]);
+
+        list.rotate_forward(1);
+        assert_eq!(lap(&list), vec![2, 3, 4, 1]);
+
+        list.rotate_backward(2);
+        assert_eq!(lap(&list), vec![4, 1, 2, 3]);
+
+        list.rotate_forward(9);
+        assert_eq!(lap(&list), vec![1, 2, 3, 4]);
+        assert_eq!(list.len(), 4);
+
+        let mut empty = CircularLinkedList::<i32>::new();
+        empty.rotate_forward(3);
+        empty.rotate_backward(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_advance_and_remove_josephus() {
+        // 7 people, every 3rd one is out, the classic answer is that 4 survives
+        let mut list = ring(&[1, 2, 3, 4, 5, 6, 7]);
+        let mut order = vec![];
+
+        while list.len() > 1 {
+            order.push(list.advance_and_remove(3).unwrap());
+        }
+
+        assert_eq!(order, vec![3, 6, 2, 7, 5, 1]);
+        assert_eq!(lap(&list), vec![4]);
+
+        assert_eq!(list.advance_and_remove(3), Some(4));
+        assert_eq!(list.advance_and_remove(3), None);
+        assert!(list.head.is_none());
+    }
+
+    #[test]
+    fn test_advance_and_remove_keeps_links() {
+        let mut list = ring(&[1, 2, 3]);
+        assert_eq!(list.advance_and_remove(1), Some(1));
+        assert_eq!(lap(&list), vec![2, 3]);
+
+        list.rotate_backward(1);
+        assert_eq!(lap(&list), vec![3, 2]);
+
+        list.insert_at_end(4);
+        assert_eq!(lap(&list), vec![3, 2, 4]);
+    }
+
+    #[test]
+    fn test_advance_and_remove_frees_the_node() {
+        let live = Rc::new(Cell::new(0));
+        let mut list = tracked_list(&[1, 2, 3], &live);
+
+        let removed = list.advance_and_remove(2).unwrap();
+        assert_eq!(removed.id, 2);
+        drop(removed);
+        assert_eq!(live.get(), 2);
+    }
+
+    #[test]
+    fn test_node_handles() {
+        let mut list = ring(&[1, 2]);
+        let handle = list.insert_with_handle(3);
+        let first = list.head_handle().unwrap();
+
+        assert_eq!(lap(&list), vec![1, 2, 3]);
+        assert_eq!(list.get(&handle).as_deref(), Some(&3));
+
+        *list.get_mut(&first).unwrap() = 10;
+        assert_eq!(list.remove_node(&handle), Some(3));
+        assert_eq!(lap(&list), vec![10, 2]);
+
+        // the node is gone, so the handle is dead
+        assert!(!list.contains_node(&handle));
+        assert_eq!(list.remove_node(&handle), None);
+
+        assert_eq!(list.remove_node(&first), Some(10));
+        assert_eq!(lap(&list), vec![2]);
+    }
+
+    #[test]
+    fn test_handle_from_other_list_is_rejected() {
+        let mut list = ring(&[1]);
+        let mut other = CircularLinkedList::new();
+        let foreign = other.insert_with_handle(1);
+
+        assert!(list.get(&foreign).is_none());
+        assert_eq!(list.remove_node(&foreign), None);
+        assert_eq!(list.len(), 1);
+        assert_eq!(other.len(), 1);
+    }
+
+    #[test]
+    fn test_insert_with_handle_on_empty_ring() {
+        let mut list = CircularLinkedList::new();
+        let only = list.insert_with_handle(5);
+
+        assert_eq!(lap(&list), vec![5]);
+        list.rotate_forward(1);
+        assert_eq!(list.remove_node(&only), Some(5));
+        assert!(list.is_empty());
+        assert!(list.head.is_none());
+    }
+
+    impl ModelList for CircularLinkedList<i32> {
+        fn insert_after(&mut self, prev_data: i32, data: i32) -> bool {
+            CircularLinkedList::insert_after(self, prev_data, data)
+        }
+
+        fn delete(&mut self, data: i32) {
+            CircularLinkedList::delete(self, data);
+        }
+
+        fn reverse(&mut self) {
+            CircularLinkedList::reverse(self);
+        }
+
+        // the ring has no search of its own
+        fn search(&self, data: i32) -> bool {
+            self.iter().any(|x| *x == data)
+        }
+    }
+
+    #[test]
+    fn test_matches_vec_deque() {
+        check_list::<CircularLinkedList<i32>>("CircularLinkedList against VecDeque");
+    }
 }
@@ -1,75 +1,47 @@
 use core::fmt;
-use std::{cell::RefCell, fmt::Debug, rc::{Rc, Weak}};
+use std::{cell::{Ref, RefCell, RefMut}, fmt::Debug, rc::{Rc, Weak}};
+
+use crate::node_ref::node_ref;
 
 
 #[derive(Clone)]
-struct Node<T> {
-    data: T,
-    next: Option<Rc<RefCell<Node<T>>>>, // Allows to mutateit safely
-    prev: Option<Weak<RefCell<Node<T>>>> // Weak has no ownership
+pub(super) struct Node<T> {
+    pub(super) data: T,
+    pub(super) next: Option<Rc<RefCell<Node<T>>>>, // Allows to mutateit safely
+    pub(super) prev: Option<Weak<RefCell<Node<T>>>> // Weak has no ownership
 }
 
 // Weak and RefCell are used to not create reference cycles
 // I did not expect them to be this different
 
 pub struct DoubleLinkedList<T> {
-    head: Option<Rc<RefCell<Node<T>>>>,
+    pub(super) head: Option<Rc<RefCell<Node<T>>>>,
+    pub(super) tail: Option<Weak<RefCell<Node<T>>>>, // Weak so the last node is still owned only by its prev
+    pub(super) len: usize,
 }
 
-impl<T: Eq + Clone> DoubleLinkedList<T> {
+impl<T: Eq> DoubleLinkedList<T> {
     pub fn new() -> Self {
-        Self { head: None }
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+        }
     }
 
     pub fn insert_at_end(&mut self, data: T) {
-        if self.head.is_none() {
-            self.insert_at_beginning(data);
-            return;
-        }
-    
-        let new_node = Rc::new(RefCell::new(Node {
-            data,
-            next: None,
-            prev: None,
-        }));
-    
-        let mut current = self.head.as_ref().unwrap().clone();
-
-        loop {
-            let next: Option<Rc<RefCell<Node<T>>>> = {
-                let current_borrow = current.borrow();
-                current_borrow.next.as_ref().cloned()
-            };
-        
-            if let Some(next_node) = next {
-                current = next_node;
-            } else {
-                new_node.borrow_mut().prev = Some(Rc::downgrade(&current));
-                
-                break;
-            }
-        }        
-
-        new_node.borrow_mut().prev = Some(Rc::downgrade(&current));
-        current.borrow_mut().next = Some(new_node);
+        self.push_back(data);
     }
-    
-    pub fn insert_at_beginning(&mut self, data: T) {
-        let node = Rc::new(RefCell::new(Node {
-            data,
-            next: self.head.clone(),
-            prev: None
-        }));
 
-        if let Some(head_node) = self.head.as_ref() {
-            head_node.borrow_mut().prev = Some(Rc::downgrade(&node));
-        }
-
-        self.head = Some(node);
+    pub fn insert_at_beginning(&mut self, data: T) {
+        self.push_front(data);
     }
 
     pub fn insert_after(&mut self, prev_data: T, data: T) -> bool { 
-        let mut current = self.head.as_ref().unwrap().clone();
+        let mut current = match self.head.as_ref() {
+            Some(head_node) => head_node.clone(),
+            None => return false,
+        };
     
         loop {
             if current.borrow().data == prev_data {
@@ -81,11 +53,13 @@ impl<T: Eq + Clone> DoubleLinkedList<T> {
                     prev: Some(Rc::downgrade(&current)),
                 }));
     
-                if let Some(ref next_node) = next {
-                    next_node.borrow_mut().prev = Some(Rc::downgrade(&new_node));
+                match next {
+                    Some(ref next_node) => next_node.borrow_mut().prev = Some(Rc::downgrade(&new_node)),
+                    None => self.tail = Some(Rc::downgrade(&new_node)),
                 }
     
                 current.borrow_mut().next = Some(new_node);
+                self.len += 1;
     
                 return true;
             }
@@ -113,11 +87,13 @@ impl<T: Eq + Clone> DoubleLinkedList<T> {
         if current.borrow().data == data {
             let next = current.borrow_mut().next.take();
  
-            if let Some(ref next_node) = next {
-                next_node.borrow_mut().prev = None;
+            match next {
+                Some(ref next_node) => next_node.borrow_mut().prev = None,
+                None => self.tail = None,
             }
  
             self.head = next;
+            self.len -= 1;
  
             return;
         }
@@ -139,14 +115,17 @@ impl<T: Eq + Clone> DoubleLinkedList<T> {
                 let next = node_to_remove.next.take();
                 let prev = node_to_remove.prev.take();
 
-                if let Some(next) = next.clone() {
-                    next.borrow_mut().prev = prev.clone();
-                }   
+                match next.clone() {
+                    Some(next) => next.borrow_mut().prev = prev.clone(),
+                    None => self.tail = prev.clone(),
+                }
 
                 if let Some(prev) = prev.clone() {
                     prev.upgrade().unwrap().borrow_mut().next = next;
                 }
 
+                self.len -= 1;
+
                 return;
             } else {
                 current = next_node;
@@ -158,6 +137,8 @@ impl<T: Eq + Clone> DoubleLinkedList<T> {
         let mut current = self.head.take();
         let mut new_head = None; 
 
+        self.tail = current.as_ref().map(Rc::downgrade);
+
         while let Some(node_rc) = current {
             let mut node = node_rc.borrow_mut();
     
@@ -165,7 +146,7 @@ impl<T: Eq + Clone> DoubleLinkedList<T> {
             let prev = node.prev.take().and_then(|w| w.upgrade());
 
             node.next = prev.clone();
-            node.prev = next.as_ref().map(|rc| Rc::downgrade(rc));
+            node.prev = next.as_ref().map(Rc::downgrade);
     
             drop(node);
 
@@ -177,39 +158,250 @@ impl<T: Eq + Clone> DoubleLinkedList<T> {
     }
     
     pub fn search(&self, data: T) -> bool {
-        self.iter().any(|x| x == data)
+        self.iter().any(|x| *x == data)
     }    
 
     pub fn length(&self) -> usize {
-        self.iter().count()
+        self.len
     }    
-    
 }
 
-pub struct Iter<T> {
-    next: Option<Rc<RefCell<Node<T>>>>,
+impl<T> DoubleLinkedList<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, data: T) {
+        let node = Rc::new(RefCell::new(Node {
+            data,
+            next: self.head.clone(),
+            prev: None
+        }));
+
+        match self.head.as_ref() {
+            Some(head_node) => head_node.borrow_mut().prev = Some(Rc::downgrade(&node)),
+            None => self.tail = Some(Rc::downgrade(&node)),
+        }
+
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    // The tail pointer means the end of the list is reachable without walking it
+    pub fn push_back(&mut self, data: T) {
+        let new_node = Rc::new(RefCell::new(Node {
+            data,
+            next: None,
+            prev: self.tail.clone(),
+        }));
+
+        match self.tail.as_ref().and_then(|weak| weak.upgrade()) {
+            Some(tail_node) => tail_node.borrow_mut().next = Some(new_node.clone()),
+            None => self.head = Some(new_node.clone()),
+        }
+
+        self.tail = Some(Rc::downgrade(&new_node));
+        self.len += 1;
+    }
+}
+
+// No bounds on T, Drop needs these
+impl<T> DoubleLinkedList<T> {
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail_node = self.tail.take()?.upgrade()?;
+        let prev = tail_node.borrow_mut().prev.take();
+
+        match prev.as_ref().and_then(|weak| weak.upgrade()) {
+            Some(prev_node) => prev_node.borrow_mut().next = None,
+            None => self.head = None,
+        }
+
+        self.tail = prev;
+        self.len -= 1;
+
+        // the node was owned by its prev (or the head) and iterators borrow the list,
+        // so this is the last strong reference
+        let cell = Rc::try_unwrap(tail_node).unwrap_or_else(|_| unreachable!("a popped node is owned only by the list"));
+        Some(cell.into_inner().data)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head_node = self.head.take()?;
+        let next = head_node.borrow_mut().next.take();
+
+        match next {
+            Some(next_node) => {
+                next_node.borrow_mut().prev = None;
+                self.head = Some(next_node);
+            }
+            None => self.tail = None,
+        }
+
+        self.len -= 1;
+
+        let cell = Rc::try_unwrap(head_node).unwrap_or_else(|_| unreachable!("a popped node is owned only by the list"));
+        Some(cell.into_inner().data)
+    }
+
+    // Popping one node at a time instead of dropping the head, which would free the
+    // chain recursively
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> Drop for DoubleLinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Eq> Default for DoubleLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Iterators hand out Ref/RefMut guards straight into the nodes, so nothing gets cloned.
+// Walking from node to node goes through `node_ref`, see node_ref.rs.
+
+// SAFETY for all of these: every node is owned by the list (through `head` and the `next`
+// links) and the links are only rewritten by methods taking `&mut self`, so for as long as
+// `node` is borrowed out of the list its neighbours can't be freed either
+pub(super) fn next_ref<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    node.borrow().next.as_ref().map(|rc| unsafe { node_ref(Rc::as_ptr(rc)) })
+}
+
+pub(super) fn prev_ref<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    node.borrow().prev.as_ref().map(|weak| unsafe { node_ref(weak.as_ptr()) })
 }
 
 impl<T> DoubleLinkedList<T> {
-    pub fn iter(&self) -> Iter<T> {
+    pub(super) fn tail_ref(&self) -> Option<&RefCell<Node<T>>> {
+        self.tail.as_ref().map(|weak| unsafe { node_ref(weak.as_ptr()) })
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a RefCell<Node<T>>>,
+    next_back: Option<&'a RefCell<Node<T>>>,
+    remaining: usize, // stops the two ends from walking past each other
+}
+
+pub struct IterMut<'a, T> {
+    next: Option<&'a RefCell<Node<T>>>,
+    next_back: Option<&'a RefCell<Node<T>>>,
+    remaining: usize,
+}
+
+impl<T> DoubleLinkedList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
-            next: self.head.clone(),
+            next: self.head.as_deref(),
+            next_back: self.tail_ref(),
+            remaining: self.len,
+        }
+    }
+
+    // Taking `&mut self` makes sure no other guard into the list exists, so handing
+    // out one RefMut per node never panics
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_deref(),
+            next_back: self.tail_ref(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn front(&self) -> Option<Ref<'_, T>> {
+        self.iter().next()
+    }
+
+    pub fn back(&self) -> Option<Ref<'_, T>> {
+        self.iter().next_back()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
         }
+
+        self.remaining -= 1;
+
+        self.next.take().map(|current| {
+            self.next = next_ref(current);
+            Ref::map(current.borrow(), |node| &node.data)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
-impl<T: Clone> Iterator for Iter<T> {
-    type Item = T;
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next_back.take().map(|current| {
+            self.next_back = prev_ref(current);
+            Ref::map(current.borrow(), |node| &node.data)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = RefMut<'a, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.next.take().map(|current_rc| {
-            let current_ref = current_rc.borrow();
-            self.next = current_ref.next.clone();
-            current_ref.data.clone()
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next.take().map(|current| {
+            // read the link before the node gets mutably borrowed by the caller
+            self.next = next_ref(current);
+            RefMut::map(current.borrow_mut(), |node| &mut node.data)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next_back.take().map(|current| {
+            self.next_back = prev_ref(current);
+            RefMut::map(current.borrow_mut(), |node| &mut node.data)
         })
     }
 }
 
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
 
 impl<T: Debug> fmt::Display for DoubleLinkedList<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -233,6 +425,7 @@ impl<T: Debug> fmt::Display for DoubleLinkedList<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::linear_data_structures::linked_lists::model::{check_list, ModelList};
 
     fn collect_backward<T: Clone>(tail: Rc<RefCell<Node<T>>>) -> Vec<T> {
         let mut result = vec![];
@@ -347,5 +540,173 @@ mod tests {
     
         let collected = collect_backward(tail);
         assert_eq!(collected, vec![3, 2, 1]);
-    }    
+    }
+
+    #[test]
+    fn test_push_and_pop_back() {
+        let mut list = DoubleLinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.insert_at_beginning(0);
+
+        assert_eq!(list.back().as_deref(), Some(&2));
+        assert_eq!(list.len(), 3);
+
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.back().as_deref(), Some(&0));
+        assert_eq!(list.pop_back(), Some(0));
+        assert_eq!(list.pop_back(), None);
+
+        assert!(list.is_empty());
+        assert_eq!(list.back().as_deref(), None);
+        assert_eq!(format!("{}", list), "[\"Empty\"]");
+
+        list.push_back(7);
+        assert_eq!(format!("{}", list), "[7]");
+    }
+
+    #[test]
+    fn test_tail_follows_every_mutation() {
+        let mut list = DoubleLinkedList::new();
+        list.insert_at_beginning(1);
+        assert_eq!(list.back().as_deref(), Some(&1));
+
+        list.insert_after(1, 2);
+        assert_eq!(list.back().as_deref(), Some(&2));
+
+        list.delete(2);
+        assert_eq!(list.back().as_deref(), Some(&1));
+
+        list.push_back(2);
+        list.push_back(3);
+        list.reverse();
+        assert_eq!(list.back().as_deref(), Some(&1));
+
+        list.delete(3);
+        list.delete(2);
+        list.delete(1);
+        assert_eq!(list.back().as_deref(), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn test_reverse_iteration() {
+        let mut list = DoubleLinkedList::new();
+        for i in 1..=5 {
+            list.push_back(i);
+        }
+
+        assert_eq!(list.iter().rev().map(|x| *x).collect::<Vec<_>>(), vec![5, 4, 3, 2, 1]);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next().as_deref(), Some(&1));
+        assert_eq!(iter.next_back().as_deref(), Some(&5));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.map(|x| *x).collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_len_is_cached() {
+        let mut list = DoubleLinkedList::new();
+        list.insert_at_end(1);
+        list.insert_at_beginning(0);
+        assert!(list.insert_after(1, 2));
+        assert!(!list.insert_after(9, 3));
+        list.delete(9);
+        assert_eq!(list.length(), 3);
+
+        list.delete(0);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_insert_after_on_empty_list() {
+        let mut list = DoubleLinkedList::<i32>::new();
+        assert!(!list.insert_after(1, 2));
+    }
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct NoClone(i32);
+
+    // A type that can't be cloned proves nothing is copied while iterating
+    #[test]
+    fn test_iter_borrows_without_clone() {
+        let mut list = DoubleLinkedList::new();
+        list.push_back(NoClone(1));
+        list.push_back(NoClone(2));
+
+        let values: Vec<i32> = list.iter().map(|x| x.0).collect();
+        assert_eq!(values, vec![1, 2]);
+        assert_eq!(list.front().map(|x| x.0), Some(1));
+        assert!(list.search(NoClone(2)));
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut list = DoubleLinkedList::new();
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+
+        for mut value in list.iter_mut() {
+            *value *= 10;
+        }
+
+        if let Some(mut last) = list.iter_mut().next_back() {
+            *last += 1;
+        }
+
+        assert_eq!(format!("{}", list), "[10, 20, 30, 41]");
+        assert_eq!(list.front().as_deref(), Some(&10));
+    }
+
+    #[test]
+    fn test_guards_can_be_held_together() {
+        let mut list = DoubleLinkedList::new();
+        list.push_back(String::from("a"));
+        list.push_back(String::from("b"));
+
+        let guards: Vec<_> = list.iter().collect();
+        let joined: Vec<&str> = guards.iter().map(|guard| guard.as_str()).collect();
+        assert_eq!(joined, vec!["a", "b"]);
+        assert!(list.search(String::from("b")));
+    }
+
+    #[test]
+    fn test_drop_long_list() {
+        // dropping the head would free the nodes recursively and overflow the stack
+        let mut list = DoubleLinkedList::new();
+        for i in 0..1_000_000 {
+            list.push_back(i);
+        }
+        drop(list);
+    }
+
+    impl ModelList for DoubleLinkedList<i32> {
+        fn insert_after(&mut self, prev_data: i32, data: i32) -> bool {
+            DoubleLinkedList::insert_after(self, prev_data, data)
+        }
+
+        fn delete(&mut self, data: i32) {
+            DoubleLinkedList::delete(self, data);
+        }
+
+        fn reverse(&mut self) {
+            DoubleLinkedList::reverse(self);
+        }
+
+        fn search(&self, data: i32) -> bool {
+            DoubleLinkedList::search(self, data)
+        }
+
+        fn backward(&self) -> Vec<i32> {
+            self.iter().rev().map(|item| *item).collect()
+        }
+    }
+
+    #[test]
+    fn test_matches_vec_deque() {
+        check_list::<DoubleLinkedList<i32>>("DoubleLinkedList against VecDeque");
+    }
 }
@@ -1,3 +1,9 @@
 pub mod singular;
 pub mod double;
-pub mod circular;
\ No newline at end of file
+pub mod raw_double;
+pub mod arena;
+pub mod circular;
+pub mod algorithms;
+
+#[cfg(test)]
+mod model;
\ No newline at end of file
@@ -0,0 +1,135 @@
+// Model test shared by the linked lists, every list replays the same random operations as a
+// VecDeque and has to end up with the same contents after each step
+
+use std::collections::VecDeque;
+
+use crate::{
+    linear_data_structures::sequence::FrontSequence,
+    property::{check, ensure_eq, shrink_value, Operation, Rng},
+};
+
+// The list specific methods the Sequence traits don't cover
+pub(super) trait ModelList: FrontSequence<i32> + Default {
+    fn insert_after(&mut self, prev_data: i32, data: i32) -> bool;
+
+    fn delete(&mut self, data: i32);
+
+    fn reverse(&mut self);
+
+    fn search(&self, data: i32) -> bool;
+
+    // Lists with prev links override this to walk them, so broken back links show up
+    fn backward(&self) -> Vec<i32> {
+        let mut values: Vec<i32> = self.iter().map(|item| *item).collect();
+        values.reverse();
+        values
+    }
+}
+
+// Values stay small so deletes and searches actually hit something
+const MAX_VALUE: i32 = 9;
+
+#[derive(Clone, Debug)]
+pub(super) enum ListOp {
+    PushFront(i32),
+    PushBack(i32),
+    PopFront,
+    PopBack,
+    InsertAfter(i32, i32),
+    Delete(i32),
+    Reverse,
+    Search(i32),
+    Clear,
+}
+
+impl Operation for ListOp {
+    fn generate(rng: &mut Rng) -> Self {
+        match rng.below(20) {
+            0..=3 => ListOp::PushFront(rng.value(MAX_VALUE)),
+            4..=7 => ListOp::PushBack(rng.value(MAX_VALUE)),
+            8 | 9 => ListOp::PopFront,
+            10 | 11 => ListOp::PopBack,
+            12 | 13 => ListOp::InsertAfter(rng.value(MAX_VALUE), rng.value(MAX_VALUE)),
+            14 | 15 => ListOp::Delete(rng.value(MAX_VALUE)),
+            16 => ListOp::Reverse,
+            17 | 18 => ListOp::Search(rng.value(MAX_VALUE)),
+            _ => ListOp::Clear,
+        }
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        match *self {
+            ListOp::PushFront(value) => shrink_value(value).into_iter().map(ListOp::PushFront).collect(),
+            ListOp::PushBack(value) => shrink_value(value).into_iter().map(ListOp::PushBack).collect(),
+            ListOp::InsertAfter(prev, value) => shrink_value(value)
+                .into_iter()
+                .map(|value| ListOp::InsertAfter(prev, value))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+pub(super) fn check_list<L: ModelList>(name: &str) {
+    check::<ListOp, _>(name, |ops| {
+        let mut list = L::default();
+        let mut model: VecDeque<i32> = VecDeque::new();
+
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                ListOp::PushFront(value) => {
+                    list.push_front(value);
+                    model.push_front(value);
+                }
+                ListOp::PushBack(value) => {
+                    list.push_back(value);
+                    model.push_back(value);
+                }
+                ListOp::PopFront => {
+                    ensure_eq!(list.pop_front(), model.pop_front(), "step {} pop_front", step);
+                }
+                ListOp::PopBack => {
+                    ensure_eq!(list.pop_back(), model.pop_back(), "step {} pop_back", step);
+                }
+                ListOp::InsertAfter(prev, value) => {
+                    let position = model.iter().position(|&x| x == prev);
+                    if let Some(position) = position {
+                        model.insert(position + 1, value);
+                    }
+
+                    ensure_eq!(list.insert_after(prev, value), position.is_some(), "step {} insert_after", step);
+                }
+                ListOp::Delete(value) => {
+                    list.delete(value);
+
+                    if let Some(position) = model.iter().position(|&x| x == value) {
+                        model.remove(position);
+                    }
+                }
+                ListOp::Reverse => {
+                    list.reverse();
+                    model = model.into_iter().rev().collect();
+                }
+                ListOp::Search(value) => {
+                    ensure_eq!(list.search(value), model.contains(&value), "step {} search", step);
+                    ensure_eq!(list.contains(&value), model.contains(&value), "step {} contains", step);
+                }
+                ListOp::Clear => {
+                    list.clear();
+                    model.clear();
+                }
+            }
+
+            let expected: Vec<i32> = model.iter().copied().collect();
+            let mut expected_backward = expected.clone();
+            expected_backward.reverse();
+
+            ensure_eq!(list.len(), model.len(), "step {} len", step);
+            ensure_eq!(list.is_empty(), model.is_empty(), "step {} is_empty", step);
+            ensure_eq!(list.iter().map(|item| *item).collect::<Vec<_>>(), expected, "step {} contents", step);
+            ensure_eq!(list.backward(), expected_backward, "step {} backward", step);
+        }
+
+        Ok(())
+    });
+}
@@ -0,0 +1,605 @@
+// Same list as double.rs but the way std::collections::LinkedList does it, raw pointers instead
+// of Rc<RefCell>. No reference counts and no borrow flags, the price is that every pointer
+// access is unsafe and the invariants below have to be kept by hand:
+// - every node was allocated by Box::leak and is owned by exactly one list
+// - head.prev and tail.next are None, for every other node prev/next point at each other
+// - len is the number of nodes reachable from head
+//
+// The tests are meant to be run under Miri as well, which catches a broken invariant as soon
+// as a dangling or aliased pointer gets used:
+//     cargo +nightly miri test --lib raw_double
+
+use core::fmt;
+use std::{fmt::Debug, marker::PhantomData, ptr::NonNull};
+
+struct Node<T> {
+    data: T,
+    next: Option<NonNull<Node<T>>>,
+    prev: Option<NonNull<Node<T>>>,
+}
+
+pub struct RawDoubleLinkedList<T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    _owns: PhantomData<Box<Node<T>>>, // tells the drop checker that we own the nodes
+}
+
+// Nodes are only reachable through the list, so it is as thread safe as T itself
+unsafe impl<T: Send> Send for RawDoubleLinkedList<T> {}
+unsafe impl<T: Sync> Sync for RawDoubleLinkedList<T> {}
+
+impl<T> RawDoubleLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    fn allocate(data: T) -> NonNull<Node<T>> {
+        NonNull::from(Box::leak(Box::new(Node {
+            data,
+            next: None,
+            prev: None,
+        })))
+    }
+
+    // Takes the node out of the chain and gives its allocation back to a Box
+    //
+    // SAFETY: `node` has to be part of this list
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> Box<Node<T>> {
+        let boxed = unsafe { Box::from_raw(node.as_ptr()) };
+
+        match boxed.prev {
+            Some(prev) => unsafe { (*prev.as_ptr()).next = boxed.next },
+            None => self.head = boxed.next,
+        }
+
+        match boxed.next {
+            Some(next) => unsafe { (*next.as_ptr()).prev = boxed.prev },
+            None => self.tail = boxed.prev,
+        }
+
+        self.len -= 1;
+        boxed
+    }
+
+    pub fn push_front(&mut self, data: T) {
+        let node = Self::allocate(data);
+
+        unsafe {
+            (*node.as_ptr()).next = self.head;
+
+            match self.head {
+                Some(head) => (*head.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, data: T) {
+        let node = Self::allocate(data);
+
+        unsafe {
+            (*node.as_ptr()).prev = self.tail;
+
+            match self.tail {
+                Some(tail) => (*tail.as_ptr()).next = Some(node),
+                None => self.head = Some(node),
+            }
+        }
+
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+        Some(unsafe { self.unlink(head) }.data)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        Some(unsafe { self.unlink(tail) }.data)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn insert_at_end(&mut self, data: T) {
+        self.push_back(data);
+    }
+
+    pub fn insert_at_beginning(&mut self, data: T) {
+        self.push_front(data);
+    }
+
+    // Every node swaps its next and prev, then head and tail swap
+    pub fn reverse(&mut self) {
+        let mut current = self.head;
+
+        while let Some(node) = current {
+            unsafe {
+                let node = &mut *node.as_ptr();
+                std::mem::swap(&mut node.next, &mut node.prev);
+                current = node.prev;
+            }
+        }
+
+        std::mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn length(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head,
+            next_back: self.tail,
+            remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head,
+            next_back: self.tail,
+            remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    fn find_node(&self, data: &T) -> Option<NonNull<Node<T>>>
+    where
+        T: PartialEq,
+    {
+        let mut current = self.head;
+
+        while let Some(node) = current {
+            unsafe {
+                if (*node.as_ptr()).data == *data {
+                    return Some(node);
+                }
+
+                current = (*node.as_ptr()).next;
+            }
+        }
+
+        None
+    }
+}
+
+impl<T: Eq> RawDoubleLinkedList<T> {
+    pub fn insert_after(&mut self, prev_data: T, data: T) -> bool {
+        let prev = match self.find_node(&prev_data) {
+            Some(node) => node,
+            None => return false,
+        };
+
+        let node = Self::allocate(data);
+
+        unsafe {
+            let next = (*prev.as_ptr()).next;
+            (*node.as_ptr()).prev = Some(prev);
+            (*node.as_ptr()).next = next;
+            (*prev.as_ptr()).next = Some(node);
+
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+
+        self.len += 1;
+        true
+    }
+
+    pub fn delete(&mut self, data: T) {
+        if let Some(node) = self.find_node(&data) {
+            unsafe { self.unlink(node) };
+        }
+    }
+
+    pub fn search(&self, data: T) -> bool {
+        self.find_node(&data).is_some()
+    }
+}
+
+impl<T> Drop for RawDoubleLinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for RawDoubleLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for RawDoubleLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> FromIterator<T> for RawDoubleLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+
+        for data in iter {
+            list.push_back(data);
+        }
+
+        list
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    next_back: Option<NonNull<Node<T>>>,
+    remaining: usize, // stops the two ends from walking past each other
+    _list: PhantomData<&'a Node<T>>,
+}
+
+pub struct IterMut<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    next_back: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    _list: PhantomData<&'a mut Node<T>>,
+}
+
+pub struct IntoIter<T> {
+    list: RawDoubleLinkedList<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.next = node.next;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next_back.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.next_back = node.prev;
+            &node.data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+// Only the data field is handed out as &mut, the links are read through the raw pointer
+// before that, so two live &mut never overlap
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next.map(|node| unsafe {
+            self.next = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        self.next_back.map(|node| unsafe {
+            self.next_back = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> IntoIterator for RawDoubleLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RawDoubleLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Debug> fmt::Display for RawDoubleLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_list = f.debug_list();
+
+        if self.is_empty() {
+            debug_list.entry(&"Empty");
+        } else {
+            debug_list.entries(self.iter());
+        }
+
+        debug_list.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_data_structures::linked_lists::model::{check_list, ModelList};
+    use std::{cell::Cell, rc::Rc};
+
+    // Walks the prev links from the tail, so broken back links show up as a mismatch
+    fn collect_both_ways<T: Clone>(list: &RawDoubleLinkedList<T>) -> (Vec<T>, Vec<T>) {
+        let forward: Vec<T> = list.iter().cloned().collect();
+        let mut backward: Vec<T> = list.iter().rev().cloned().collect();
+        backward.reverse();
+        (forward, backward)
+    }
+
+    fn values(list: &RawDoubleLinkedList<i32>) -> Vec<i32> {
+        let (forward, backward) = collect_both_ways(list);
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), list.len());
+        forward
+    }
+
+    #[test]
+    fn test_insert_at_end_and_beginning() {
+        let mut list = RawDoubleLinkedList::new();
+        list.insert_at_end(2);
+        list.insert_at_end(3);
+        list.insert_at_beginning(1);
+
+        assert_eq!(format!("{}", list), "[1, 2, 3]");
+        assert_eq!(values(&list), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_insert_after() {
+        let mut list: RawDoubleLinkedList<i32> = (1..=3).collect();
+        assert!(list.insert_after(2, 99));
+        assert!(list.insert_after(3, 100));
+        assert!(!list.insert_after(42, 0));
+
+        assert_eq!(values(&list), vec![1, 2, 99, 3, 100]);
+        assert_eq!(list.back(), Some(&100));
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut list: RawDoubleLinkedList<i32> = (1..=4).collect();
+        list.delete(3);
+        assert_eq!(values(&list), vec![1, 2, 4]);
+
+        list.delete(1);
+        list.delete(4);
+        assert_eq!(values(&list), vec![2]);
+
+        list.delete(2);
+        list.delete(2);
+        assert!(list.is_empty());
+        assert_eq!(format!("{}", list), "[\"Empty\"]");
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut list: RawDoubleLinkedList<i32> = (1..=5).collect();
+        list.reverse();
+        assert_eq!(values(&list), vec![5, 4, 3, 2, 1]);
+
+        let mut empty = RawDoubleLinkedList::<i32>::new();
+        empty.reverse();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_push_pop_both_ends() {
+        let mut list = RawDoubleLinkedList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.front(), None);
+    }
+
+    #[test]
+    fn test_search_and_length() {
+        let list: RawDoubleLinkedList<i32> = (0..10).collect();
+        assert!(list.search(7));
+        assert!(!list.search(10));
+        assert_eq!(list.length(), 10);
+    }
+
+    #[test]
+    fn test_iter_mut_and_into_iter() {
+        let mut list: RawDoubleLinkedList<i32> = (1..=4).collect();
+
+        for value in list.iter_mut() {
+            *value *= 2;
+        }
+
+        if let Some(first) = list.front_mut() {
+            *first = 0;
+        }
+
+        let mut iter = list.iter_mut();
+        let first = iter.next().unwrap();
+        let last = iter.next_back().unwrap();
+        std::mem::swap(first, last);
+
+        assert_eq!(list.into_iter().rev().collect::<Vec<_>>(), vec![0, 6, 4, 8]);
+    }
+
+    #[test]
+    fn test_clone_is_deep() {
+        let original: RawDoubleLinkedList<String> =
+            ["a", "b"].iter().map(|s| s.to_string()).collect();
+        let mut copy = original.clone();
+        copy.push_back("c".to_string());
+
+        assert_eq!(original.len(), 2);
+        assert_eq!(copy.iter().cloned().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    }
+
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_every_node_is_dropped() {
+        let drops = Rc::new(Cell::new(0));
+
+        {
+            let mut list = RawDoubleLinkedList::new();
+            for _ in 0..5 {
+                list.push_back(DropCounter(drops.clone()));
+            }
+
+            drop(list.pop_front());
+            list.reverse();
+            assert_eq!(drops.get(), 1);
+        }
+
+        assert_eq!(drops.get(), 5);
+
+        let mut iter = (0..3)
+            .map(|_| DropCounter(drops.clone()))
+            .collect::<RawDoubleLinkedList<_>>()
+            .into_iter();
+        iter.next();
+        drop(iter);
+
+        assert_eq!(drops.get(), 8);
+    }
+
+    impl ModelList for RawDoubleLinkedList<i32> {
+        fn insert_after(&mut self, prev_data: i32, data: i32) -> bool {
+            RawDoubleLinkedList::insert_after(self, prev_data, data)
+        }
+
+        fn delete(&mut self, data: i32) {
+            RawDoubleLinkedList::delete(self, data);
+        }
+
+        fn reverse(&mut self) {
+            RawDoubleLinkedList::reverse(self);
+        }
+
+        fn search(&self, data: i32) -> bool {
+            RawDoubleLinkedList::search(self, data)
+        }
+
+        fn backward(&self) -> Vec<i32> {
+            self.iter().rev().copied().collect()
+        }
+    }
+
+    #[test]
+    fn test_matches_vec_deque() {
+        check_list::<RawDoubleLinkedList<i32>>("RawDoubleLinkedList against VecDeque");
+    }
+}
@@ -1,13 +1,13 @@
 use core::fmt;
 use std::fmt::Debug;
 
-struct Node<T> {
-    data: T,
-    next: Option<Box<Node<T>>>,
+pub(super) struct Node<T> {
+    pub(super) data: T,
+    pub(super) next: Option<Box<Node<T>>>,
 }
 
 pub struct SingularLinkedList<T> {
-    head: Option<Box<Node<T>>>,
+    pub(super) head: Option<Box<Node<T>>>,
 }
 
 impl<T: Eq> SingularLinkedList<T> {
@@ -16,30 +16,11 @@ impl<T: Eq> SingularLinkedList<T> {
     }
 
     pub fn insert_at_end(&mut self, data: T) {
-        if self.head.is_none() {
-            self.insert_at_beginning(data);
-
-            return;
-        }
-
-        let new_node = Box::new(Node { data, next: None });
-
-        let mut current = self.head.as_mut().unwrap();
-
-        while let Some(ref mut next_node) = current.next {
-            current = next_node;
-        }
-
-        current.next = Some(new_node);
+        self.push_back(data);
     }
 
     pub fn insert_at_beginning(&mut self, data: T) {
-        let node = Box::new(Node {
-            data,
-            next: self.head.take(), // pushes out of the option the value leaving None there
-        });
-
-        self.head = Some(node);
+        self.push_front(data);
     }
 
     pub fn insert_after(&mut self, prev_data: T, data: T) -> bool {
@@ -95,10 +76,16 @@ impl<T: Eq> SingularLinkedList<T> {
     }    
 
     pub fn length(&self) -> usize {
-        self.iter().count()
+        self.len()
     }    
 }
 
+impl<T: Eq> Default for SingularLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -109,6 +96,61 @@ impl<T> SingularLinkedList<T> {
             next: self.head.as_deref(),
         }
     }
+
+    // No length is stored, so this walks the list
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn push_front(&mut self, data: T) {
+        let node = Box::new(Node {
+            data,
+            next: self.head.take(), // pushes out of the option the value leaving None there
+        });
+
+        self.head = Some(node);
+    }
+
+    pub fn push_back(&mut self, data: T) {
+        let mut link = &mut self.head;
+
+        while let Some(node) = link {
+            link = &mut node.next;
+        }
+
+        *link = Some(Box::new(Node { data, next: None }));
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.take().map(|boxed_node| {
+            self.head = boxed_node.next;
+            boxed_node.data
+        })
+    }
+
+    // Without a prev link the node before the last one has to be found by walking
+    pub fn pop_back(&mut self) -> Option<T> {
+        let mut link = &mut self.head;
+
+        while link.as_ref()?.next.is_some() {
+            link = &mut link.as_mut().unwrap().next;
+        }
+
+        link.take().map(|boxed_node| boxed_node.data)
+    }
+
+    // Unlinks one node at a time, dropping the head alone would free the chain recursively
+    pub fn clear(&mut self) {
+        let mut current = self.head.take();
+
+        while let Some(mut boxed_node) = current {
+            current = boxed_node.next.take();
+        }
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -143,6 +185,7 @@ impl<T: Debug> fmt::Display for SingularLinkedList<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::linear_data_structures::linked_lists::model::{check_list, ModelList};
 
     #[test]
     fn test_insert_at_end() {
@@ -269,4 +312,27 @@ mod tests {
         let collected: Vec<_> = list.iter().cloned().collect();
         assert_eq!(collected, vec![1, 2, 3]);
     }
+
+    impl ModelList for SingularLinkedList<i32> {
+        fn insert_after(&mut self, prev_data: i32, data: i32) -> bool {
+            SingularLinkedList::insert_after(self, prev_data, data)
+        }
+
+        fn delete(&mut self, data: i32) {
+            SingularLinkedList::delete(self, data);
+        }
+
+        fn reverse(&mut self) {
+            SingularLinkedList::reverse(self);
+        }
+
+        fn search(&self, data: i32) -> bool {
+            SingularLinkedList::search(self, data)
+        }
+    }
+
+    #[test]
+    fn test_matches_vec_deque() {
+        check_list::<SingularLinkedList<i32>>("SingularLinkedList against VecDeque");
+    }
 }
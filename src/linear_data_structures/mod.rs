@@ -1,3 +1,5 @@
 pub mod vec;
 pub mod linked_lists;
-pub mod stacks;
\ No newline at end of file
+pub mod stacks;
+pub mod round_robin;
+pub mod sequence;
\ No newline at end of file
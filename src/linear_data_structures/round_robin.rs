@@ -0,0 +1,396 @@
+// Weighted round robin scheduler, the ring from circular.rs does all the work:
+// the head of the ring is the task whose turn it is and moving on is just a rotation.
+// A task with weight w is handed out w times in a row before the head moves to the next one.
+// Paused tasks leave the ring and wait in a map, so `next` never has to skip over them.
+
+use std::{cell::Ref, collections::HashMap};
+
+use crate::linear_data_structures::linked_lists::circular::{CircularLinkedList, NodeHandle};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TaskHandle(u64);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TaskStats {
+    pub weight: u32,
+    pub runs: u64,
+    pub paused: bool,
+}
+
+struct Task<T> {
+    id: u64,
+    value: T,
+    weight: u32,
+    credits: u32, // turns left before the ring moves on
+    runs: u64,
+}
+
+pub struct RoundRobin<T> {
+    ring: CircularLinkedList<Task<T>>,
+    running: HashMap<u64, NodeHandle<Task<T>>>,
+    paused: HashMap<u64, Task<T>>,
+    next_id: u64,
+    dispatches: u64,
+}
+
+impl<T> RoundRobin<T> {
+    pub fn new() -> Self {
+        RoundRobin {
+            ring: CircularLinkedList::new(),
+            running: HashMap::new(),
+            paused: HashMap::new(),
+            next_id: 0,
+            dispatches: 0,
+        }
+    }
+
+    pub fn add(&mut self, value: T) -> TaskHandle {
+        self.add_weighted(value, 1)
+    }
+
+    // New tasks join at the back of the ring, a weight of 0 is treated like 1
+    pub fn add_weighted(&mut self, value: T, weight: u32) -> TaskHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let weight = weight.max(1);
+        self.enqueue(Task {
+            id,
+            value,
+            weight,
+            credits: weight,
+            runs: 0,
+        });
+
+        TaskHandle(id)
+    }
+
+    fn enqueue(&mut self, task: Task<T>) {
+        let id = task.id;
+        let node = self.ring.insert_with_handle(task);
+        self.running.insert(id, node);
+    }
+
+    // Hands out the task whose turn it is and moves the schedule forward.
+    // Not an Iterator because the task is borrowed out of the scheduler
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(TaskHandle, Ref<'_, T>)> {
+        let current = self.ring.head_handle()?;
+
+        let (id, turn_over) = {
+            let mut task = self.ring.get_mut(&current).unwrap();
+            task.runs += 1;
+            task.credits -= 1;
+
+            let turn_over = task.credits == 0;
+            if turn_over {
+                task.credits = task.weight;
+            }
+
+            (task.id, turn_over)
+        };
+
+        if turn_over {
+            self.ring.rotate_forward(1);
+        }
+
+        self.dispatches += 1;
+
+        let task = self.ring.get(&current).unwrap();
+        Some((TaskHandle(id), Ref::map(task, |task| &task.value)))
+    }
+
+    // The task `next` would hand out, without touching the schedule
+    pub fn peek(&self) -> Option<(TaskHandle, Ref<'_, T>)> {
+        let current = self.ring.head_handle()?;
+        let task = self.ring.get(&current)?;
+        Some((TaskHandle(task.id), Ref::map(task, |task| &task.value)))
+    }
+
+    // Takes the task out of the rotation, O(1) because the handle leads straight to its node
+    pub fn pause(&mut self, handle: TaskHandle) -> bool {
+        match self.running.remove(&handle.0) {
+            Some(node) => {
+                let mut task = self.ring.remove_node(&node).unwrap();
+                task.credits = task.weight;
+                self.paused.insert(handle.0, task);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // A resumed task goes to the back of the ring like a new one
+    pub fn resume(&mut self, handle: TaskHandle) -> bool {
+        match self.paused.remove(&handle.0) {
+            Some(task) => {
+                self.enqueue(task);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, handle: TaskHandle) -> Option<T> {
+        if let Some(node) = self.running.remove(&handle.0) {
+            return self.ring.remove_node(&node).map(|task| task.value);
+        }
+
+        self.paused.remove(&handle.0).map(|task| task.value)
+    }
+
+    // The new weight counts from the next turn of the task
+    pub fn set_weight(&mut self, handle: TaskHandle, weight: u32) -> bool {
+        let weight = weight.max(1);
+
+        if let Some(task) = self.paused.get_mut(&handle.0) {
+            task.weight = weight;
+            task.credits = weight;
+            return true;
+        }
+
+        match self.running.get(&handle.0) {
+            Some(node) => {
+                let mut task = self.ring.get_mut(node).unwrap();
+                task.credits = task.credits.min(weight);
+                task.weight = weight;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, handle: TaskHandle) -> Option<Ref<'_, T>> {
+        if let Some(node) = self.running.get(&handle.0) {
+            return self.ring.get(node).map(|task| Ref::map(task, |task| &task.value));
+        }
+
+        None
+    }
+
+    pub fn get_paused(&self, handle: TaskHandle) -> Option<&T> {
+        self.paused.get(&handle.0).map(|task| &task.value)
+    }
+
+    pub fn is_paused(&self, handle: TaskHandle) -> bool {
+        self.paused.contains_key(&handle.0)
+    }
+
+    // Every task, running or paused
+    pub fn len(&self) -> usize {
+        self.running.len() + self.paused.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn active_len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn stats(&self, handle: TaskHandle) -> Option<TaskStats> {
+        if let Some(task) = self.paused.get(&handle.0) {
+            return Some(TaskStats {
+                weight: task.weight,
+                runs: task.runs,
+                paused: true,
+            });
+        }
+
+        let task = self.ring.get(self.running.get(&handle.0)?)?;
+        Some(TaskStats {
+            weight: task.weight,
+            runs: task.runs,
+            paused: false,
+        })
+    }
+
+    pub fn total_dispatches(&self) -> u64 {
+        self.dispatches
+    }
+
+    // Jain's fairness index of runs / weight over the running tasks, 1.0 means every task
+    // got exactly its share and 1/n means one task got everything
+    pub fn fairness(&self) -> f64 {
+        let shares: Vec<f64> = self
+            .ring
+            .iter()
+            .map(|task| task.runs as f64 / task.weight as f64)
+            .collect();
+
+        let sum: f64 = shares.iter().sum();
+        let sum_of_squares: f64 = shares.iter().map(|share| share * share).sum();
+
+        if sum_of_squares == 0.0 {
+            return 1.0;
+        }
+
+        sum * sum / (shares.len() as f64 * sum_of_squares)
+    }
+}
+
+impl<T> Default for RoundRobin<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(scheduler: &mut RoundRobin<&'static str>, steps: usize) -> Vec<&'static str> {
+        (0..steps)
+            .map(|_| *scheduler.next().unwrap().1)
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_round_robin() {
+        let mut scheduler = RoundRobin::new();
+        scheduler.add("a");
+        scheduler.add("b");
+        scheduler.add("c");
+
+        assert_eq!(run(&mut scheduler, 7), vec!["a", "b", "c", "a", "b", "c", "a"]);
+        assert_eq!(scheduler.total_dispatches(), 7);
+    }
+
+    #[test]
+    fn test_weights_give_consecutive_turns() {
+        let mut scheduler = RoundRobin::new();
+        let a = scheduler.add_weighted("a", 3);
+        scheduler.add_weighted("b", 1);
+        let c = scheduler.add_weighted("c", 2);
+
+        assert_eq!(
+            run(&mut scheduler, 12),
+            vec!["a", "a", "a", "b", "c", "c", "a", "a", "a", "b", "c", "c"]
+        );
+
+        assert_eq!(scheduler.stats(a).unwrap().runs, 6);
+        assert_eq!(scheduler.stats(c).unwrap().runs, 4);
+        assert!((scheduler.fairness() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let mut scheduler = RoundRobin::new();
+        scheduler.add("a");
+        let b = scheduler.add("b");
+        scheduler.add("c");
+
+        assert_eq!(run(&mut scheduler, 1), vec!["a"]);
+        assert!(scheduler.pause(b));
+        assert!(!scheduler.pause(b));
+        assert!(scheduler.is_paused(b));
+        assert_eq!(scheduler.get_paused(b), Some(&"b"));
+        assert_eq!(scheduler.active_len(), 2);
+        assert_eq!(scheduler.len(), 3);
+
+        assert_eq!(run(&mut scheduler, 3), vec!["c", "a", "c"]);
+
+        // resumed tasks queue up behind the current head
+        assert!(scheduler.resume(b));
+        assert!(!scheduler.resume(b));
+        assert_eq!(run(&mut scheduler, 4), vec!["a", "c", "b", "a"]);
+        assert!(!scheduler.stats(b).unwrap().paused);
+    }
+
+    #[test]
+    fn test_pause_current_task_mid_turn() {
+        let mut scheduler = RoundRobin::new();
+        let a = scheduler.add_weighted("a", 3);
+        scheduler.add("b");
+
+        assert_eq!(run(&mut scheduler, 1), vec!["a"]);
+        scheduler.pause(a);
+        assert_eq!(run(&mut scheduler, 2), vec!["b", "b"]);
+
+        // a starts over with a full turn
+        scheduler.resume(a);
+        assert_eq!(run(&mut scheduler, 5), vec!["b", "a", "a", "a", "b"]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut scheduler = RoundRobin::new();
+        let a = scheduler.add("a");
+        let b = scheduler.add("b");
+        let c = scheduler.add("c");
+
+        assert_eq!(scheduler.remove(a), Some("a"));
+        assert_eq!(scheduler.remove(a), None);
+
+        scheduler.pause(c);
+        assert_eq!(scheduler.remove(c), Some("c"));
+
+        assert_eq!(run(&mut scheduler, 2), vec!["b", "b"]);
+        assert_eq!(scheduler.remove(b), Some("b"));
+        assert!(scheduler.is_empty());
+        assert!(scheduler.next().is_none());
+        assert!(scheduler.peek().is_none());
+    }
+
+    #[test]
+    fn test_set_weight_and_peek() {
+        let mut scheduler = RoundRobin::new();
+        let a = scheduler.add("a");
+        scheduler.add("b");
+
+        assert!(scheduler.set_weight(a, 2));
+        assert_eq!(scheduler.peek().map(|(handle, task)| (handle, *task)), Some((a, "a")));
+        assert_eq!(run(&mut scheduler, 5), vec!["a", "b", "a", "a", "b"]);
+        assert_eq!(scheduler.get(a).as_deref(), Some(&"a"));
+        assert!(!scheduler.set_weight(TaskHandle(99), 2));
+    }
+
+    #[test]
+    fn test_fairness_drops_when_a_task_is_starved() {
+        let mut scheduler = RoundRobin::new();
+        let a = scheduler.add("a");
+        let b = scheduler.add("b");
+
+        scheduler.pause(b);
+        run(&mut scheduler, 10);
+        scheduler.resume(b);
+
+        assert_eq!(scheduler.stats(a).unwrap().runs, 10);
+        assert_eq!(scheduler.stats(b).unwrap().runs, 0);
+        assert!((scheduler.fairness() - 0.5).abs() < 1e-9);
+    }
+
+    // Long deterministic run with churn, the ring and the maps have to agree the whole time
+    #[test]
+    fn test_simulation_with_churn() {
+        let mut scheduler = RoundRobin::new();
+        let mut handles = vec![];
+
+        for i in 0..5u32 {
+            handles.push(scheduler.add_weighted(i, i + 1));
+        }
+
+        for step in 0..500 {
+            let (handle, _) = scheduler.next().unwrap();
+
+            match step % 37 {
+                0 => {
+                    scheduler.pause(handle);
+                }
+                18 => {
+                    for &h in &handles {
+                        scheduler.resume(h);
+                    }
+                }
+                _ => {}
+            }
+
+            assert_eq!(scheduler.len(), 5);
+            assert_eq!(scheduler.active_len() + scheduler.paused.len(), 5);
+        }
+
+        let total: u64 = handles.iter().map(|&h| scheduler.stats(h).unwrap().runs).sum();
+        assert_eq!(total, scheduler.total_dispatches());
+    }
+}
@@ -0,0 +1,407 @@
+// One interface over the vector and the linked lists, so code that only needs a sequence
+// can swap one container for another. The inherent methods keep their old names
+// (insert, insert_at_end, length, search...), the trait is what makes them line up.
+//
+// The Rc based lists can only lend their elements through Ref guards, so `iter` yields
+// anything that derefs to T instead of plain references.
+
+use std::{cell::Ref, fmt::Debug, ops::Deref};
+
+use crate::linear_data_structures::{
+    linked_lists::{
+        circular::{self, CircularLinkedList},
+        double::{self, DoubleLinkedList},
+        raw_double::{self, RawDoubleLinkedList},
+        singular::{self, SingularLinkedList},
+    },
+    vec::Vector,
+};
+
+pub trait Sequence<T> {
+    type Item<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    type Iter<'a>: Iterator<Item = Self::Item<'a>>
+    where
+        Self: 'a;
+
+    fn push_back(&mut self, value: T);
+
+    fn pop_back(&mut self) -> Option<T>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| *item == *value)
+    }
+
+    // Front to back
+    fn iter(&self) -> Self::Iter<'_>;
+
+    fn clear(&mut self);
+}
+
+// For containers where the front is as cheap to reach as the back, the vector would
+// have to shift everything so it stays out
+pub trait FrontSequence<T>: Sequence<T> {
+    fn push_front(&mut self, value: T);
+
+    fn pop_front(&mut self) -> Option<T>;
+}
+
+impl<T: Debug + Copy + PartialEq> Sequence<T> for Vector<T> {
+    type Item<'a> = &'a T where Self: 'a;
+    type Iter<'a> = std::slice::Iter<'a, T> where Self: 'a;
+
+    fn push_back(&mut self, value: T) {
+        self.insert(value);
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn len(&self) -> usize {
+        Vector::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        Vector::iter(self)
+    }
+
+    fn clear(&mut self) {
+        Vector::clear(self);
+    }
+}
+
+// pop_back and push_back walk the whole list, there is no tail pointer
+impl<T> Sequence<T> for SingularLinkedList<T> {
+    type Item<'a> = &'a T where Self: 'a;
+    type Iter<'a> = singular::Iter<'a, T> where Self: 'a;
+
+    fn push_back(&mut self, value: T) {
+        SingularLinkedList::push_back(self, value);
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        SingularLinkedList::pop_back(self)
+    }
+
+    fn len(&self) -> usize {
+        SingularLinkedList::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SingularLinkedList::is_empty(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        SingularLinkedList::iter(self)
+    }
+
+    fn clear(&mut self) {
+        SingularLinkedList::clear(self);
+    }
+}
+
+impl<T> FrontSequence<T> for SingularLinkedList<T> {
+    fn push_front(&mut self, value: T) {
+        SingularLinkedList::push_front(self, value);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        SingularLinkedList::pop_front(self)
+    }
+}
+
+impl<T> Sequence<T> for DoubleLinkedList<T> {
+    type Item<'a> = Ref<'a, T> where Self: 'a;
+    type Iter<'a> = double::Iter<'a, T> where Self: 'a;
+
+    fn push_back(&mut self, value: T) {
+        DoubleLinkedList::push_back(self, value);
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        DoubleLinkedList::pop_back(self)
+    }
+
+    fn len(&self) -> usize {
+        DoubleLinkedList::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        DoubleLinkedList::iter(self)
+    }
+
+    fn clear(&mut self) {
+        DoubleLinkedList::clear(self);
+    }
+}
+
+impl<T> FrontSequence<T> for DoubleLinkedList<T> {
+    fn push_front(&mut self, value: T) {
+        DoubleLinkedList::push_front(self, value);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        DoubleLinkedList::pop_front(self)
+    }
+}
+
+// The front is the head and the back is the node just behind it, iteration is one lap
+impl<T> Sequence<T> for CircularLinkedList<T> {
+    type Item<'a> = Ref<'a, T> where Self: 'a;
+    type Iter<'a> = circular::Iter<'a, T> where Self: 'a;
+
+    fn push_back(&mut self, value: T) {
+        CircularLinkedList::push_back(self, value);
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        CircularLinkedList::pop_back(self)
+    }
+
+    fn len(&self) -> usize {
+        CircularLinkedList::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        CircularLinkedList::iter(self)
+    }
+
+    fn clear(&mut self) {
+        CircularLinkedList::clear(self);
+    }
+}
+
+impl<T> FrontSequence<T> for CircularLinkedList<T> {
+    fn push_front(&mut self, value: T) {
+        CircularLinkedList::push_front(self, value);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        CircularLinkedList::pop_front(self)
+    }
+}
+
+impl<T> Sequence<T> for RawDoubleLinkedList<T> {
+    type Item<'a> = &'a T where Self: 'a;
+    type Iter<'a> = raw_double::Iter<'a, T> where Self: 'a;
+
+    fn push_back(&mut self, value: T) {
+        RawDoubleLinkedList::push_back(self, value);
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        RawDoubleLinkedList::pop_back(self)
+    }
+
+    fn len(&self) -> usize {
+        RawDoubleLinkedList::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        RawDoubleLinkedList::iter(self)
+    }
+
+    fn clear(&mut self) {
+        RawDoubleLinkedList::clear(self);
+    }
+}
+
+impl<T> FrontSequence<T> for RawDoubleLinkedList<T> {
+    fn push_front(&mut self, value: T) {
+        RawDoubleLinkedList::push_front(self, value);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        RawDoubleLinkedList::pop_front(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The same scenarios run against every container, each one only sees the trait
+
+    fn collect<S: Sequence<i32>>(sequence: &S) -> Vec<i32> {
+        sequence.iter().map(|item| *item).collect()
+    }
+
+    fn check_empty<S: Sequence<i32> + Default>() {
+        let mut sequence = S::default();
+
+        assert!(sequence.is_empty());
+        assert_eq!(sequence.len(), 0);
+        assert_eq!(sequence.pop_back(), None);
+        assert!(!sequence.contains(&1));
+        assert_eq!(collect(&sequence), Vec::<i32>::new());
+
+        sequence.clear();
+        assert!(sequence.is_empty());
+    }
+
+    fn check_push_and_pop_back<S: Sequence<i32> + Default>() {
+        let mut sequence = S::default();
+
+        for i in 0..5 {
+            sequence.push_back(i);
+        }
+
+        assert_eq!(sequence.len(), 5);
+        assert!(!sequence.is_empty());
+        assert_eq!(collect(&sequence), vec![0, 1, 2, 3, 4]);
+
+        assert_eq!(sequence.pop_back(), Some(4));
+        assert_eq!(sequence.pop_back(), Some(3));
+        assert_eq!(collect(&sequence), vec![0, 1, 2]);
+
+        sequence.push_back(7);
+        assert_eq!(collect(&sequence), vec![0, 1, 2, 7]);
+
+        while sequence.pop_back().is_some() {}
+        assert!(sequence.is_empty());
+
+        // still usable after being emptied
+        sequence.push_back(9);
+        assert_eq!(collect(&sequence), vec![9]);
+    }
+
+    fn check_contains<S: Sequence<i32> + Default>() {
+        let mut sequence = S::default();
+
+        for i in [3, 1, 4, 1, 5] {
+            sequence.push_back(i);
+        }
+
+        assert!(sequence.contains(&4));
+        assert!(sequence.contains(&1));
+        assert!(!sequence.contains(&2));
+
+        sequence.pop_back();
+        assert!(!sequence.contains(&5));
+    }
+
+    fn check_clear<S: Sequence<i32> + Default>() {
+        let mut sequence = S::default();
+
+        for i in 0..1000 {
+            sequence.push_back(i);
+        }
+
+        sequence.clear();
+        assert!(sequence.is_empty());
+        assert_eq!(sequence.len(), 0);
+        assert_eq!(sequence.pop_back(), None);
+
+        sequence.push_back(1);
+        assert_eq!(collect(&sequence), vec![1]);
+    }
+
+    fn check_front<S: FrontSequence<i32> + Default>() {
+        let mut sequence = S::default();
+        assert_eq!(sequence.pop_front(), None);
+
+        sequence.push_front(2);
+        sequence.push_front(1);
+        sequence.push_back(3);
+        sequence.push_front(0);
+
+        assert_eq!(collect(&sequence), vec![0, 1, 2, 3]);
+        assert_eq!(sequence.len(), 4);
+
+        assert_eq!(sequence.pop_front(), Some(0));
+        assert_eq!(sequence.pop_back(), Some(3));
+        assert_eq!(sequence.pop_front(), Some(1));
+        assert_eq!(sequence.pop_front(), Some(2));
+        assert_eq!(sequence.pop_front(), None);
+        assert!(sequence.is_empty());
+    }
+
+    // Used as a queue: in at the back, out at the front
+    fn check_queue<S: FrontSequence<i32> + Default>() {
+        let mut sequence = S::default();
+        let mut popped = vec![];
+
+        for i in 0..20 {
+            sequence.push_back(i);
+
+            if i % 3 == 2 {
+                popped.push(sequence.pop_front().unwrap());
+            }
+        }
+
+        while let Some(value) = sequence.pop_front() {
+            popped.push(value);
+        }
+
+        assert_eq!(popped, (0..20).collect::<Vec<_>>());
+    }
+
+    macro_rules! sequence_tests {
+        ($name:ident, $sequence:ty) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn empty() {
+                    check_empty::<$sequence>();
+                }
+
+                #[test]
+                fn push_and_pop_back() {
+                    check_push_and_pop_back::<$sequence>();
+                }
+
+                #[test]
+                fn contains() {
+                    check_contains::<$sequence>();
+                }
+
+                #[test]
+                fn clear() {
+                    check_clear::<$sequence>();
+                }
+            }
+        };
+    }
+
+    macro_rules! front_sequence_tests {
+        ($name:ident, $sequence:ty) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn front() {
+                    check_front::<$sequence>();
+                }
+
+                #[test]
+                fn queue() {
+                    check_queue::<$sequence>();
+                }
+            }
+        };
+    }
+
+    sequence_tests!(vector, Vector<i32>);
+    sequence_tests!(singular_list, SingularLinkedList<i32>);
+    sequence_tests!(double_list, DoubleLinkedList<i32>);
+    sequence_tests!(circular_list, CircularLinkedList<i32>);
+    sequence_tests!(raw_double_list, RawDoubleLinkedList<i32>);
+
+    front_sequence_tests!(singular_list_front, SingularLinkedList<i32>);
+    front_sequence_tests!(double_list_front, DoubleLinkedList<i32>);
+    front_sequence_tests!(circular_list_front, CircularLinkedList<i32>);
+    front_sequence_tests!(raw_double_list_front, RawDoubleLinkedList<i32>);
+}
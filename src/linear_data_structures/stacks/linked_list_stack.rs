@@ -4,16 +4,16 @@
 // But array based do better with predictable size and catch storing, but their speed is
 // O(1) amortized
 
-use std::collections::LinkedList;
+use crate::linear_data_structures::linked_lists::raw_double::RawDoubleLinkedList;
 
 pub struct LinkedListStack<T> {
-    list: LinkedList<T>,
+    list: RawDoubleLinkedList<T>,
 }
 
 impl<T> LinkedListStack<T> {
     pub fn new() -> Self {
         LinkedListStack {
-            list: LinkedList::new(),
+            list: RawDoubleLinkedList::new(),
         }
     }
 
@@ -37,3 +37,83 @@ impl<T> LinkedListStack<T> {
         self.list.len()
     }
 }
+
+impl<T> Default for LinkedListStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property::{check, ensure_eq, shrink_value, Operation, Rng};
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        Push(i32),
+        Pop,
+        Peek,
+    }
+
+    impl Operation for Op {
+        fn generate(rng: &mut Rng) -> Self {
+            match rng.below(5) {
+                0 | 1 => Op::Push(rng.value(20)),
+                2 | 3 => Op::Pop,
+                _ => Op::Peek,
+            }
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            match *self {
+                Op::Push(value) => shrink_value(value).into_iter().map(Op::Push).collect(),
+                _ => vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn test_push_pop_peek() {
+        let mut stack = LinkedListStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+
+        assert_eq!(stack.peek(), Some(&2));
+        assert_eq!(stack.size(), 2);
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_matches_std_vec() {
+        check::<Op, _>("LinkedListStack against Vec", |ops| {
+            let mut stack = LinkedListStack::new();
+            let mut model: Vec<i32> = Vec::new();
+
+            for (step, op) in ops.iter().enumerate() {
+                match *op {
+                    Op::Push(value) => {
+                        stack.push(value);
+                        model.push(value);
+                    }
+                    Op::Pop => {
+                        ensure_eq!(stack.pop(), model.pop(), "step {} pop", step);
+                    }
+                    Op::Peek => {
+                        ensure_eq!(stack.peek(), model.last(), "step {} peek", step);
+                    }
+                }
+
+                ensure_eq!(stack.size(), model.len(), "step {} size", step);
+                ensure_eq!(stack.is_empty(), model.is_empty(), "step {} is_empty", step);
+            }
+
+            Ok(())
+        });
+    }
+}
@@ -63,10 +63,12 @@ impl<T: Debug + Copy + PartialEq> Vector<T> {
            self.data = new_data;
         }
 
-        self.capacity = self.capacity * 2;
+        self.capacity *= 2;
     }
     
 
+    /// # Safety
+    /// `index` must be smaller than the current size, nothing checks the bounds
     pub unsafe fn get(&self, index: usize) -> T {
         *self.data.add(index)
     }
@@ -84,6 +86,8 @@ impl<T: Debug + Copy + PartialEq> Vector<T> {
         self.size += 1;
     }
 
+    /// # Safety
+    /// `index` must be smaller than the current size, nothing checks the bounds
     pub unsafe fn remove(&mut self, index: usize) {
         std::ptr::drop_in_place(self.data.add(index));   
 
@@ -96,20 +100,60 @@ impl<T: Debug + Copy + PartialEq> Vector<T> {
         self.size -= 1
     }
 
+    /// # Safety
+    /// Only reads the initialized part of the buffer, kept unsafe to match `get`
     pub unsafe fn find(&mut self, value: T) -> Option<usize> {
-        for i in 0..self.size {
-            if self.get(i) == value {
-                return Some(i);
-            }
-        }
-
-        None
+        (0..self.size).find(|&i| self.get(i) == value)
     }
+
+    /// # Safety
+    /// `index` must be smaller than the current size, nothing checks the bounds
     pub unsafe fn set(&mut self, index: usize, value: T) {
         *self.data.add(index) = value
     }
 }
 
+impl<T: Debug> Vector<T> {
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    // Moves the last element out, the slot is left as uninitialized memory past `size`
+    pub fn pop(&mut self) -> Option<T> {
+        if self.size == 0 {
+            return None;
+        }
+
+        self.size -= 1;
+
+        unsafe {
+            Some(std::ptr::read(self.data.add(self.size)))
+        }
+    }
+
+    // Keeps the capacity, only the elements are dropped
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        // the first `size` elements are initialized and `data` is never null
+        unsafe {
+            std::slice::from_raw_parts(self.data, self.size).iter()
+        }
+    }
+}
+
+impl<T: Debug + Copy + PartialEq> Default for Vector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Debug> fmt::Debug for Vector<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut vec_contents = f.debug_list();
@@ -149,6 +193,7 @@ impl <T: Debug> Drop for Vector<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::property::{check, ensure_eq, shrink_value, Operation, Rng};
     
     #[test]
     fn basics() {
@@ -163,14 +208,111 @@ mod tests {
             my_vec.set(5, 100);
             println!("{}", my_vec);
             my_vec.remove(6);
-            println!("{}", my_vec.get(5));
-            println!("{}", my_vec);
+            assert_eq!(my_vec.get(5), 100);
+            assert_eq!(format!("{}", my_vec), "[0, 1, 2, 3, 4, 100, 7, 8, 9]");
 
-            match my_vec.find(9) {
-                Some(index) => println!("Found at index {}", index),
-                None => println!("Not found"),
+            assert_eq!(my_vec.find(9), Some(8));
+            assert_eq!(my_vec.find(6), None);
+        }
+    }
+
+    #[test]
+    fn test_len_pop_clear() {
+        let mut my_vec: Vector<i32> = Vector::new();
+        assert!(my_vec.is_empty());
+        assert_eq!(my_vec.pop(), None);
+
+        for i in 0..5 {
+            my_vec.insert(i);
+        }
+
+        assert_eq!(my_vec.len(), 5);
+        assert_eq!(my_vec.pop(), Some(4));
+        assert_eq!(my_vec.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        my_vec.clear();
+        assert!(my_vec.is_empty());
+        assert_eq!(my_vec.iter().count(), 0);
+    }
+
+    // Indexes are taken modulo the current length so every operation hits a real element
+    #[derive(Clone, Debug)]
+    enum Op {
+        Insert(i32),
+        Pop,
+        Set(usize, i32),
+        Remove(usize),
+        Find(i32),
+        Clear,
+    }
+
+    impl Operation for Op {
+        fn generate(rng: &mut Rng) -> Self {
+            match rng.below(12) {
+                0..=4 => Op::Insert(rng.value(20)),
+                5 | 6 => Op::Pop,
+                7 => Op::Set(rng.below(64), rng.value(20)),
+                8 | 9 => Op::Remove(rng.below(64)),
+                10 => Op::Find(rng.value(20)),
+                _ => Op::Clear,
+            }
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            match *self {
+                Op::Insert(value) => shrink_value(value).into_iter().map(Op::Insert).collect(),
+                Op::Set(index, value) => shrink_value(value).into_iter().map(|v| Op::Set(index, v)).collect(),
+                Op::Find(value) => shrink_value(value).into_iter().map(Op::Find).collect(),
+                _ => vec![],
             }
-            
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_matches_std_vec() {
+        check::<Op, _>("Vector against Vec", |ops| {
+            let mut vector: Vector<i32> = Vector::new();
+            let mut model: Vec<i32> = Vec::new();
+
+            for (step, op) in ops.iter().enumerate() {
+                match *op {
+                    Op::Insert(value) => {
+                        vector.insert(value);
+                        model.push(value);
+                    }
+                    Op::Pop => {
+                        ensure_eq!(vector.pop(), model.pop(), "step {} pop", step);
+                    }
+                    Op::Set(index, value) if !model.is_empty() => {
+                        let index = index % model.len();
+                        unsafe { vector.set(index, value) };
+                        model[index] = value;
+                    }
+                    Op::Remove(index) if !model.is_empty() => {
+                        let index = index % model.len();
+                        unsafe { vector.remove(index) };
+                        model.remove(index);
+                    }
+                    Op::Find(value) => {
+                        let found = unsafe { vector.find(value) };
+                        ensure_eq!(found, model.iter().position(|&x| x == value), "step {} find", step);
+                    }
+                    Op::Clear => {
+                        vector.clear();
+                        model.clear();
+                    }
+                    _ => {}
+                }
+
+                ensure_eq!(vector.len(), model.len(), "step {} len", step);
+                ensure_eq!(vector.iter().copied().collect::<Vec<_>>(), model, "step {} contents", step);
+
+                for (index, &expected) in model.iter().enumerate() {
+                    ensure_eq!(unsafe { vector.get(index) }, expected, "step {} get({})", step, index);
+                }
+            }
+
+            Ok(())
+        });
+    }
+}
@@ -1,11 +1,11 @@
-use std::collections::LinkedList;
+use data_structures::linear_data_structures::linked_lists::raw_double::RawDoubleLinkedList;
 
 fn main() {
-   let mut list = LinkedList::new();
+   let mut list = RawDoubleLinkedList::new();
    list.push_back(1);
    list.push_back(3);
    list.push_back(6);
    list.push_back(2);
 
-   println!("List {:?}", list);
+   println!("List {}", list);
 }
\ No newline at end of file
@@ -0,0 +1,15 @@
+// The Rc<RefCell<..>> containers hand out guards straight into their nodes and walk from
+// node to node with plain references, so nothing gets cloned and no step has to hold on to
+// the Ref guard of the node before it. A reference read out of a Ref only lives as long as
+// the guard though, so the step goes through a raw pointer here. The link helpers in each
+// container wrap this with their lifetime tied to the node they start from.
+
+use std::cell::RefCell;
+
+/// # Safety
+/// `node` has to point at a live node of a container, and nothing may unlink or free that
+/// node for as long as `'a` lasts. The containers get that by only rewriting links in
+/// methods taking `&mut self` and by keeping `'a` within a shared borrow of the container.
+pub(crate) unsafe fn node_ref<'a, N>(node: *const RefCell<N>) -> &'a RefCell<N> {
+    unsafe { &*node }
+}
@@ -0,0 +1,1107 @@
+// Red-black tree with the nodes in one Vec and u32 indices for the left, right and parent
+// links, the same idea as the arena linked list. Rotations and fix-ups only copy indices
+// around, there is no refcounting and no RefCell that can panic on a bad borrow order.
+// Deleted nodes go on a free list and their slots get reused by later inserts.
+//
+// The balancing is step for step the one in red_black_tree.rs, so both trees end up with
+// the same shape and colors for the same operations (the tests check exactly that).
+
+use core::fmt;
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+    vec,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    bulk,
+    invariant::{check_order, InvariantViolation},
+};
+
+const NIL: u32 = u32::MAX; // index used as "no node", like None in the Rc tree
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Slot<T> {
+    value: Option<T>, // None while the slot sits on the free list
+    is_red: bool,
+    left: u32,
+    right: u32, // for free slots this links the free list
+    parent: u32,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArenaRedBlackTree<T> {
+    slots: Vec<Slot<T>>,
+    root: u32,
+    free: u32,
+    len: usize,
+}
+
+impl<T> ArenaRedBlackTree<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            root: NIL,
+            free: NIL,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.root = NIL;
+        self.free = NIL;
+        self.len = 0;
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.value_at(self.find(value))
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(value) != NIL
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.value_at(self.leftmost(self.root))
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.value_at(self.rightmost(self.root))
+    }
+
+    // Smallest value greater than `value`, which doesn't have to be in the tree itself
+    pub fn successor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.value_at(self.lower_bound(Bound::Excluded(value)))
+    }
+
+    // Largest value less than `value`
+    pub fn predecessor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.value_at(self.upper_bound(Bound::Excluded(value)))
+    }
+
+    // In order, walks the parent indices so no stack is needed
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            tree: self,
+            next: self.leftmost(self.root),
+            next_back: self.rightmost(self.root),
+            remaining: self.len,
+        }
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let first = self.lower_bound(range.start_bound());
+        let last = self.upper_bound(range.end_bound());
+
+        // an empty range leaves `last` before `first`
+        let empty = first == NIL || last == NIL || self.key::<Q>(first) > self.key(last);
+
+        if empty {
+            Range { tree: self, next: NIL, next_back: NIL }
+        } else {
+            Range { tree: self, next: first, next_back: last }
+        }
+    }
+
+    fn slot(&self, index: u32) -> &Slot<T> {
+        &self.slots[index as usize]
+    }
+
+    fn slot_mut(&mut self, index: u32) -> &mut Slot<T> {
+        &mut self.slots[index as usize]
+    }
+
+    fn value_at(&self, index: u32) -> Option<&T> {
+        if index == NIL {
+            None
+        } else {
+            self.slot(index).value.as_ref()
+        }
+    }
+
+    fn key<Q>(&self, index: u32) -> &Q
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+    {
+        self.slot(index).value.as_ref().expect("linked slot is never free").borrow()
+    }
+
+    fn is_red(&self, index: u32) -> bool {
+        index != NIL && self.slot(index).is_red
+    }
+
+    fn find<Q>(&self, value: &Q) -> u32
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root;
+
+        while current != NIL {
+            current = match value.cmp(self.key(current)) {
+                Ordering::Less => self.slot(current).left,
+                Ordering::Greater => self.slot(current).right,
+                Ordering::Equal => return current,
+            };
+        }
+
+        NIL
+    }
+
+    fn leftmost(&self, mut index: u32) -> u32 {
+        while index != NIL && self.slot(index).left != NIL {
+            index = self.slot(index).left;
+        }
+        index
+    }
+
+    fn rightmost(&self, mut index: u32) -> u32 {
+        while index != NIL && self.slot(index).right != NIL {
+            index = self.slot(index).right;
+        }
+        index
+    }
+
+    // Next node in order: the leftmost of the right subtree, or else the first ancestor we
+    // reach from its left side
+    fn next_index(&self, index: u32) -> u32 {
+        let right = self.slot(index).right;
+        if right != NIL {
+            return self.leftmost(right);
+        }
+
+        let mut child = index;
+        let mut parent = self.slot(index).parent;
+        while parent != NIL && self.slot(parent).right == child {
+            child = parent;
+            parent = self.slot(parent).parent;
+        }
+
+        parent
+    }
+
+    fn prev_index(&self, index: u32) -> u32 {
+        let left = self.slot(index).left;
+        if left != NIL {
+            return self.rightmost(left);
+        }
+
+        let mut child = index;
+        let mut parent = self.slot(index).parent;
+        while parent != NIL && self.slot(parent).left == child {
+            child = parent;
+            parent = self.slot(parent).parent;
+        }
+
+        parent
+    }
+
+    // First node inside the lower bound
+    fn lower_bound<Q>(&self, bound: Bound<&Q>) -> u32
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root;
+        let mut found = NIL;
+
+        while current != NIL {
+            let inside = match bound {
+                Bound::Included(value) => self.key(current) >= value,
+                Bound::Excluded(value) => self.key(current) > value,
+                Bound::Unbounded => true,
+            };
+
+            if inside {
+                found = current;
+                current = self.slot(current).left;
+            } else {
+                current = self.slot(current).right;
+            }
+        }
+
+        found
+    }
+
+    // Last node inside the upper bound
+    fn upper_bound<Q>(&self, bound: Bound<&Q>) -> u32
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root;
+        let mut found = NIL;
+
+        while current != NIL {
+            let inside = match bound {
+                Bound::Included(value) => self.key(current) <= value,
+                Bound::Excluded(value) => self.key(current) < value,
+                Bound::Unbounded => true,
+            };
+
+            if inside {
+                found = current;
+                current = self.slot(current).right;
+            } else {
+                current = self.slot(current).left;
+            }
+        }
+
+        found
+    }
+
+    fn allocate(&mut self, value: T, parent: u32) -> u32 {
+        let slot = Slot {
+            value: Some(value),
+            is_red: true,
+            left: NIL,
+            right: NIL,
+            parent,
+        };
+
+        if self.free != NIL {
+            let index = self.free;
+            self.free = self.slot(index).right;
+            *self.slot_mut(index) = slot;
+            return index;
+        }
+
+        let index = u32::try_from(self.slots.len())
+            .ok()
+            .filter(|&index| index != NIL)
+            .expect("arena red-black tree is limited to u32::MAX - 1 slots");
+
+        self.slots.push(slot);
+        index
+    }
+
+    fn release(&mut self, index: u32) -> T {
+        let free = self.free;
+        let slot = self.slot_mut(index);
+        let value = slot.value.take().expect("slot released twice");
+
+        slot.left = NIL;
+        slot.parent = NIL;
+        slot.right = free;
+        self.free = index;
+
+        value
+    }
+
+    // Points whatever linked to `old` (its parent or the root) at `new`
+    fn replace_child(&mut self, parent: u32, old: u32, new: u32) {
+        if parent == NIL {
+            self.root = new;
+        } else if self.slot(parent).left == old {
+            self.slot_mut(parent).left = new;
+        } else {
+            self.slot_mut(parent).right = new;
+        }
+    }
+
+    fn left_rotation(&mut self, node: u32) {
+        let right = self.slot(node).right;
+        let inner = self.slot(right).left;
+        let parent = self.slot(node).parent;
+
+        self.slot_mut(node).right = inner;
+        if inner != NIL {
+            self.slot_mut(inner).parent = node;
+        }
+
+        self.slot_mut(right).parent = parent;
+        self.replace_child(parent, node, right);
+
+        self.slot_mut(right).left = node;
+        self.slot_mut(node).parent = right;
+    }
+
+    fn right_rotation(&mut self, node: u32) {
+        let left = self.slot(node).left;
+        let inner = self.slot(left).right;
+        let parent = self.slot(node).parent;
+
+        self.slot_mut(node).left = inner;
+        if inner != NIL {
+            self.slot_mut(inner).parent = node;
+        }
+
+        self.slot_mut(left).parent = parent;
+        self.replace_child(parent, node, left);
+
+        self.slot_mut(left).right = node;
+        self.slot_mut(node).parent = left;
+    }
+
+    fn insert_fix(&mut self, mut node: u32) {
+        while self.is_red(self.slot(node).parent) {
+            let mut parent = self.slot(node).parent;
+            // a red parent is never the root, so the grandparent exists
+            let grand = self.slot(parent).parent;
+
+            if self.slot(grand).left == parent {
+                let uncle = self.slot(grand).right;
+
+                if self.is_red(uncle) {
+                    self.slot_mut(parent).is_red = false;
+                    self.slot_mut(uncle).is_red = false;
+                    self.slot_mut(grand).is_red = true;
+                    node = grand;
+                    continue;
+                }
+
+                if self.slot(parent).right == node {
+                    self.left_rotation(parent);
+                    std::mem::swap(&mut node, &mut parent);
+                }
+
+                self.slot_mut(parent).is_red = false;
+                self.slot_mut(grand).is_red = true;
+                self.right_rotation(grand);
+            } else {
+                let uncle = self.slot(grand).left;
+
+                if self.is_red(uncle) {
+                    self.slot_mut(parent).is_red = false;
+                    self.slot_mut(uncle).is_red = false;
+                    self.slot_mut(grand).is_red = true;
+                    node = grand;
+                    continue;
+                }
+
+                if self.slot(parent).left == node {
+                    self.right_rotation(parent);
+                    std::mem::swap(&mut node, &mut parent);
+                }
+
+                self.slot_mut(parent).is_red = false;
+                self.slot_mut(grand).is_red = true;
+                self.left_rotation(grand);
+            }
+        }
+
+        let root = self.root;
+        self.slot_mut(root).is_red = false;
+    }
+
+    // Puts `new` where `old` hangs, `new` can be NIL
+    fn transplant(&mut self, old: u32, new: u32) {
+        let parent = self.slot(old).parent;
+        self.replace_child(parent, old, new);
+
+        if new != NIL {
+            self.slot_mut(new).parent = parent;
+        }
+    }
+
+    // Unlinks the node and rebalances, the slot itself is left for the caller to release
+    fn unlink(&mut self, node: u32) {
+        let left = self.slot(node).left;
+        let right = self.slot(node).right;
+        let mut removed_red = self.slot(node).is_red;
+
+        // x can be NIL, so where it sits has to be tracked separately
+        let (x, x_parent) = if left == NIL {
+            let parent = self.slot(node).parent;
+            self.transplant(node, right);
+            (right, parent)
+        } else if right == NIL {
+            let parent = self.slot(node).parent;
+            self.transplant(node, left);
+            (left, parent)
+        } else {
+            let successor = self.leftmost(right);
+            removed_red = self.slot(successor).is_red;
+            let x = self.slot(successor).right;
+
+            let x_parent = if self.slot(successor).parent == node {
+                successor
+            } else {
+                let parent = self.slot(successor).parent;
+                self.transplant(successor, x);
+
+                self.slot_mut(successor).right = right;
+                self.slot_mut(right).parent = successor;
+                parent
+            };
+
+            self.transplant(node, successor);
+
+            self.slot_mut(successor).left = left;
+            self.slot_mut(left).parent = successor;
+            self.slot_mut(successor).is_red = self.slot(node).is_red;
+
+            (x, x_parent)
+        };
+
+        if !removed_red {
+            self.delete_fix(x, x_parent);
+        }
+    }
+
+    // x carries an extra black, it gets pushed up the tree until a red node or the root
+    // can absorb it. The sibling always exists, the extra black means its side is at
+    // least one black node deep
+    fn delete_fix(&mut self, mut x: u32, mut parent: u32) {
+        while x != self.root && !self.is_red(x) && parent != NIL {
+            if self.slot(parent).left == x {
+                let mut sibling = self.slot(parent).right;
+
+                if self.is_red(sibling) {
+                    self.slot_mut(sibling).is_red = false;
+                    self.slot_mut(parent).is_red = true;
+                    self.left_rotation(parent);
+                    sibling = self.slot(parent).right;
+                }
+
+                let sibling_left_red = self.is_red(self.slot(sibling).left);
+                let sibling_right_red = self.is_red(self.slot(sibling).right);
+
+                if !sibling_left_red && !sibling_right_red {
+                    self.slot_mut(sibling).is_red = true;
+                    x = parent;
+                    parent = self.slot(parent).parent;
+                } else {
+                    if !sibling_right_red {
+                        let inner = self.slot(sibling).left;
+                        self.slot_mut(inner).is_red = false;
+                        self.slot_mut(sibling).is_red = true;
+                        self.right_rotation(sibling);
+                        sibling = self.slot(parent).right;
+                    }
+
+                    self.slot_mut(sibling).is_red = self.slot(parent).is_red;
+                    self.slot_mut(parent).is_red = false;
+
+                    let outer = self.slot(sibling).right;
+                    if outer != NIL {
+                        self.slot_mut(outer).is_red = false;
+                    }
+
+                    self.left_rotation(parent);
+                    x = self.root;
+                    parent = NIL;
+                }
+            } else {
+                let mut sibling = self.slot(parent).left;
+
+                if self.is_red(sibling) {
+                    self.slot_mut(sibling).is_red = false;
+                    self.slot_mut(parent).is_red = true;
+                    self.right_rotation(parent);
+                    sibling = self.slot(parent).left;
+                }
+
+                let sibling_left_red = self.is_red(self.slot(sibling).left);
+                let sibling_right_red = self.is_red(self.slot(sibling).right);
+
+                if !sibling_left_red && !sibling_right_red {
+                    self.slot_mut(sibling).is_red = true;
+                    x = parent;
+                    parent = self.slot(parent).parent;
+                } else {
+                    if !sibling_left_red {
+                        let inner = self.slot(sibling).right;
+                        self.slot_mut(inner).is_red = false;
+                        self.slot_mut(sibling).is_red = true;
+                        self.left_rotation(sibling);
+                        sibling = self.slot(parent).left;
+                    }
+
+                    self.slot_mut(sibling).is_red = self.slot(parent).is_red;
+                    self.slot_mut(parent).is_red = false;
+
+                    let outer = self.slot(sibling).left;
+                    if outer != NIL {
+                        self.slot_mut(outer).is_red = false;
+                    }
+
+                    self.right_rotation(parent);
+                    x = self.root;
+                    parent = NIL;
+                }
+            }
+        }
+
+        if x != NIL {
+            self.slot_mut(x).is_red = false;
+        }
+    }
+}
+
+impl<T: Ord> ArenaRedBlackTree<T> {
+    // Builds a balanced tree in O(n) with no fix-ups, colored like the Rc tree's
+    // from_sorted_iter. Equal neighbours are kept once, panics if the values aren't ascending
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted(bulk::expect_sorted(iter))
+    }
+
+    // `values` has to be ascending without duplicates already
+    fn from_sorted(values: Vec<T>) -> Self {
+        let len = values.len();
+        let mut tree = Self::with_capacity(len);
+
+        tree.root = tree.build(&mut values.into_iter(), len, 0, bulk::red_depth(len));
+        tree.len = len;
+        tree
+    }
+
+    // Takes the next `len` values in order, left subtree first, then the root, then the right
+    fn build(&mut self, values: &mut vec::IntoIter<T>, len: usize, depth: usize, red_depth: usize) -> u32 {
+        if len == 0 {
+            return NIL;
+        }
+
+        let left_len = bulk::left_len(len);
+        let left = self.build(values, left_len, depth + 1, red_depth);
+        let value = values.next().expect("build is never asked for more values than there are");
+        let index = self.allocate(value, NIL);
+        let right = self.build(values, len - 1 - left_len, depth + 1, red_depth);
+
+        for child in [left, right] {
+            if child != NIL {
+                self.slot_mut(child).parent = index;
+            }
+        }
+
+        let slot = self.slot_mut(index);
+        slot.left = left;
+        slot.right = right;
+        slot.is_red = depth == red_depth;
+
+        index
+    }
+
+    // Returns false when the value was already there
+    pub fn insert(&mut self, value: T) -> bool {
+        let mut parent = NIL;
+        let mut current = self.root;
+        let mut go_left = false;
+
+        while current != NIL {
+            parent = current;
+
+            match value.cmp(self.key(current)) {
+                Ordering::Less => {
+                    go_left = true;
+                    current = self.slot(current).left;
+                }
+                Ordering::Greater => {
+                    go_left = false;
+                    current = self.slot(current).right;
+                }
+                Ordering::Equal => return false,
+            }
+        }
+
+        let node = self.allocate(value, parent);
+
+        if parent == NIL {
+            self.root = node;
+        } else if go_left {
+            self.slot_mut(parent).left = node;
+        } else {
+            self.slot_mut(parent).right = node;
+        }
+
+        self.insert_fix(node);
+        self.len += 1;
+        true
+    }
+
+    // Returns whether the value was in the tree
+    pub fn delete<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.take(value).is_some()
+    }
+
+    // Removes the value and hands it back
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = self.find(value);
+        if node == NIL {
+            return None;
+        }
+
+        self.unlink(node);
+        self.len -= 1;
+        Some(self.release(node))
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        let node = self.leftmost(self.root);
+        if node == NIL {
+            return None;
+        }
+
+        self.unlink(node);
+        self.len -= 1;
+        Some(self.release(node))
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        let node = self.rightmost(self.root);
+        if node == NIL {
+            return None;
+        }
+
+        self.unlink(node);
+        self.len -= 1;
+        Some(self.release(node))
+    }
+}
+
+impl<T: Ord + Clone> ArenaRedBlackTree<T> {
+    // Same checks as RedBlackTree::validate, reported the same way
+    pub fn validate(&self) -> Result<(), InvariantViolation<T>> {
+        if self.root != NIL {
+            let root = self.slot(self.root);
+            let value = self.key::<T>(self.root).clone();
+
+            if root.is_red {
+                return Err(InvariantViolation::RedRoot { root: value });
+            }
+
+            if root.parent != NIL {
+                return Err(InvariantViolation::Parent { node: None, child: value });
+            }
+        }
+
+        self.validate_node(self.root, None, None).map(|_| ())
+    }
+
+    fn validate_node(&self, node: u32, low: Option<&T>, high: Option<&T>) -> Result<usize, InvariantViolation<T>> {
+        if node == NIL {
+            return Ok(1);
+        }
+
+        let slot = self.slot(node);
+        let value = self.key::<T>(node);
+
+        check_order(value, low, high)?;
+
+        for child in [slot.left, slot.right] {
+            if child == NIL {
+                continue;
+            }
+
+            if self.slot(child).parent != node {
+                return Err(InvariantViolation::Parent {
+                    node: Some(value.clone()),
+                    child: self.key::<T>(child).clone(),
+                });
+            }
+
+            if slot.is_red && self.slot(child).is_red {
+                return Err(InvariantViolation::RedRed {
+                    node: value.clone(),
+                    child: self.key::<T>(child).clone(),
+                });
+            }
+        }
+
+        let left = self.validate_node(slot.left, low, Some(value))?;
+        let right = self.validate_node(slot.right, Some(value), high)?;
+
+        if left != right {
+            return Err(InvariantViolation::BlackHeight {
+                node: value.clone(),
+                left,
+                right,
+            });
+        }
+
+        Ok(left + if slot.is_red { 0 } else { 1 })
+    }
+}
+
+impl<T> Default for ArenaRedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for ArenaRedBlackTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+// Sorted input takes the O(n) path when the tree is empty, anything else is inserted one by one
+impl<T: Ord> Extend<T> for ArenaRedBlackTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        match bulk::into_sorted(iter.into_iter().collect()) {
+            Ok(sorted) if self.is_empty() => *self = Self::from_sorted(sorted),
+            Ok(values) | Err(values) => {
+                for value in values {
+                    self.insert(value);
+                }
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaRedBlackTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a, T> {
+    tree: &'a ArenaRedBlackTree<T>,
+    next: u32,
+    next_back: u32,
+    remaining: usize, // stops the two ends from walking past each other
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.next;
+        self.next = self.tree.next_index(node);
+        self.remaining -= 1;
+        self.tree.value_at(node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.next_back;
+        self.next_back = self.tree.prev_index(node);
+        self.remaining -= 1;
+        self.tree.value_at(node)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a ArenaRedBlackTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// There's no count up front, the two ends are done once they meet on the same node
+pub struct Range<'a, T> {
+    tree: &'a ArenaRedBlackTree<T>,
+    next: u32,
+    next_back: u32,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next;
+        if node == NIL {
+            return None;
+        }
+
+        if node == self.next_back {
+            self.next = NIL;
+            self.next_back = NIL;
+        } else {
+            self.next = self.tree.next_index(node);
+        }
+
+        self.tree.value_at(node)
+    }
+}
+
+impl<T> DoubleEndedIterator for Range<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.next_back;
+        if node == NIL {
+            return None;
+        }
+
+        if node == self.next {
+            self.next = NIL;
+            self.next_back = NIL;
+        } else {
+            self.next_back = self.tree.prev_index(node);
+        }
+
+        self.tree.value_at(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        non_linear_data_structures::trees::{
+            model::{check_set, ModelSet, SetOp},
+            red_black_tree::RedBlackTree,
+        },
+        property::{check, ensure_eq},
+    };
+
+    impl<T: Clone> ArenaRedBlackTree<T> {
+        // Pre-order values with their colors, together with the in-order sort that pins
+        // down the whole tree
+        fn shape(&self) -> Vec<(T, bool)> {
+            let mut shape = vec![];
+            let mut stack = vec![self.root];
+
+            while let Some(node) = stack.pop() {
+                if node != NIL {
+                    let slot = self.slot(node);
+                    shape.push((slot.value.clone().unwrap(), slot.is_red));
+                    stack.push(slot.right);
+                    stack.push(slot.left);
+                }
+            }
+
+            shape
+        }
+    }
+
+    #[test]
+    fn test_insert_delete_and_lookups() {
+        let mut tree = ArenaRedBlackTree::new();
+        assert!(tree.is_empty());
+        assert!(tree.min().is_none());
+
+        for value in [40, 10, 30, 20, 50] {
+            assert!(tree.insert(value));
+        }
+        assert!(!tree.insert(30));
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.validate(), Ok(()));
+
+        assert!(tree.contains(&20));
+        assert_eq!(tree.min(), Some(&10));
+        assert_eq!(tree.max(), Some(&50));
+        assert_eq!(tree.successor(&25), Some(&30));
+        assert_eq!(tree.predecessor(&10), None);
+
+        assert!(tree.delete(&30));
+        assert!(!tree.delete(&30));
+        assert_eq!(tree.take(&10), Some(10));
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![20, 40, 50]);
+    }
+
+    #[test]
+    fn test_freed_slots_are_reused() {
+        let mut tree: ArenaRedBlackTree<i32> = (0..100).collect();
+        assert_eq!(tree.slots.len(), 100);
+
+        for value in 0..50 {
+            tree.delete(&value);
+        }
+        for value in 100..150 {
+            tree.insert(value);
+        }
+
+        // the 50 new values went into the 50 freed slots
+        assert_eq!(tree.slots.len(), 100);
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), (50..150).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_pop_and_iterate_both_ends() {
+        let mut tree: ArenaRedBlackTree<i32> = (0..20).rev().collect();
+
+        assert_eq!(tree.iter().next_back(), Some(&19));
+        assert_eq!(tree.iter().len(), 20);
+        assert_eq!(tree.range(5..9).copied().collect::<Vec<_>>(), vec![5, 6, 7, 8]);
+        assert_eq!(tree.range(..=3).rev().copied().collect::<Vec<_>>(), vec![3, 2, 1, 0]);
+        assert_eq!(tree.range(7..7).count(), 0);
+
+        assert_eq!(tree.pop_first(), Some(0));
+        assert_eq!(tree.pop_last(), Some(19));
+        assert_eq!(tree.validate(), Ok(()));
+
+        while tree.pop_first().is_some() {}
+        assert!(tree.is_empty());
+        assert_eq!(tree.pop_last(), None);
+        assert_eq!(format!("{:?}", tree), "{}");
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let mut tree: ArenaRedBlackTree<String> = ["pear", "apple", "fig"].iter().map(|word| word.to_string()).collect();
+
+        assert!(tree.contains("fig"));
+        assert_eq!(tree.successor("b").map(String::as_str), Some("fig"));
+        assert!(tree.delete("pear"));
+        assert_eq!(format!("{:?}", tree), r#"{"apple", "fig"}"#);
+    }
+
+    impl ModelSet for ArenaRedBlackTree<i32> {
+        fn insert(&mut self, value: i32) -> bool {
+            ArenaRedBlackTree::insert(self, value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            ArenaRedBlackTree::delete(self, &value)
+        }
+
+        fn values(&self) -> Vec<i32> {
+            self.iter().copied().collect()
+        }
+
+        fn contains(&self, value: i32) -> bool {
+            ArenaRedBlackTree::contains(self, &value)
+        }
+
+        fn len(&self) -> usize {
+            ArenaRedBlackTree::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            ArenaRedBlackTree::validate(self)
+        }
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<ArenaRedBlackTree<i32>>("ArenaRedBlackTree against BTreeSet");
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for len in [0, 1, 2, 3, 6, 7, 8, 100, 1024] {
+            let tree = ArenaRedBlackTree::from_sorted_iter(0..len);
+
+            assert_eq!(tree.len(), len as usize);
+            assert!(tree.validate().is_ok(), "len {}", len);
+            assert!(tree.iter().copied().eq(0..len));
+        }
+
+        let mut tree: ArenaRedBlackTree<i32> = (0..100).collect();
+        for i in 0..50 {
+            tree.delete(&(i * 2));
+        }
+        tree.extend([3, 1000, 500]);
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.len(), 52);
+    }
+
+    // Both trees run the same operations and have to agree on every answer and on the
+    // exact shape and colors after each step
+    #[test]
+    fn test_matches_rc_red_black_tree() {
+        check::<SetOp, _>("ArenaRedBlackTree against RedBlackTree", |ops| {
+            let mut arena = ArenaRedBlackTree::new();
+            let mut rc = RedBlackTree::new();
+
+            for (step, op) in ops.iter().enumerate() {
+                match *op {
+                    SetOp::Insert(value) => {
+                        ensure_eq!(arena.insert(value), rc.insert(value), "step {} insert({})", step, value);
+                    }
+                    SetOp::Delete(value) => {
+                        ensure_eq!(arena.delete(&value), rc.delete(value), "step {} delete({})", step, value);
+                    }
+                    SetOp::Contains(value) => {
+                        ensure_eq!(arena.contains(&value), rc.contains(&value), "step {} contains({})", step, value);
+                        ensure_eq!(
+                            arena.successor(&value).copied(),
+                            rc.successor(&value).map(|value| *value),
+                            "step {} successor({})",
+                            step,
+                            value
+                        );
+                        ensure_eq!(
+                            arena.predecessor(&value).copied(),
+                            rc.predecessor(&value).map(|value| *value),
+                            "step {} predecessor({})",
+                            step,
+                            value
+                        );
+                    }
+                    SetOp::Range(low, high) => {
+                        let (low, high) = (low.min(high), low.max(high));
+                        ensure_eq!(
+                            arena.range(low..=high).copied().collect::<Vec<_>>(),
+                            rc.range(low..=high).map(|value| *value).collect::<Vec<_>>(),
+                            "step {} range({}, {})",
+                            step,
+                            low,
+                            high
+                        );
+                    }
+                }
+
+                ensure_eq!(arena.len(), rc.len(), "step {} len", step);
+                ensure_eq!(arena.shape(), rc.shape(), "step {} shape", step);
+            }
+
+            Ok(())
+        });
+    }
+}
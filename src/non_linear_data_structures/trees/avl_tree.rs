@@ -1,9 +1,23 @@
+use core::fmt;
+use std::{
+    borrow::Borrow,
+    ops::RangeBounds,
+    vec,
+};
+
+use super::{
+    bulk,
+    descent::{self, PlainNode, SearchNode},
+    invariant::{check_order, InvariantViolation},
+};
+
 #[derive(Clone)]
 pub struct AvlNode<T> {
     value: T,
     right: Option<Box<AvlNode<T>>>,
     left: Option<Box<AvlNode<T>>>,
     height: i64,
+    size: usize, // nodes in this subtree, kept next to the height for rank and select
 }
 
 impl<T> AvlNode<T> {
@@ -13,38 +27,216 @@ impl<T> AvlNode<T> {
             right: None,
             left: None,
             height: 0,
+            size: 1,
         }
     }
 }
 
+impl<T> SearchNode for AvlNode<T> {
+    type Value = T;
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn compare<Q>(&self, value: &Q) -> std::cmp::Ordering
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        value.cmp(self.value.borrow())
+    }
+}
+
+impl<T> PlainNode for AvlNode<T> {
+    fn value(&self) -> &T {
+        &self.value
+    }
+}
+
+// What split_node hands back: the smaller side, the key if it was there, the bigger side
+type SplitNodes<T> = (Option<Box<AvlNode<T>>>, Option<T>, Option<Box<AvlNode<T>>>);
+
 pub struct AvlTree<T> {
     root: Option<Box<AvlNode<T>>>,
+    len: usize, // kept up to date by insert and delete so len() doesn't walk the tree
 }
 
-impl<T: Eq + Clone + Ord> AvlTree<T> {
-    pub fn new(value: T) -> Self {
-        Self {
-            root: Some(Box::new(AvlNode::new(value))),
+impl<T> AvlTree<T> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Any type the values can be borrowed as works for lookups, like &str for a String tree
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref();
+
+        while let Some(node) = current {
+            match value.cmp(node.value.borrow()) {
+                std::cmp::Ordering::Less => current = node.left.as_deref(),
+                std::cmp::Ordering::Greater => current = node.right.as_deref(),
+                std::cmp::Ordering::Equal => return Some(&node.value),
+            }
         }
+
+        None
     }
 
-    fn get_height(node: Option<&Box<AvlNode<T>>>) -> i64 {
-        match node {
-            Some(n) => {
-                1 + std::cmp::max(
-                    AvlTree::get_height(n.left.as_ref()),
-                    AvlTree::get_height(n.right.as_ref()),
-                )
+    // Only for the maps, which never touch the key part of a value through it. Anything else
+    // could move a value out of its place in the order
+    pub(super) fn get_mut<Q>(&mut self, value: &Q) -> Option<&mut T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref_mut();
+
+        while let Some(node) = current {
+            match value.cmp(node.value.borrow()) {
+                std::cmp::Ordering::Less => current = node.left.as_deref_mut(),
+                std::cmp::Ordering::Greater => current = node.right.as_deref_mut(),
+                std::cmp::Ordering::Equal => return Some(&mut node.value),
             }
+        }
+
+        None
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(value).is_some()
+    }
+
+    // Smallest value, the end of the left spine
+    pub fn first(&self) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+
+        Some(&node.value)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+
+        Some(&node.value)
+    }
+
+    // In order, smallest first
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            front: vec![],
+            back: vec![],
+            remaining: self.len,
+        };
+
+        iter.push_left_spine(self.root.as_deref());
+        iter.push_right_spine(self.root.as_deref());
+        iter
+    }
+
+    // Values inside `range` in order, `tree.range(3..7)` works like it does on BTreeSet
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new(self.root.as_deref(), range)
+    }
+
+    pub(super) fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let mut iter = IterMut { stack: vec![] };
+        iter.push_left_spine(&mut self.root);
+        iter
+    }
+
+    // Largest value that is <= `value`
+    pub fn floor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::floor(self.root.as_deref(), value).map(|node| &node.value)
+    }
+
+    // Smallest value that is >= `value`
+    pub fn ceiling<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::ceiling(self.root.as_deref(), value).map(|node| &node.value)
+    }
+
+    // How many values are smaller than `value`, whether or not it is in the tree.
+    // Sizes make that O(log n)
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::rank(self.root.as_deref(), value)
+    }
+
+    // The k-th smallest value counting from 0, so select(rank(x)) is x when x is in the tree
+    pub fn select(&self, k: usize) -> Option<&T> {
+        descent::select(self.root.as_deref(), k).map(|node| &node.value)
+    }
+
+    fn get_size(node: Option<&AvlNode<T>>) -> usize {
+        node.map_or(0, |n| n.size)
+    }
+
+    fn update_size(node: &mut AvlNode<T>) {
+        node.size = 1 + AvlTree::get_size(node.left.as_deref()) + AvlTree::get_size(node.right.as_deref());
+    }
+}
+
+impl<T: Ord> AvlTree<T> {
+
+    // Every node keeps its height up to date, so this is O(1) (validate checks that)
+    fn get_height(node: Option<&AvlNode<T>>) -> i64 {
+        match node {
+            Some(n) => n.height,
             None => -1, // height of empty node is -1
         }
     }
 
-    fn get_balance(node: Option<&Box<AvlNode<T>>>) -> i64 {
+    fn get_balance(node: Option<&AvlNode<T>>) -> i64 {
         match node {
             Some(n) => {
-                let num_left = AvlTree::get_height(n.left.as_ref());
-                let num_right = AvlTree::get_height(n.right.as_ref());
+                let num_left = AvlTree::get_height(n.left.as_deref());
+                let num_right = AvlTree::get_height(n.right.as_deref());
                 num_left - num_right
             }
             None => 0,
@@ -59,14 +251,16 @@ impl<T: Eq + Clone + Ord> AvlTree<T> {
         left_node.right.as_mut().unwrap().left = right_node;
 
         left_node.right.as_mut().unwrap().height = 1 + std::cmp::max(
-            AvlTree::get_height(left_node.right.as_ref().unwrap().left.as_ref()),
-            AvlTree::get_height(left_node.right.as_ref().unwrap().right.as_ref()),
+            AvlTree::get_height(left_node.right.as_ref().unwrap().left.as_deref()),
+            AvlTree::get_height(left_node.right.as_ref().unwrap().right.as_deref()),
         );
+        AvlTree::update_size(left_node.right.as_mut().unwrap());
 
         left_node.height = 1 + std::cmp::max(
-            AvlTree::get_height(left_node.left.as_ref()),
-            AvlTree::get_height(left_node.right.as_ref()),
+            AvlTree::get_height(left_node.left.as_deref()),
+            AvlTree::get_height(left_node.right.as_deref()),
         );
+        AvlTree::update_size(&mut left_node);
 
         left_node
     }
@@ -79,14 +273,16 @@ impl<T: Eq + Clone + Ord> AvlTree<T> {
         right_node.left.as_mut().unwrap().right = left_node;
 
         right_node.left.as_mut().unwrap().height = 1 + std::cmp::max(
-            AvlTree::get_height(right_node.left.as_ref().unwrap().left.as_ref()),
-            AvlTree::get_height(right_node.left.as_ref().unwrap().right.as_ref()),
+            AvlTree::get_height(right_node.left.as_ref().unwrap().left.as_deref()),
+            AvlTree::get_height(right_node.left.as_ref().unwrap().right.as_deref()),
         );
+        AvlTree::update_size(right_node.left.as_mut().unwrap());
 
         right_node.height = 1 + std::cmp::max(
-            AvlTree::get_height(right_node.left.as_ref()),
-            AvlTree::get_height(right_node.right.as_ref()),
+            AvlTree::get_height(right_node.left.as_deref()),
+            AvlTree::get_height(right_node.right.as_deref()),
         );
+        AvlTree::update_size(&mut right_node);
 
         right_node
     }
@@ -101,39 +297,24 @@ impl<T: Eq + Clone + Ord> AvlTree<T> {
         AvlTree::rotate_left(node)
     }
 
-    fn min_value_node(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
-        while let Some(left) = node.left.take() {
-            node = left;
-        }
-        node
-    }
-    fn insert_node(mut node: Box<AvlNode<T>>, value: T) -> Box<AvlNode<T>> {
-        if value < node.value {
-            if let Some(left_node) = node.left.take() {
-                node.left = Some(AvlTree::insert_node(left_node, value.clone()));
-            } else {
-                node.left = Some(Box::new(AvlNode::new(value.clone())));
-            }
-        } else if value > node.value {
-            if let Some(right_node) = node.right.take() {
-                node.right = Some(AvlTree::insert_node(right_node, value.clone()));
-            } else {
-                node.right = Some(Box::new(AvlNode::new(value.clone())));
-            }
-        } else {
-            return node;
-        }
-
+    fn update_height(node: &mut AvlNode<T>) {
         node.height = 1 + std::cmp::max(
-            AvlTree::get_height(node.left.as_ref()),
-            AvlTree::get_height(node.right.as_ref()),
+            AvlTree::get_height(node.left.as_deref()),
+            AvlTree::get_height(node.right.as_deref()),
         );
+    }
+
+    // Fixes the height of `node` and rotates if one side got two levels deeper,
+    // called on the way back up after a subtree grew or shrank by one
+    fn rebalance(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+        AvlTree::update_height(&mut node);
+        AvlTree::update_size(&mut node);
 
         let balance = AvlTree::get_balance(Some(&node));
 
         // Left Heavy
         if balance > 1 {
-            if value < node.left.as_ref().unwrap().value {
+            if AvlTree::get_balance(node.left.as_deref()) >= 0 {
                 return AvlTree::rotate_right(node); // LL
             } else {
                 return AvlTree::rotate_left_right(node); // LR
@@ -142,7 +323,7 @@ impl<T: Eq + Clone + Ord> AvlTree<T> {
 
         // Right Heavy
         if balance < -1 {
-            if value > node.right.as_ref().unwrap().value {
+            if AvlTree::get_balance(node.right.as_deref()) <= 0 {
                 return AvlTree::rotate_left(node); // RR
             } else {
                 return AvlTree::rotate_right_left(node); // RL
@@ -152,86 +333,674 @@ impl<T: Eq + Clone + Ord> AvlTree<T> {
         node
     }
 
-    pub fn insert(&mut self, value: T)
-    where
-        T: Ord,
-    {
+    // The new node comes in ready made so insert_new can keep track of its address
+    fn insert_node(mut node: Box<AvlNode<T>>, leaf: Box<AvlNode<T>>, inserted: &mut bool) -> Box<AvlNode<T>> {
+        if leaf.value < node.value {
+            if let Some(left_node) = node.left.take() {
+                node.left = Some(AvlTree::insert_node(left_node, leaf, inserted));
+            } else {
+                node.left = Some(leaf);
+                *inserted = true;
+            }
+        } else if leaf.value > node.value {
+            if let Some(right_node) = node.right.take() {
+                node.right = Some(AvlTree::insert_node(right_node, leaf, inserted));
+            } else {
+                node.right = Some(leaf);
+                *inserted = true;
+            }
+        } else {
+            return node;
+        }
+
+        AvlTree::rebalance(node)
+    }
+
+    // Returns false when the value was already there, like BTreeSet::insert
+    pub fn insert(&mut self, value: T) -> bool {
+        let mut inserted = false;
+        let leaf = Box::new(AvlNode::new(value));
+
         if let Some(root) = self.root.take() {
-            self.root = Some(AvlTree::insert_node(root, value));
+            self.root = Some(AvlTree::insert_node(root, leaf, &mut inserted));
         } else {
-            self.root = Some(Box::new(AvlNode::new(value)));
+            self.root = Some(leaf);
+            inserted = true;
+        }
+
+        if inserted {
+            self.len += 1;
+        }
+
+        inserted
+    }
+
+    // Inserts a value that is known not to be in the tree yet and lends it straight back,
+    // for the maps' entry API where the key has moved into the tree by then.
+    // Rebalancing only reshuffles nodes that were on the way down, so remembering which
+    // side the value went at each of them (by address) is enough to walk to it again
+    pub(super) fn insert_new(&mut self, value: T) -> &mut T {
+        let mut turns: Vec<(*const AvlNode<T>, bool)> = vec![];
+        let mut current = self.root.as_deref();
+
+        while let Some(node) = current {
+            let left = value < node.value;
+            turns.push((node, left));
+            current = if left { node.left.as_deref() } else { node.right.as_deref() };
+        }
+
+        let leaf = Box::new(AvlNode::new(value));
+        let target: *const AvlNode<T> = &*leaf;
+
+        let mut inserted = false;
+        self.root = Some(match self.root.take() {
+            Some(root) => AvlTree::insert_node(root, leaf, &mut inserted),
+            None => leaf,
+        });
+        self.len += 1;
+
+        let mut node = self.root.as_deref_mut().expect("a value was just inserted");
+
+        while !std::ptr::eq(node, target) {
+            let left = turns
+                .iter()
+                .find(|&&(seen, _)| std::ptr::eq(seen, node))
+                .map(|&(_, left)| left)
+                .expect("every ancestor of the new node was on the way down");
+
+            let next = if left { node.left.as_deref_mut() } else { node.right.as_deref_mut() };
+            node = next.expect("the new node is below");
         }
+
+        &mut node.value
     }
 
-    fn delete_node(mut node: Box<AvlNode<T>>, value: T) -> Option<Box<AvlNode<T>>> {
-        if value < node.value {
-            if let Some(left) = node.left.take() {
-                node.left = AvlTree::delete_node(left, value);
+    // Detaches the smallest node of the subtree and hands back the rest, rebalanced, with it.
+    // Only walks the left spine, so O(log n) and nothing gets copied
+    fn remove_min(mut node: Box<AvlNode<T>>) -> (Option<Box<AvlNode<T>>>, Box<AvlNode<T>>) {
+        match node.left.take() {
+            Some(left) => {
+                let (rest, min) = AvlTree::remove_min(left);
+                node.left = rest;
+                (Some(AvlTree::rebalance(node)), min)
             }
-        } else if value > node.value {
-            if let Some(right) = node.right.take() {
-                node.right = AvlTree::delete_node(right, value);
+            None => (node.right.take(), node),
+        }
+    }
+
+    fn remove_max(mut node: Box<AvlNode<T>>) -> (Option<Box<AvlNode<T>>>, Box<AvlNode<T>>) {
+        match node.right.take() {
+            Some(right) => {
+                let (rest, max) = AvlTree::remove_max(right);
+                node.right = rest;
+                (Some(AvlTree::rebalance(node)), max)
             }
-        } else {
-            // Node to be deleted found
+            None => (node.left.take(), node),
+        }
+    }
 
-            // Case 1: Only one child or no child
-            if node.left.is_none() {
-                return node.right;
-            } else if node.right.is_none() {
-                return node.left;
+    fn delete_node<Q>(mut node: Box<AvlNode<T>>, value: &Q, removed: &mut Option<T>) -> Option<Box<AvlNode<T>>>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match value.cmp(node.value.borrow()) {
+            std::cmp::Ordering::Less => {
+                if let Some(left) = node.left.take() {
+                    node.left = AvlTree::delete_node(left, value, removed);
+                }
             }
+            std::cmp::Ordering::Greater => {
+                if let Some(right) = node.right.take() {
+                    node.right = AvlTree::delete_node(right, value, removed);
+                }
+            }
+            std::cmp::Ordering::Equal => {
+                // Node to be deleted found
+                let AvlNode { value, left, right, .. } = *node;
+                *removed = Some(value);
+
+                match (left, right) {
+                    // Case 1: Only one child or no child
+                    (None, child) | (child, None) => return child,
+                    // Case 2: Two children, the successor node itself takes the place of this one
+                    (left, Some(right)) => {
+                        let (right, mut successor) = AvlTree::remove_min(right);
 
-            // Case 2: Two children
-            let right_subtree = node.right.take().unwrap();
-            let successor = AvlTree::min_value_node(right_subtree.clone());
-            node.value = successor.value.clone();
-            node.right = AvlTree::delete_node(right_subtree, successor.value);
-            
+                        successor.left = left;
+                        successor.right = right;
+                        node = successor;
+                    }
+                }
+            }
         }
 
-        node.height = 1 + std::cmp::max(
-            AvlTree::get_height(node.left.as_ref()),
-            AvlTree::get_height(node.right.as_ref()),
+        Some(AvlTree::rebalance(node))
+    }
+
+    // Returns whether the value was in the tree
+    pub fn delete(&mut self, value: T) -> bool {
+        self.take(&value).is_some()
+    }
+
+    // Removes the value and hands it back
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut removed = None;
+
+        if let Some(root) = self.root.take() {
+            self.root = AvlTree::delete_node(root, value, &mut removed);
+        }
+
+        if removed.is_some() {
+            self.len -= 1;
+        }
+
+        removed
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        let (rest, min) = AvlTree::remove_min(self.root.take()?);
+        self.root = rest;
+        self.len -= 1;
+
+        Some(min.value)
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        let (rest, max) = AvlTree::remove_max(self.root.take()?);
+        self.root = rest;
+        self.len -= 1;
+
+        Some(max.value)
+    }
+
+    // Builds a perfectly balanced tree in O(n), heights and sizes filled in on the way.
+    // Equal neighbours are kept once, panics if the values aren't ascending
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        AvlTree::from_sorted(bulk::expect_sorted(iter))
+    }
+
+    // `values` has to be ascending without duplicates already
+    fn from_sorted(values: Vec<T>) -> Self {
+        let len = values.len();
+        AvlTree::from_root(AvlTree::build(&mut values.into_iter(), len))
+    }
+
+    // Takes the next `len` values in order, left subtree first, then the root, then the right
+    fn build(values: &mut vec::IntoIter<T>, len: usize) -> Option<Box<AvlNode<T>>> {
+        if len == 0 {
+            return None;
+        }
+
+        let left_len = bulk::left_len(len);
+        let left = AvlTree::build(values, left_len);
+        let value = values.next().expect("build is never asked for more values than there are");
+        let right = AvlTree::build(values, len - 1 - left_len);
+
+        let mut node = Box::new(AvlNode::new(value));
+        node.left = left;
+        node.right = right;
+        AvlTree::update_height(&mut node);
+        AvlTree::update_size(&mut node);
+
+        Some(node)
+    }
+
+    // A tree holding exactly what is in the subtree, its size is the len
+    fn from_root(root: Option<Box<AvlNode<T>>>) -> Self {
+        let len = AvlTree::get_size(root.as_deref());
+        AvlTree { root, len }
+    }
+
+    // Everything in `left` has to be smaller than `key` and everything in `right` bigger.
+    // Only walks down the spine of the taller tree until the heights meet, so it is
+    // O(difference in height)
+    pub fn join(left: AvlTree<T>, key: T, right: AvlTree<T>) -> AvlTree<T> {
+        assert!(
+            left.last().is_none_or(|last| *last < key) && right.first().is_none_or(|first| key < *first),
+            "join needs every value on the left below the key and every value on the right above it"
         );
 
-        let balance = AvlTree::get_balance(Some(&node));
+        let root = AvlTree::join_nodes(left.root, Box::new(AvlNode::new(key)), right.root);
+        AvlTree::from_root(Some(root))
+    }
 
-        if balance > 1 {
-            if AvlTree::get_balance(node.left.as_ref()) >= 0 {
-                return Some(AvlTree::rotate_right(node)); // LL
-            } else {
-                return Some(AvlTree::rotate_left_right(node)); // LR
+    // Splits around `key`: everything smaller, the key itself if it was there, everything bigger
+    pub fn split<Q>(self, key: &Q) -> (AvlTree<T>, Option<T>, AvlTree<T>)
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (left, found, right) = AvlTree::split_node(self.root, key);
+        (AvlTree::from_root(left), found, AvlTree::from_root(right))
+    }
+
+    // All values of both trees. Where both hold an equal value the one from self is kept
+    pub fn union(self, other: AvlTree<T>) -> AvlTree<T> {
+        AvlTree::from_root(AvlTree::union_nodes(self.root, other.root))
+    }
+
+    pub fn intersection(self, other: AvlTree<T>) -> AvlTree<T> {
+        AvlTree::from_root(AvlTree::intersection_nodes(self.root, other.root))
+    }
+
+    // The values of self that are not in other
+    pub fn difference(self, other: AvlTree<T>) -> AvlTree<T> {
+        AvlTree::from_root(AvlTree::difference_nodes(self.root, other.root))
+    }
+
+    // The values that are in exactly one of the two trees
+    pub fn symmetric_difference(self, other: AvlTree<T>) -> AvlTree<T> {
+        AvlTree::from_root(AvlTree::symmetric_difference_nodes(self.root, other.root))
+    }
+
+    // Moves every value of `other` into self and leaves `other` empty, like BTreeSet::append
+    pub fn append(&mut self, other: &mut AvlTree<T>) {
+        let root = AvlTree::union_nodes(self.root.take(), other.root.take());
+        *self = AvlTree::from_root(root);
+        other.len = 0;
+    }
+
+    // Hangs `left` and `right` under `middle`, going down the taller side until the
+    // heights are within one. On the way back up each node needs at most one rotation
+    fn join_nodes(
+        left: Option<Box<AvlNode<T>>>,
+        mut middle: Box<AvlNode<T>>,
+        right: Option<Box<AvlNode<T>>>,
+    ) -> Box<AvlNode<T>> {
+        let left_height = AvlTree::get_height(left.as_deref());
+        let right_height = AvlTree::get_height(right.as_deref());
+
+        if left_height > right_height + 1 {
+            let mut left = left.unwrap();
+            left.right = Some(AvlTree::join_nodes(left.right.take(), middle, right));
+            return AvlTree::rebalance(left);
+        }
+
+        if right_height > left_height + 1 {
+            let mut right = right.unwrap();
+            right.left = Some(AvlTree::join_nodes(left, middle, right.left.take()));
+            return AvlTree::rebalance(right);
+        }
+
+        middle.left = left;
+        middle.right = right;
+        AvlTree::rebalance(middle)
+    }
+
+    // Like join_nodes without a middle value, the biggest value on the left takes its place
+    fn join_without(left: Option<Box<AvlNode<T>>>, right: Option<Box<AvlNode<T>>>) -> Option<Box<AvlNode<T>>> {
+        match left {
+            None => right,
+            Some(left) => {
+                let (rest, max) = AvlTree::remove_max(left);
+                Some(AvlTree::join_nodes(rest, max, right))
             }
         }
+    }
 
-        // Right Heavy
-        if balance < -1 {
-            if AvlTree::get_balance(node.right.as_ref()) <= 0 {
-                return Some(AvlTree::rotate_left(node)); // RR
-            } else {
-                return Some(AvlTree::rotate_right_left(node)); // RL
+    // Each level joins one node back in, so the whole split is O(log n)
+    fn split_node<Q>(
+        node: Option<Box<AvlNode<T>>>,
+        key: &Q,
+    ) -> SplitNodes<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (None, None, None),
+        };
+
+        let left = node.left.take();
+        let right = node.right.take();
+
+        match key.cmp(node.value.borrow()) {
+            std::cmp::Ordering::Less => {
+                let (smaller, found, bigger) = AvlTree::split_node(left, key);
+                (smaller, found, Some(AvlTree::join_nodes(bigger, node, right)))
             }
+            std::cmp::Ordering::Greater => {
+                let (smaller, found, bigger) = AvlTree::split_node(right, key);
+                (Some(AvlTree::join_nodes(left, node, smaller)), found, bigger)
+            }
+            std::cmp::Ordering::Equal => (left, Some(node.value), right),
         }
+    }
 
-        Some(node)
+    // The set operations all work the same way: split the second tree around the root of
+    // the first, recurse on both halves and join the results back together. For sizes
+    // m <= n that comes to O(m log(n/m + 1)), against O(m log n) for inserting one by one
+
+    fn union_nodes(a: Option<Box<AvlNode<T>>>, b: Option<Box<AvlNode<T>>>) -> Option<Box<AvlNode<T>>> {
+        let (mut a, b) = match (a, b) {
+            (None, b) => return b,
+            (a, None) => return a,
+            (Some(a), Some(b)) => (a, b),
+        };
+
+        let (a_left, a_right) = (a.left.take(), a.right.take());
+        let (b_left, _, b_right) = AvlTree::split_node(Some(b), &a.value);
+
+        let left = AvlTree::union_nodes(a_left, b_left);
+        let right = AvlTree::union_nodes(a_right, b_right);
+        Some(AvlTree::join_nodes(left, a, right))
     }
 
-    pub fn delete(&mut self, value: T) {
-        if let Some(root) = self.root.take() {
-            self.root = AvlTree::delete_node(root, value);
+    fn intersection_nodes(a: Option<Box<AvlNode<T>>>, b: Option<Box<AvlNode<T>>>) -> Option<Box<AvlNode<T>>> {
+        let (mut a, b) = match (a, b) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return None,
+        };
+
+        let (a_left, a_right) = (a.left.take(), a.right.take());
+        let (b_left, found, b_right) = AvlTree::split_node(Some(b), &a.value);
+
+        let left = AvlTree::intersection_nodes(a_left, b_left);
+        let right = AvlTree::intersection_nodes(a_right, b_right);
+
+        if found.is_some() {
+            Some(AvlTree::join_nodes(left, a, right))
+        } else {
+            AvlTree::join_without(left, right)
         }
     }
+
+    // Splits the first tree by the second here, the values of the second are the ones to drop
+    fn difference_nodes(a: Option<Box<AvlNode<T>>>, b: Option<Box<AvlNode<T>>>) -> Option<Box<AvlNode<T>>> {
+        let (a, mut b) = match (a, b) {
+            (None, _) => return None,
+            (a, None) => return a,
+            (Some(a), Some(b)) => (a, b),
+        };
+
+        let (b_left, b_right) = (b.left.take(), b.right.take());
+        let (a_left, _, a_right) = AvlTree::split_node(Some(a), &b.value);
+
+        let left = AvlTree::difference_nodes(a_left, b_left);
+        let right = AvlTree::difference_nodes(a_right, b_right);
+        AvlTree::join_without(left, right)
+    }
+
+    fn symmetric_difference_nodes(a: Option<Box<AvlNode<T>>>, b: Option<Box<AvlNode<T>>>) -> Option<Box<AvlNode<T>>> {
+        let (mut a, b) = match (a, b) {
+            (None, b) => return b,
+            (a, None) => return a,
+            (Some(a), Some(b)) => (a, b),
+        };
+
+        let (a_left, a_right) = (a.left.take(), a.right.take());
+        let (b_left, found, b_right) = AvlTree::split_node(Some(b), &a.value);
+
+        let left = AvlTree::symmetric_difference_nodes(a_left, b_left);
+        let right = AvlTree::symmetric_difference_nodes(a_right, b_right);
+
+        if found.is_some() {
+            AvlTree::join_without(left, right)
+        } else {
+            Some(AvlTree::join_nodes(left, a, right))
+        }
+    }
+}
+
+impl<T: Ord + Clone> AvlTree<T> {
+
+    // Checks BST order, that every stored height matches the subtree and that no node
+    // is out of balance
+    pub fn validate(&self) -> Result<(), InvariantViolation<T>> {
+        AvlTree::validate_node(self.root.as_deref(), None, None).map(|_| ())
+    }
+
+    // Hands back the real height and size so the parent can compare them with its stored ones
+    fn validate_node(node: Option<&AvlNode<T>>, low: Option<&T>, high: Option<&T>) -> Result<(i64, usize), InvariantViolation<T>> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok((-1, 0)),
+        };
+
+        check_order(&n.value, low, high)?;
+
+        let (left, left_size) = AvlTree::validate_node(n.left.as_deref(), low, Some(&n.value))?;
+        let (right, right_size) = AvlTree::validate_node(n.right.as_deref(), Some(&n.value), high)?;
+        let actual = 1 + std::cmp::max(left, right);
+        let size = 1 + left_size + right_size;
+
+        if n.size != size {
+            return Err(InvariantViolation::Size {
+                node: n.value.clone(),
+                stored: n.size,
+                actual: size,
+            });
+        }
+
+        if n.height != actual {
+            return Err(InvariantViolation::Height {
+                node: n.value.clone(),
+                stored: n.height,
+                actual,
+            });
+        }
+
+        if (left - right).abs() > 1 {
+            return Err(InvariantViolation::Balance {
+                node: n.value.clone(),
+                balance: left - right,
+            });
+        }
+
+        Ok((actual, size))
+    }
     
 }
 
+impl<T> Default for AvlTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for AvlTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = AvlTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+// Sorted input is built into a tree in O(n) and, if there already are values, merged in
+// with union. Anything else is inserted one by one
+impl<T: Ord> Extend<T> for AvlTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        match bulk::into_sorted(iter.into_iter().collect()) {
+            Ok(sorted) => {
+                let tree = std::mem::take(self);
+                *self = tree.union(AvlTree::from_sorted(sorted));
+            }
+            Err(values) => {
+                for value in values {
+                    self.insert(value);
+                }
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AvlTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+// The front stack holds the left spine still to visit, the back stack the right spine.
+// `remaining` stops the two ends once they meet in the middle
+pub struct Iter<'a, T> {
+    front: Vec<&'a AvlNode<T>>,
+    back: Vec<&'a AvlNode<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut node: Option<&'a AvlNode<T>>) {
+        while let Some(n) = node {
+            self.front.push(n);
+            node = n.left.as_deref();
+        }
+    }
+
+    fn push_right_spine(&mut self, mut node: Option<&'a AvlNode<T>>) {
+        while let Some(n) = node {
+            self.back.push(n);
+            node = n.right.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.front.pop()?;
+        self.push_left_spine(node.right.as_deref());
+        self.remaining -= 1;
+
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.back.pop()?;
+        self.push_right_spine(node.left.as_deref());
+        self.remaining -= 1;
+
+        Some(&node.value)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub type Range<'a, T> = descent::Range<'a, AvlNode<T>>;
+
+// A node can't sit on the stack while its left child is borrowed, so the node is split
+// into its value and right subtree on the way down
+pub struct IterMut<'a, T> {
+    stack: Vec<(&'a mut T, &'a mut Option<Box<AvlNode<T>>>)>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    fn push_left_spine(&mut self, mut node: &'a mut Option<Box<AvlNode<T>>>) {
+        while let Some(n) = node {
+            let AvlNode { value, left, right, .. } = &mut **n;
+            self.stack.push((value, right));
+            node = left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, right) = self.stack.pop()?;
+        self.push_left_spine(right);
+        Some(value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a AvlTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Owning iterator, the tree is taken apart into a sorted Vec up front
+pub struct IntoIter<T> {
+    values: std::vec::IntoIter<T>,
+}
+
+impl<T> IntoIterator for AvlTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut values = Vec::with_capacity(self.len);
+        let mut stack: Vec<Box<AvlNode<T>>> = vec![];
+        let mut current = self.root.take();
+
+        // in order walk that moves every value out, no recursion so deep trees are fine
+        loop {
+            while let Some(mut node) = current {
+                current = node.left.take();
+                stack.push(node);
+            }
+
+            match stack.pop() {
+                Some(mut node) => {
+                    current = node.right.take();
+                    values.push(node.value);
+                }
+                None => break,
+            }
+        }
+
+        IntoIter {
+            values: values.into_iter(),
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.values.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_set, ModelSet};
 
     #[test]
     fn test_avl_tree_creation() {
-        let tree = AvlTree::new(10);
+        let mut tree = AvlTree::new();
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+
+        tree.insert(10);
         assert!(tree.root.is_some());
 
         if let Some(root) = &tree.root {
@@ -244,8 +1013,10 @@ mod tests {
 
     #[test]
     fn test_single_insertion() {
-        let mut tree = AvlTree::new(20);
+        let mut tree = AvlTree::new();
+        tree.insert(20);
         tree.insert(10);
+        assert_eq!(tree.validate(), Ok(()));
 
         let root = tree.root.as_ref().unwrap();
         assert_eq!(root.value, 20);
@@ -255,9 +1026,11 @@ mod tests {
 
     #[test]
     fn test_ll_rotation() {
-        let mut tree = AvlTree::new(30);
+        let mut tree = AvlTree::new();
+        tree.insert(30);
         tree.insert(20);
         tree.insert(10); // Triggers LL rotation
+        assert_eq!(tree.validate(), Ok(()));
 
         let root = tree.root.as_ref().unwrap();
         assert_eq!(root.value, 20);
@@ -267,9 +1040,11 @@ mod tests {
 
     #[test]
     fn test_rr_rotation() {
-        let mut tree = AvlTree::new(10);
+        let mut tree = AvlTree::new();
+        tree.insert(10);
         tree.insert(20);
         tree.insert(30); // Triggers RR rotation
+        assert_eq!(tree.validate(), Ok(()));
 
         let root = tree.root.as_ref().unwrap();
         assert_eq!(root.value, 20);
@@ -279,9 +1054,11 @@ mod tests {
 
     #[test]
     fn test_lr_rotation() {
-        let mut tree = AvlTree::new(30);
+        let mut tree = AvlTree::new();
+        tree.insert(30);
         tree.insert(10);
         tree.insert(20); // Triggers LR rotation
+        assert_eq!(tree.validate(), Ok(()));
 
         let root = tree.root.as_ref().unwrap();
         assert_eq!(root.value, 20);
@@ -291,9 +1068,11 @@ mod tests {
 
     #[test]
     fn test_rl_rotation() {
-        let mut tree = AvlTree::new(10);
+        let mut tree = AvlTree::new();
+        tree.insert(10);
         tree.insert(30);
         tree.insert(20); // Triggers RL rotation
+        assert_eq!(tree.validate(), Ok(()));
 
         let root = tree.root.as_ref().unwrap();
         assert_eq!(root.value, 20);
@@ -303,13 +1082,15 @@ mod tests {
 
     #[test]
     fn test_balanced_tree_heights() {
-        let mut tree = AvlTree::new(15);
+        let mut tree = AvlTree::new();
+        tree.insert(15);
         tree.insert(10);
         tree.insert(20);
         tree.insert(5);
         tree.insert(12);
         tree.insert(18);
         tree.insert(25);
+        assert_eq!(tree.validate(), Ok(()));
 
         fn assert_balanced<T>(node: &Option<Box<AvlNode<T>>>)
         where
@@ -332,7 +1113,8 @@ mod tests {
 
     #[test]
     fn test_avl_deletion_balanced() {
-        let mut tree = AvlTree::new(20);
+        let mut tree = AvlTree::new();
+        tree.insert(20);
         tree.insert(10);
         tree.insert(30);
         tree.insert(5);
@@ -341,12 +1123,311 @@ mod tests {
         tree.insert(35);
 
         tree.delete(10); // node with two children
+        assert_eq!(tree.validate(), Ok(()));
 
         let root = tree.root.as_ref().unwrap();
         assert_eq!(root.value, 20);
         assert!(root.left.is_some());
         assert!(root.right.is_some());
+    }
+
+    #[test]
+    fn test_validate_reports_stale_height_and_imbalance() {
+        let mut tree = AvlTree::new();
+        tree.insert(20);
+        tree.insert(10);
+        tree.insert(30);
+
+        tree.root.as_mut().unwrap().height = 5;
+        assert_eq!(
+            tree.validate(),
+            Err(InvariantViolation::Height { node: 20, stored: 5, actual: 1 })
+        );
+
+        // hang a chain off the right side without rebalancing
+        let root = tree.root.as_mut().unwrap();
+        root.height = 3;
+        root.size = 5;
+        let right = root.right.as_mut().unwrap();
+        right.height = 2;
+        right.size = 3;
+        right.right = Some(Box::new(AvlNode::new(40)));
+        let right_right = right.right.as_mut().unwrap();
+        right_right.height = 1;
+        right_right.size = 2;
+        right_right.right = Some(Box::new(AvlNode::new(50)));
+
+        assert_eq!(
+            tree.validate(),
+            Err(InvariantViolation::Balance { node: 30, balance: -2 })
+        );
+    }
+
+    #[test]
+    fn test_insert_delete_report_changes_and_len() {
+        let mut tree = AvlTree::new();
+
+        assert!(tree.insert(5));
+        assert!(tree.insert(3));
+        assert!(!tree.insert(5));
+        assert_eq!(tree.len(), 2);
+
+        assert!(tree.delete(3));
+        assert!(!tree.delete(3));
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_contains_and_get_with_borrowed_key() {
+        let tree: AvlTree<String> = ["pear", "apple", "fig"].iter().map(|s| s.to_string()).collect();
+
+        assert!(tree.contains("fig"));
+        assert!(!tree.contains("kiwi"));
+        assert_eq!(tree.get("apple"), Some(&"apple".to_string()));
+        assert_eq!(tree.get("plum"), None);
+    }
+
+    #[test]
+    fn test_first_last_and_pop() {
+        let mut tree: AvlTree<i32> = [8, 3, 10, 1, 6, 14].into_iter().collect();
 
-        // Optional: use assert_balanced() from earlier to check full balance
+        assert_eq!(tree.first(), Some(&1));
+        assert_eq!(tree.last(), Some(&14));
+
+        assert_eq!(tree.pop_first(), Some(1));
+        assert_eq!(tree.pop_last(), Some(14));
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.validate(), Ok(()));
+
+        let mut empty: AvlTree<i32> = AvlTree::new();
+        assert_eq!(empty.first(), None);
+        assert_eq!(empty.pop_last(), None);
+    }
+
+    #[test]
+    fn test_iter_from_both_ends() {
+        let tree: AvlTree<i32> = (0..20).rev().collect();
+
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+        assert_eq!(tree.iter().rev().copied().collect::<Vec<_>>(), (0..20).rev().collect::<Vec<_>>());
+        assert_eq!(tree.iter().len(), 20);
+
+        // the two ends meet without handing out anything twice
+        let mut iter = tree.iter();
+        let mut seen = vec![];
+        while let (Some(&a), back) = (iter.next(), iter.next_back()) {
+            seen.push(a);
+            seen.extend(back.copied());
+        }
+        seen.sort();
+        assert_eq!(seen, (0..20).collect::<Vec<_>>());
+
+        assert_eq!(format!("{:?}", AvlTree::from_iter([2, 1, 3])), "{1, 2, 3}");
+    }
+
+    // Ascending inserts rotate at almost every step, the value still has to come back
+    #[test]
+    fn test_take_and_insert_new() {
+        let mut tree = AvlTree::new();
+
+        for i in 0..64 {
+            let value = tree.insert_new(i * 2);
+            assert_eq!(*value, i * 2);
+            *value += 1;
+        }
+        assert_eq!(tree.len(), 64);
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.iter().all(|value| value % 2 == 1));
+
+        assert_eq!(tree.take(&31), Some(31));
+        assert_eq!(tree.take(&31), None);
+        assert_eq!(tree.len(), 63);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let tree: AvlTree<i32> = [5, 2, 9, 1].into_iter().collect();
+        let mut values = tree.into_iter();
+
+        assert_eq!(values.len(), 4);
+        assert_eq!(values.next_back(), Some(9));
+        assert_eq!(values.collect::<Vec<_>>(), vec![1, 2, 5]);
+    }
+
+    // Deleting and popping never copy a value, so types without Clone work too
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct NoClone(u32);
+
+    #[test]
+    fn test_delete_without_clone() {
+        let mut tree: AvlTree<NoClone> = (0..200).map(NoClone).collect();
+
+        for i in (0..200).step_by(2) {
+            assert!(tree.delete(NoClone(i)));
+        }
+
+        assert_eq!(tree.len(), 100);
+        assert!(tree.iter().map(|value| value.0).eq((1..200).step_by(2)));
+        assert_eq!(tree.pop_first(), Some(NoClone(1)));
+        assert_eq!(tree.pop_last(), Some(NoClone(199)));
+    }
+
+    // Deleting inner nodes over and over keeps heights and balance right
+    #[test]
+    fn test_delete_two_child_nodes_stays_balanced() {
+        let mut tree: AvlTree<i32> = (0..512).collect();
+
+        while let Some(&root) = tree.root.as_ref().map(|root| &root.value) {
+            tree.delete(root);
+            assert_eq!(tree.validate(), Ok(()));
+
+            // an AVL tree is never more than about 1.44 log2(n + 2) high
+            let height = tree.root.as_ref().map_or(-1, |root| root.height);
+            let bound = (1.44 * (tree.len() as f64 + 2.0).log2()).ceil() as i64;
+            assert!(height <= bound, "height {} with {} values", height, tree.len());
+        }
+
+        assert!(tree.is_empty());
+    }
+
+    impl ModelSet for AvlTree<i32> {
+        fn insert(&mut self, value: i32) -> bool {
+            AvlTree::insert(self, value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            AvlTree::delete(self, value)
+        }
+
+        fn values(&self) -> Vec<i32> {
+            self.iter().copied().collect()
+        }
+
+        fn contains(&self, value: i32) -> bool {
+            AvlTree::contains(self, &value)
+        }
+
+        fn len(&self) -> usize {
+            AvlTree::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            AvlTree::validate(self)
+        }
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        let tree: AvlTree<i32> = (0..100).map(|i| i * 2).collect();
+
+        let (left, found, right) = tree.split(&50);
+        assert_eq!(found, Some(50));
+        assert_eq!(left.iter().copied().collect::<Vec<_>>(), (0..25).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(right.iter().copied().collect::<Vec<_>>(), (26..100).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!((left.len(), right.len()), (25, 74));
+        assert!(left.validate().is_ok() && right.validate().is_ok());
+
+        // a key that isn't there splits between its neighbours
+        let (small, missing, big) = right.split(&101);
+        assert_eq!(missing, None);
+        assert_eq!((small.len(), big.len()), (25, 49));
+        assert_eq!(small.last(), Some(&100));
+        assert_eq!(big.first(), Some(&102));
+
+        // joining very different heights still comes out balanced
+        let joined = AvlTree::join(left, 50, big);
+        assert_eq!(joined.len(), 75);
+        assert!(joined.validate().is_ok());
+        assert_eq!(joined.rank(&102), 26);
+
+        let single = AvlTree::join(AvlTree::new(), 1, AvlTree::new());
+        assert_eq!(single.iter().copied().collect::<Vec<_>>(), vec![1]);
+
+        let (left, found, right) = AvlTree::<i32>::new().split(&1);
+        assert!(left.is_empty() && found.is_none() && right.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "join needs every value on the left below the key")]
+    fn test_join_out_of_order_panics() {
+        let left: AvlTree<i32> = (0..10).collect();
+        let right: AvlTree<i32> = (20..30).collect();
+        AvlTree::join(left, 5, right);
+    }
+
+    #[test]
+    fn test_set_operations_match_btree_set() {
+        use crate::property::Rng;
+        use std::collections::BTreeSet;
+
+        let mut rng = Rng::new(44);
+
+        // lopsided sizes too, that's the case split and join are there for
+        for (a_len, b_len) in [(0, 0), (0, 30), (30, 0), (1, 200), (200, 1), (50, 60), (300, 20), (7, 400)] {
+            let a: BTreeSet<i32> = (0..a_len).map(|_| rng.value(500)).collect();
+            let b: BTreeSet<i32> = (0..b_len).map(|_| rng.value(500)).collect();
+            let tree = |set: &BTreeSet<i32>| set.iter().copied().collect::<AvlTree<i32>>();
+
+            let check = |result: AvlTree<i32>, expected: Vec<i32>| {
+                assert!(result.validate().is_ok());
+                assert_eq!(result.len(), expected.len());
+                assert_eq!(result.iter().copied().collect::<Vec<_>>(), expected);
+            };
+
+            check(tree(&a).union(tree(&b)), a.union(&b).copied().collect());
+            check(tree(&a).intersection(tree(&b)), a.intersection(&b).copied().collect());
+            check(tree(&a).difference(tree(&b)), a.difference(&b).copied().collect());
+            check(
+                tree(&a).symmetric_difference(tree(&b)),
+                a.symmetric_difference(&b).copied().collect(),
+            );
+
+            let mut appended = tree(&a);
+            let mut other = tree(&b);
+            appended.append(&mut other);
+            assert!(other.is_empty() && other.iter().next().is_none());
+            check(appended, a.union(&b).copied().collect());
+        }
+    }
+
+    #[test]
+    fn test_from_sorted_iter_is_balanced() {
+        for len in [0, 1, 2, 3, 7, 8, 100, 1023, 1024] {
+            let tree = AvlTree::from_sorted_iter(0..len);
+
+            assert_eq!(tree.len(), len as usize);
+            assert!(tree.validate().is_ok(), "len {}", len);
+            assert_eq!(tree.iter().copied().collect::<Vec<_>>(), (0..len).collect::<Vec<_>>());
+
+            // as low as a tree with that many nodes gets
+            let height = tree.root.as_ref().map_or(-1, |root| root.height);
+            assert_eq!(height, if len == 0 { -1 } else { (len as u32).ilog2() as i64 });
+        }
+
+        let tree = AvlTree::from_sorted_iter([1, 1, 2, 3, 3]);
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_extend_with_sorted_and_unsorted_input() {
+        let mut tree: AvlTree<i32> = (0..50).map(|i| i * 2).collect();
+
+        // sorted goes through union, overlapping values stay once
+        tree.extend(40..60);
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.len(), 60);
+        assert_eq!(tree.rank(&60), 40);
+
+        tree.extend([1001, 1000, 7]);
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.len(), 63);
+        assert!(tree.contains(&7) && tree.contains(&1000) && tree.contains(&1001));
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<AvlTree<i32>>("AvlTree against BTreeSet");
     }
 }
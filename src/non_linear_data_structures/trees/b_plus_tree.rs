@@ -0,0 +1,828 @@
+// B+ tree: the entries all live in the leaves and the inner nodes only hold copies of keys
+// to steer by, so K has to be Clone. The leaves are linked in key order, a scan finds its
+// first leaf and then just follows the links without going back up the tree.
+//
+// Linked leaves mean a node is reachable from its parent and from the leaf before it, so
+// the nodes live in one Vec with u32 indices for links, like the arena red-black tree.
+// Freed slots go on a free list and get reused.
+//
+// Unlike the B-tree, an overflowing or underflowing node is fixed on the way back up: a
+// full leaf splits in two and the first key of the new right half goes up as separator, an
+// underfull node borrows from or merges with a sibling. Child i of an inner node holds the
+// keys from separator i - 1 (inclusive) up to separator i. Deleting a key can leave a copy
+// of it behind as separator, which is fine, it still divides the keys the same way.
+
+use std::{
+    borrow::Borrow,
+    iter, mem,
+    ops::{Bound, RangeBounds},
+    slice, vec,
+};
+
+use super::invariant::InvariantViolation;
+
+const NIL: u32 = u32::MAX; // no node, the end of the leaf chain and of the free list
+
+struct Node<K, V> {
+    keys: Vec<K>,       // the entries' keys in a leaf, separators in an inner node
+    values: Vec<V>,     // leaves only, one per key
+    children: Vec<u32>, // inner nodes only, one more than keys. A leaf has none
+    next: u32,          // leaves: the next leaf in key order. Free slots: the free list
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(keys: Vec<K>, values: Vec<V>, next: u32) -> Self {
+        Node { keys, values, children: vec![], next }
+    }
+
+    fn inner(keys: Vec<K>, children: Vec<u32>) -> Self {
+        Node { keys, values: vec![], children, next: NIL }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    // The child whose keys `key` falls among, separators equal to it count as to its left
+    fn child_for<Q>(&self, key: &Q) -> u32
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.children[self.keys.partition_point(|k| k.borrow() <= key)]
+    }
+}
+
+pub struct BPlusTree<K, V, const B: usize = 6> {
+    nodes: Vec<Node<K, V>>,
+    root: u32,
+    first: u32, // the leftmost leaf, where full scans start. Merges never free it
+    free: u32,
+    len: usize,
+}
+
+impl<K, V, const B: usize> BPlusTree<K, V, B> {
+    // Same bounds as in the B-tree, for leaves and inner nodes alike
+    const MAX: usize = 2 * B - 1;
+    const MIN: usize = B - 1;
+
+    pub fn new() -> Self {
+        const { assert!(B >= 2, "a B+ tree needs a minimum degree of at least 2") };
+
+        BPlusTree {
+            nodes: vec![Node::leaf(vec![], vec![], NIL)],
+            root: 0,
+            first: 0,
+            free: NIL,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // Levels from the root down to the leaves, 1 for a tree that is a single leaf
+    pub fn height(&self) -> usize {
+        let mut id = self.root;
+        let mut height = 1;
+
+        while let Some(&child) = self.node(id).children.first() {
+            id = child;
+            height += 1;
+        }
+
+        height
+    }
+
+    fn node(&self, id: u32) -> &Node<K, V> {
+        &self.nodes[id as usize]
+    }
+
+    fn node_mut(&mut self, id: u32) -> &mut Node<K, V> {
+        &mut self.nodes[id as usize]
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> u32 {
+        if self.free == NIL {
+            let id = u32::try_from(self.nodes.len())
+                .ok()
+                .filter(|&id| id != NIL)
+                .expect("a B+ tree is limited to u32::MAX - 1 nodes");
+
+            self.nodes.push(node);
+            return id;
+        }
+
+        let id = self.free;
+        self.free = mem::replace(self.node_mut(id), node).next;
+        id
+    }
+
+    // Hands back what was in the slot
+    fn release(&mut self, id: u32) -> Node<K, V> {
+        let free = Node::leaf(vec![], vec![], self.free);
+        self.free = id;
+        mem::replace(self.node_mut(id), free)
+    }
+
+    // Two different nodes at once, for moving keys between siblings
+    fn pair_mut(&mut self, a: u32, b: u32) -> (&mut Node<K, V>, &mut Node<K, V>) {
+        let (a, b) = (a as usize, b as usize);
+
+        if a < b {
+            let (low, high) = self.nodes.split_at_mut(b);
+            (&mut low[a], &mut high[0])
+        } else {
+            let (low, high) = self.nodes.split_at_mut(a);
+            (&mut high[0], &mut low[b])
+        }
+    }
+
+    fn find_leaf<Q>(&self, key: &Q) -> u32
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut id = self.root;
+
+        while !self.node(id).is_leaf() {
+            id = self.node(id).child_for(key);
+        }
+
+        id
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let leaf = self.node(self.find_leaf(key));
+        let i = leaf.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+        Some(&leaf.values[i])
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let id = self.find_leaf(key);
+        let leaf = self.node_mut(id);
+        let i = leaf.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+        Some(&mut leaf.values[i])
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let leaf = self.node(self.first);
+        leaf.keys.first().zip(leaf.values.first())
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut id = self.root;
+
+        while let Some(&child) = self.node(id).children.last() {
+            id = child;
+        }
+
+        let leaf = self.node(id);
+        leaf.keys.last().zip(leaf.values.last())
+    }
+
+    // In key order along the leaf chain, the inner nodes aren't touched at all
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { nodes: &self.nodes, leaf: self.first, index: 0, end: (NIL, 0) }
+    }
+
+    // The leaves are spread over the Vec in no particular order and each one has to be
+    // borrowed mutably on its own, so they are put in chain order first, which costs one
+    // pass over the nodes and a Vec with a slot per leaf
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut position = vec![usize::MAX; self.nodes.len()];
+        let mut leaves = 0;
+        let mut id = self.first;
+        while id != NIL {
+            position[id as usize] = leaves;
+            leaves += 1;
+            id = self.node(id).next;
+        }
+
+        let mut ordered: Vec<_> = (0..leaves).map(|_| None).collect();
+        for (node, &position) in self.nodes.iter_mut().zip(&position) {
+            if position != usize::MAX {
+                ordered[position] = Some(node.keys.iter().zip(node.values.iter_mut()));
+            }
+        }
+
+        IterMut { inner: ordered.into_iter().flatten().flatten() }
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> BPlusTree<K, V, B> {
+    // Hands back the old value if the key was there, the key itself stays the old one
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (old, split) = self.insert_at(self.root, key, value);
+
+        // the root split, a new root on top with the two halves is the only way up
+        if let Some((separator, right)) = split {
+            self.root = self.alloc(Node::inner(vec![separator], vec![self.root, right]));
+        }
+
+        if old.is_none() {
+            self.len += 1;
+        }
+
+        old
+    }
+
+    // Only for the maps. Finding the slot again is one more walk down, cheap next to
+    // what a B+ tree insert does anyway
+    pub(super) fn insert_new(&mut self, key: K, value: V) -> &mut V {
+        let probe = key.clone();
+        self.insert(key, value);
+        self.get_mut(&probe).unwrap()
+    }
+
+    // Returns the old value if the key was there, and the separator and new right half if
+    // `id` split
+    fn insert_at(&mut self, id: u32, key: K, value: V) -> (Option<V>, Option<(K, u32)>) {
+        let node = self.node_mut(id);
+
+        if node.is_leaf() {
+            match node.keys.binary_search(&key) {
+                Ok(i) => return (Some(mem::replace(&mut node.values[i], value)), None),
+                Err(i) => {
+                    node.keys.insert(i, key);
+                    node.values.insert(i, value);
+                }
+            }
+
+            if node.keys.len() <= Self::MAX {
+                return (None, None);
+            }
+
+            // B on each side, the right half gets linked in after this leaf
+            let keys = node.keys.split_off(B);
+            let values = node.values.split_off(B);
+            let separator = keys[0].clone();
+            let next = node.next;
+
+            let right = self.alloc(Node::leaf(keys, values, next));
+            self.node_mut(id).next = right;
+            return (None, Some((separator, right)));
+        }
+
+        let i = node.keys.partition_point(|k| *k <= key);
+        let child = node.children[i];
+        let (old, split) = self.insert_at(child, key, value);
+
+        let Some((separator, right)) = split else {
+            return (old, None);
+        };
+
+        let node = self.node_mut(id);
+        node.keys.insert(i, separator);
+        node.children.insert(i + 1, right);
+
+        if node.keys.len() <= Self::MAX {
+            return (old, None);
+        }
+
+        // the middle separator moves up instead of being copied, B keys stay, B - 1 go right
+        let keys = node.keys.split_off(B + 1);
+        let children = node.children.split_off(B + 1);
+        let separator = node.keys.pop().unwrap();
+
+        let right = self.alloc(Node::inner(keys, children));
+        (old, Some((separator, right)))
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = self.remove_at(self.root, key)?;
+        self.len -= 1;
+
+        // a root left with a single child hands over to it, the only way down
+        let root = self.node(self.root);
+        if root.keys.is_empty() && !root.is_leaf() {
+            let child = root.children[0];
+            self.release(self.root);
+            self.root = child;
+        }
+
+        Some(removed)
+    }
+
+    fn remove_at<Q>(&mut self, id: u32, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = self.node_mut(id);
+
+        if node.is_leaf() {
+            let i = node.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+            return Some((node.keys.remove(i), node.values.remove(i)));
+        }
+
+        let i = node.keys.partition_point(|k| k.borrow() <= key);
+        let child = node.children[i];
+        let removed = self.remove_at(child, key)?;
+
+        if self.node(child).keys.len() < Self::MIN {
+            self.fix_child(id, i);
+        }
+
+        Some(removed)
+    }
+
+    // Child i of `parent` is one key short: a sibling with keys to spare gives one up,
+    // otherwise the child and a sibling merge
+    fn fix_child(&mut self, parent: u32, i: usize) {
+        let children = &self.node(parent).children;
+        let child = children[i];
+        let left = i.checked_sub(1).map(|left| children[left]);
+        let right = children.get(i + 1).copied();
+
+        let spare = |id: &u32| self.node(*id).keys.len() > Self::MIN;
+        match (left.filter(spare), right.filter(spare)) {
+            (Some(left), _) => self.borrow_from_left(parent, i, left, child),
+            (None, Some(right)) => self.borrow_from_right(parent, i, child, right),
+            (None, None) => match left {
+                Some(left) => self.merge(parent, i - 1, left, child),
+                None => self.merge(parent, i, child, right.unwrap()),
+            },
+        }
+    }
+
+    fn borrow_from_left(&mut self, parent: u32, i: usize, left: u32, child: u32) {
+        let (from, to) = self.pair_mut(left, child);
+
+        // a leaf takes the entry itself and its new first key becomes the separator, an
+        // inner node takes the separator and the left node's last key replaces it
+        let separator = if to.is_leaf() {
+            to.keys.insert(0, from.keys.pop().unwrap());
+            to.values.insert(0, from.values.pop().unwrap());
+            to.keys[0].clone()
+        } else {
+            to.children.insert(0, from.children.pop().unwrap());
+            from.keys.pop().unwrap()
+        };
+
+        let old = mem::replace(&mut self.node_mut(parent).keys[i - 1], separator);
+        let to = self.node_mut(child);
+        if !to.is_leaf() {
+            to.keys.insert(0, old);
+        }
+    }
+
+    fn borrow_from_right(&mut self, parent: u32, i: usize, child: u32, right: u32) {
+        let (to, from) = self.pair_mut(child, right);
+
+        let separator = if to.is_leaf() {
+            to.keys.push(from.keys.remove(0));
+            to.values.push(from.values.remove(0));
+            from.keys[0].clone()
+        } else {
+            to.children.push(from.children.remove(0));
+            from.keys.remove(0)
+        };
+
+        let old = mem::replace(&mut self.node_mut(parent).keys[i], separator);
+        let to = self.node_mut(child);
+        if !to.is_leaf() {
+            to.keys.push(old);
+        }
+    }
+
+    // Right child i + 1 goes into left child i. Leaves drop the separator between them,
+    // inner nodes pull it down. Always the right one is freed, so the first leaf stays put
+    fn merge(&mut self, parent: u32, i: usize, left: u32, right: u32) {
+        let parent = self.node_mut(parent);
+        let separator = parent.keys.remove(i);
+        parent.children.remove(i + 1);
+
+        let right = self.release(right);
+        let left = self.node_mut(left);
+
+        if left.is_leaf() {
+            left.next = right.next;
+        } else {
+            left.keys.push(separator);
+        }
+
+        left.keys.extend(right.keys);
+        left.values.extend(right.values);
+        left.children.extend(right.children);
+    }
+
+    // Entries with keys inside `range` in order: one walk down to the first leaf, then
+    // along the leaf chain
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(low) => {
+                let leaf = self.find_leaf(low);
+                (leaf, self.node(leaf).keys.partition_point(|k| k.borrow() < low))
+            }
+            Bound::Excluded(low) => {
+                let leaf = self.find_leaf(low);
+                (leaf, self.node(leaf).keys.partition_point(|k| k.borrow() <= low))
+            }
+            Bound::Unbounded => (self.first, 0),
+        };
+
+        // where it stops, the first key past the end
+        let end = match range.end_bound() {
+            Bound::Included(high) => {
+                let leaf = self.find_leaf(high);
+                (leaf, self.node(leaf).keys.partition_point(|k| k.borrow() <= high))
+            }
+            Bound::Excluded(high) => {
+                let leaf = self.find_leaf(high);
+                (leaf, self.node(leaf).keys.partition_point(|k| k.borrow() < high))
+            }
+            Bound::Unbounded => (NIL, 0),
+        };
+
+        let mut iter = Iter { nodes: &self.nodes, leaf: start.0, index: start.1, end };
+        iter.end = iter.skip_leaf_ends(end);
+
+        // the first key already past the end, a range like 5..3
+        let (leaf, index) = iter.skip_leaf_ends(start);
+        let past_end = leaf != NIL
+            && match range.end_bound() {
+                Bound::Included(high) => self.node(leaf).keys[index].borrow() > high,
+                Bound::Excluded(high) => self.node(leaf).keys[index].borrow() >= high,
+                Bound::Unbounded => false,
+            };
+
+        if past_end {
+            iter.leaf = NIL;
+        }
+
+        Range { iter }
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> BPlusTree<K, V, B> {
+    // Keys in order and between their separators, every node but the root between B - 1
+    // and 2B - 1 keys, every leaf at the same depth, and the leaf chain visiting the leaves
+    // in exactly the order the tree has them
+    pub fn validate(&self) -> Result<(), InvariantViolation<K>> {
+        let mut leaves = vec![];
+        self.validate_node(self.root, None, None, 0, &mut leaves)?;
+
+        // an empty root leaf is the only leaf without a key to name it by, and it can't be
+        // linked wrong in a way that matters
+        let broken = |leaf: u32| match self.node(leaf).keys.first() {
+            Some(key) => Err(InvariantViolation::LeafChain { leaf: key.clone() }),
+            None => Ok(()),
+        };
+
+        if self.first != leaves[0] {
+            return broken(leaves[0]);
+        }
+
+        for (i, &leaf) in leaves.iter().enumerate() {
+            if self.node(leaf).next != leaves.get(i + 1).copied().unwrap_or(NIL) {
+                return broken(leaf);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Collects the leaves in tree order and returns how deep they are below `id`, the
+    // children of a node all have to come back with the same depth
+    fn validate_node(
+        &self,
+        id: u32,
+        low: Option<&K>,
+        high: Option<&K>,
+        depth: usize,
+        leaves: &mut Vec<u32>,
+    ) -> Result<usize, InvariantViolation<K>> {
+        let node = self.node(id);
+
+        let range = if depth == 0 { 0..=Self::MAX } else { Self::MIN..=Self::MAX };
+        if !range.contains(&node.keys.len()) {
+            return Err(InvariantViolation::Occupancy {
+                node: node.keys.first().cloned(),
+                keys: node.keys.len(),
+            });
+        }
+
+        // the low bound is inclusive here, a separator is a copy of the first key right of it
+        let mut previous: Option<&K> = None;
+        for key in &node.keys {
+            let bound = low
+                .filter(|low| key < *low)
+                .or(high.filter(|high| key >= *high))
+                .or(previous.filter(|previous| key <= *previous));
+
+            if let Some(bound) = bound {
+                return Err(InvariantViolation::Order {
+                    node: key.clone(),
+                    ancestor: bound.clone(),
+                });
+            }
+
+            previous = Some(key);
+        }
+
+        if node.is_leaf() {
+            leaves.push(id);
+            return Ok(depth);
+        }
+
+        let mut leaf_depth = None;
+        for (i, &child) in node.children.iter().enumerate() {
+            let low = if i == 0 { low } else { Some(&node.keys[i - 1]) };
+            let high = node.keys.get(i).or(high);
+            let depth = self.validate_node(child, low, high, depth + 1, leaves)?;
+
+            match leaf_depth {
+                Some(expected) if expected != depth => {
+                    return Err(InvariantViolation::LeafDepth {
+                        leaf: self.node(*leaves.last().unwrap()).keys[0].clone(),
+                        depth,
+                        expected,
+                    });
+                }
+                _ => leaf_depth = Some(depth),
+            }
+        }
+
+        Ok(leaf_depth.unwrap())
+    }
+}
+
+impl<K, V, const B: usize> Default for BPlusTree<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> FromIterator<(K, V)> for BPlusTree<K, V, B> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> Extend<(K, V)> for BPlusTree<K, V, B> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, const B: usize> IntoIterator for &'a BPlusTree<K, V, B> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    leaf: u32,
+    index: usize,
+    end: (u32, usize), // the position it stops at, (NIL, 0) to run to the last leaf
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    // The position past a leaf's last key is the same as the start of the next one, this
+    // always gives the latter so two positions can be compared
+    fn skip_leaf_ends(&self, (mut leaf, mut index): (u32, usize)) -> (u32, usize) {
+        while leaf != NIL && index == self.nodes[leaf as usize].keys.len() {
+            leaf = self.nodes[leaf as usize].next;
+            index = 0;
+        }
+
+        (leaf, index)
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.leaf, self.index) = self.skip_leaf_ends((self.leaf, self.index));
+
+        if self.leaf == NIL || (self.leaf, self.index) == self.end {
+            return None;
+        }
+
+        let leaf = &self.nodes[self.leaf as usize];
+        self.index += 1;
+        Some((&leaf.keys[self.index - 1], &leaf.values[self.index - 1]))
+    }
+}
+
+pub struct Range<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+// One leaf's entries, the leaves in chain order
+type LeafMut<'a, K, V> = iter::Zip<slice::Iter<'a, K>, slice::IterMut<'a, V>>;
+type LeavesMut<'a, K, V> = iter::Flatten<vec::IntoIter<Option<LeafMut<'a, K, V>>>>;
+
+pub struct IterMut<'a, K, V> {
+    inner: iter::Flatten<LeavesMut<'a, K, V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_set, ModelSet};
+
+    fn keys<V, const B: usize>(tree: &BPlusTree<i32, V, B>) -> Vec<i32> {
+        tree.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn test_insert_get_and_remove() {
+        let mut tree: BPlusTree<i32, &str, 2> = BPlusTree::new();
+        assert!(tree.is_empty());
+
+        for (key, value) in [(5, "five"), (3, "three"), (8, "eight"), (1, "one"), (4, "four")] {
+            assert_eq!(tree.insert(key, value), None);
+        }
+        assert_eq!(tree.insert(4, "FOUR"), Some("four"));
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.validate(), Ok(()));
+
+        assert_eq!(tree.get(&4), Some(&"FOUR"));
+        assert_eq!(tree.get(&6), None);
+        assert!(tree.contains_key(&8));
+        *tree.get_mut(&1).unwrap() = "ONE";
+
+        assert_eq!(tree.first_key_value(), Some((&1, &"ONE")));
+        assert_eq!(tree.last_key_value(), Some((&8, &"eight")));
+
+        assert_eq!(tree.remove(&5), Some("five"));
+        assert_eq!(tree.remove(&5), None);
+        assert_eq!(tree.remove_entry(&3), Some((3, "three")));
+        assert_eq!(keys(&tree), vec![1, 4, 8]);
+        assert_eq!(tree.validate(), Ok(()));
+
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.first_key_value(), None);
+
+        // keys don't need Display to be validated
+        let mut tree: BPlusTree<(u8, u8), (), 2> = BPlusTree::new();
+        for i in 0..20 {
+            tree.insert((i % 3, i), ());
+        }
+        assert!(tree.validate().is_ok());
+    }
+
+    #[test]
+    fn test_splits_and_merges_keep_the_leaves_linked() {
+        let mut tree: BPlusTree<i32, (), 2> = BPlusTree::new();
+
+        for key in (0..1000).rev() {
+            tree.insert(key, ());
+        }
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(keys(&tree), (0..1000).collect::<Vec<_>>());
+
+        for key in (0..1000).step_by(2) {
+            assert_eq!(tree.remove(&key), Some(()));
+        }
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(keys(&tree), (1..1000).step_by(2).collect::<Vec<_>>());
+
+        for key in (1..1000).step_by(2) {
+            assert_eq!(tree.remove(&key), Some(()));
+            if key % 50 == 1 {
+                assert_eq!(tree.validate(), Ok(()));
+            }
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 1);
+
+        // the freed nodes get used again
+        let slots = tree.nodes.len();
+        tree.extend((0..100).map(|key| (key, ())));
+        assert_eq!(tree.nodes.len(), slots);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_range_and_iter_mut() {
+        let mut tree: BPlusTree<i32, i32, 2> = (0..40).map(|key| (key * 2, key)).collect();
+
+        let range = |tree: &BPlusTree<i32, i32, 2>, low: i32, high: i32| {
+            tree.range(low..high).map(|(key, _)| *key).collect::<Vec<_>>()
+        };
+        assert_eq!(range(&tree, 5, 11), vec![6, 8, 10]);
+        assert_eq!(range(&tree, 7, 8), Vec::<i32>::new());
+        assert_eq!(range(&tree, 75, 100), vec![76, 78]);
+        assert_eq!(range(&tree, 10, 5), Vec::<i32>::new());
+        assert_eq!(tree.range(..=4).count(), 3);
+        assert_eq!(tree.range((Bound::Excluded(70), Bound::Unbounded)).count(), 4);
+        assert_eq!(tree.range::<i32, _>(..).count(), 40);
+
+        for (key, value) in tree.iter_mut() {
+            *value = -key;
+        }
+        assert!(tree.iter().all(|(key, value)| *value == -key));
+        assert_eq!(tree.iter_mut().count(), 40);
+    }
+
+    #[test]
+    fn test_validate_reports_broken_chain() {
+        let mut tree: BPlusTree<i32, (), 2> = (0..10).map(|key| (key, ())).collect();
+
+        // the first leaf skips the second one
+        let second = tree.node(tree.first).next;
+        let third = tree.node(second).next;
+        let first = tree.first;
+        tree.node_mut(first).next = third;
+
+        assert_eq!(tree.validate(), Err(InvariantViolation::LeafChain { leaf: 0 }));
+    }
+
+    impl ModelSet for BPlusTree<i32, (), 2> {
+        fn insert(&mut self, value: i32) -> bool {
+            BPlusTree::insert(self, value, ()).is_none()
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            BPlusTree::remove(self, &value).is_some()
+        }
+
+        fn values(&self) -> Vec<i32> {
+            keys(self)
+        }
+
+        fn len(&self) -> usize {
+            BPlusTree::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            BPlusTree::validate(self)
+        }
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<BPlusTree<i32, (), 2>>("b+ tree matches BTreeSet");
+    }
+}
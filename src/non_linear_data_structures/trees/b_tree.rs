@@ -0,0 +1,773 @@
+// B-tree of minimum degree B: every node but the root holds between B - 1 and 2B - 1 keys,
+// an inner node with k keys has k + 1 children, and all leaves sit at the same depth. The
+// keys of a node lie next to each other in one Vec and the tree is only log base B of n
+// deep, which is what makes it kinder to the cache than the binary trees.
+//
+// Both insert and delete go down in one pass (as in CLRS): a full child is split before
+// the insert steps into it, and a delete tops up every child it steps into to more than
+// the minimum first, so neither ever has to come back up to fix a parent.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    mem,
+    ops::{Bound, RangeBounds},
+    ptr, slice,
+};
+
+use super::invariant::{check_order, InvariantViolation};
+
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,            // one per key
+    children: Vec<Node<K, V>>, // one more than keys, empty in a leaf
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Self {
+        Node { keys: vec![], values: vec![], children: vec![] }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    // Ok with the key's index, or Err with the child the key would be under
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.keys.binary_search_by(|k| k.borrow().cmp(key))
+    }
+}
+
+// A map. With V = () it is the set the OrderedSet impl uses
+pub struct BTree<K, V, const B: usize = 6> {
+    root: Node<K, V>,
+    len: usize,
+}
+
+impl<K, V, const B: usize> BTree<K, V, B> {
+    const MAX: usize = 2 * B - 1;
+    const MIN: usize = B - 1;
+
+    pub fn new() -> Self {
+        const { assert!(B >= 2, "a B-tree needs a minimum degree of at least 2") };
+
+        BTree { root: Node::new(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // Levels from the root down to the leaves, 1 for a tree that is a single leaf
+    pub fn height(&self) -> usize {
+        let mut node = &self.root;
+        let mut height = 1;
+
+        while let Some(child) = node.children.first() {
+            node = child;
+            height += 1;
+        }
+
+        height
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &self.root;
+
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(&node.values[i]),
+                Err(i) => node = node.children.get(i)?,
+            }
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &mut self.root;
+
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(&mut node.values[i]),
+                Err(i) => node = node.children.get_mut(i)?,
+            }
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // Smallest key, at the start of the leftmost leaf
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+
+        node.keys.first().zip(node.values.first())
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+
+        node.keys.last().zip(node.values.last())
+    }
+
+    // In key order, smallest first
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+
+    // The keys stay out of reach, so changing values can't break the order
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut iter = IterMut { stack: vec![] };
+        iter.push_left_spine(&mut self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V, const B: usize> BTree<K, V, B> {
+    // Hands back the old value if the key was there, the key itself stays the old one
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_entry(key, value).1
+    }
+
+    // Only for the maps, the new value's slot without a second search for it
+    pub(super) fn insert_new(&mut self, key: K, value: V) -> &mut V {
+        self.insert_entry(key, value).0
+    }
+
+    fn insert_entry(&mut self, key: K, value: V) -> (&mut V, Option<V>) {
+        // a full root splits into two halves under a new root, the only way the tree grows
+        if self.root.keys.len() == Self::MAX {
+            let old = mem::replace(&mut self.root, Node::new());
+            self.root.children.push(old);
+            Self::split_child(&mut self.root, 0);
+        }
+
+        let mut node = &mut self.root;
+
+        loop {
+            let i = match node.search(&key) {
+                Ok(i) => {
+                    let old = mem::replace(&mut node.values[i], value);
+                    return (&mut node.values[i], Some(old));
+                }
+                Err(i) if node.is_leaf() => {
+                    node.keys.insert(i, key);
+                    node.values.insert(i, value);
+                    self.len += 1;
+                    return (&mut node.values[i], None);
+                }
+                Err(i) => i,
+            };
+
+            // the child might have to take a key from a split below it, so it can't be full
+            if node.children[i].keys.len() < Self::MAX {
+                node = &mut node.children[i];
+                continue;
+            }
+
+            Self::split_child(node, i);
+
+            // the middle key of the child moved up to i, the key goes left, right or is it
+            node = match key.cmp(&node.keys[i]) {
+                Ordering::Less => &mut node.children[i],
+                Ordering::Greater => &mut node.children[i + 1],
+                Ordering::Equal => {
+                    let old = mem::replace(&mut node.values[i], value);
+                    return (&mut node.values[i], Some(old));
+                }
+            };
+        }
+    }
+
+    // Splits the full child i around its middle key, which moves up into the parent
+    fn split_child(parent: &mut Node<K, V>, i: usize) {
+        let child = &mut parent.children[i];
+
+        let keys = child.keys.split_off(B);
+        let values = child.values.split_off(B);
+        let children = if child.is_leaf() { vec![] } else { child.children.split_off(B) };
+        let middle = (child.keys.pop().unwrap(), child.values.pop().unwrap());
+
+        parent.keys.insert(i, middle.0);
+        parent.values.insert(i, middle.1);
+        parent.children.insert(i + 1, Node { keys, values, children });
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = Self::remove_from(&mut self.root, key);
+
+        // a merge can empty the root, its only child takes over, the only way the tree shrinks
+        if self.root.keys.is_empty() {
+            if let Some(child) = self.root.children.pop() {
+                self.root = child;
+            }
+        }
+
+        if removed.is_some() {
+            self.len -= 1;
+        }
+
+        removed
+    }
+
+    // `node` has more than the minimum, or is the root, so it can give up a key
+    fn remove_from<Q>(node: &mut Node<K, V>, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match node.search(key) {
+            Ok(i) if node.is_leaf() => Some((node.keys.remove(i), node.values.remove(i))),
+            Ok(i) => {
+                // an inner key is swapped for its predecessor or successor from a child that
+                // can spare one, and if neither can the two children merge around it
+                let replacement = if node.children[i].keys.len() > Self::MIN {
+                    Self::remove_max(&mut node.children[i])
+                } else if node.children[i + 1].keys.len() > Self::MIN {
+                    Self::remove_min(&mut node.children[i + 1])
+                } else {
+                    Self::merge_children(node, i);
+                    return Self::remove_from(&mut node.children[i], key);
+                };
+
+                Some((
+                    mem::replace(&mut node.keys[i], replacement.0),
+                    mem::replace(&mut node.values[i], replacement.1),
+                ))
+            }
+            Err(_) if node.is_leaf() => None,
+            Err(i) => {
+                let i = Self::fill_child(node, i);
+                Self::remove_from(&mut node.children[i], key)
+            }
+        }
+    }
+
+    fn remove_max(node: &mut Node<K, V>) -> (K, V) {
+        if node.is_leaf() {
+            return (node.keys.pop().unwrap(), node.values.pop().unwrap());
+        }
+
+        let i = Self::fill_child(node, node.children.len() - 1);
+        Self::remove_max(&mut node.children[i])
+    }
+
+    fn remove_min(node: &mut Node<K, V>) -> (K, V) {
+        if node.is_leaf() {
+            return (node.keys.remove(0), node.values.remove(0));
+        }
+
+        let i = Self::fill_child(node, 0);
+        Self::remove_min(&mut node.children[i])
+    }
+
+    // Makes sure child i has a key to spare before a delete goes into it: it borrows one
+    // through the parent from a sibling that has enough, or else merges with a sibling.
+    // Returns where the child's keys are now, a merge with the left sibling moves them
+    fn fill_child(node: &mut Node<K, V>, i: usize) -> usize {
+        if node.children[i].keys.len() > Self::MIN {
+            return i;
+        }
+
+        if i > 0 && node.children[i - 1].keys.len() > Self::MIN {
+            let (left, right) = node.children.split_at_mut(i);
+            let (left, child) = (&mut left[i - 1], &mut right[0]);
+
+            let key = mem::replace(&mut node.keys[i - 1], left.keys.pop().unwrap());
+            let value = mem::replace(&mut node.values[i - 1], left.values.pop().unwrap());
+            child.keys.insert(0, key);
+            child.values.insert(0, value);
+            if let Some(grandchild) = left.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+
+            i
+        } else if i + 1 < node.children.len() && node.children[i + 1].keys.len() > Self::MIN {
+            let (left, right) = node.children.split_at_mut(i + 1);
+            let (child, right) = (&mut left[i], &mut right[0]);
+
+            let key = mem::replace(&mut node.keys[i], right.keys.remove(0));
+            let value = mem::replace(&mut node.values[i], right.values.remove(0));
+            child.keys.push(key);
+            child.values.push(value);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+
+            i
+        } else if i + 1 < node.children.len() {
+            Self::merge_children(node, i);
+            i
+        } else {
+            Self::merge_children(node, i - 1);
+            i - 1
+        }
+    }
+
+    // Child i, key i and child i + 1 become one node, two minimal children make a full one
+    fn merge_children(node: &mut Node<K, V>, i: usize) {
+        let right = node.children.remove(i + 1);
+        let key = node.keys.remove(i);
+        let value = node.values.remove(i);
+
+        let left = &mut node.children[i];
+        left.keys.push(key);
+        left.values.push(value);
+        left.keys.extend(right.keys);
+        left.values.extend(right.values);
+        left.children.extend(right.children);
+    }
+
+    // Entries with keys inside `range` in order, `tree.range(3..7)` works like on BTreeMap
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut iter = Iter { stack: vec![] };
+
+        // the way down to the first key inside, every node resumes right after what it skips
+        let mut node = &self.root;
+        loop {
+            let i = node.keys.partition_point(|k| match range.start_bound() {
+                Bound::Included(low) => k.borrow() < low,
+                Bound::Excluded(low) => k.borrow() <= low,
+                Bound::Unbounded => false,
+            });
+
+            let after = node.children.get(i + 1..).unwrap_or(&[]);
+            iter.stack.push((node.keys[i..].iter(), node.values[i..].iter(), after.iter()));
+
+            match node.children.get(i) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        // and the last key inside, where the iterator stops
+        let mut last = None;
+        let mut node = &self.root;
+        loop {
+            let i = node.keys.partition_point(|k| match range.end_bound() {
+                Bound::Included(high) => k.borrow() <= high,
+                Bound::Excluded(high) => k.borrow() < high,
+                Bound::Unbounded => true,
+            });
+
+            if i > 0 {
+                last = Some(&node.keys[i - 1]);
+            }
+
+            match node.children.get(i) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        // an empty range leaves the last key before the first
+        let first = iter.stack.iter().rev().find_map(|(keys, _, _)| keys.as_slice().first());
+        let empty = match (first, last) {
+            (Some(first), Some(last)) => first > last,
+            _ => true,
+        };
+
+        if empty {
+            iter.stack.clear();
+        }
+
+        Range { iter, last }
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> BTree<K, V, B> {
+    // Keys in order inside and across nodes, every node but the root between B - 1 and
+    // 2B - 1 keys, and every leaf at the same depth
+    pub fn validate(&self) -> Result<(), InvariantViolation<K>> {
+        Self::validate_node(&self.root, None, None, 0, &mut None)
+    }
+
+    fn validate_node(
+        node: &Node<K, V>,
+        low: Option<&K>,
+        high: Option<&K>,
+        depth: usize,
+        leaf_depth: &mut Option<usize>,
+    ) -> Result<(), InvariantViolation<K>> {
+        let range = if depth == 0 { 0..=Self::MAX } else { Self::MIN..=Self::MAX };
+        if !range.contains(&node.keys.len()) {
+            return Err(InvariantViolation::Occupancy {
+                node: node.keys.first().cloned(),
+                keys: node.keys.len(),
+            });
+        }
+
+        let mut previous = low;
+        for key in &node.keys {
+            check_order(key, previous, high)?;
+            previous = Some(key);
+        }
+
+        if node.is_leaf() {
+            return match *leaf_depth {
+                Some(expected) if expected != depth => Err(InvariantViolation::LeafDepth {
+                    leaf: node.keys[0].clone(),
+                    depth,
+                    expected,
+                }),
+                _ => {
+                    *leaf_depth = Some(depth);
+                    Ok(())
+                }
+            };
+        }
+
+        for (i, child) in node.children.iter().enumerate() {
+            let low = if i == 0 { low } else { Some(&node.keys[i - 1]) };
+            let high = node.keys.get(i).or(high);
+            Self::validate_node(child, low, high, depth + 1, leaf_depth)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, V, const B: usize> Default for BTree<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, const B: usize> FromIterator<(K, V)> for BTree<K, V, B> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord, V, const B: usize> Extend<(K, V)> for BTree<K, V, B> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, const B: usize> IntoIterator for &'a BTree<K, V, B> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// What is left of one node: its keys and values from the next one on, and the children
+// after the one being visited. A node hands out a key and then steps into the child after it
+type Frame<'a, K, V> = (slice::Iter<'a, K>, slice::Iter<'a, V>, slice::Iter<'a, Node<K, V>>);
+
+pub struct Iter<'a, K, V> {
+    stack: Vec<Frame<'a, K, V>>, // the deepest node on top
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut node: &'a Node<K, V>) {
+        loop {
+            let mut children = node.children.iter();
+            let first = children.next();
+            self.stack.push((node.keys.iter(), node.values.iter(), children));
+
+            match first {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (keys, values, children) = self.stack.last_mut()?;
+
+            match keys.next().zip(values.next()) {
+                Some(entry) => {
+                    if let Some(child) = children.next() {
+                        self.push_left_spine(child);
+                    }
+                    return Some(entry);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+pub struct Range<'a, K, V> {
+    iter: Iter<'a, K, V>,
+    last: Option<&'a K>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+
+        // the last key inside ends it, whatever is still on the stack is past the range
+        if self.last.is_some_and(|last| ptr::eq(last, key)) {
+            self.iter.stack.clear();
+        }
+
+        Some((key, value))
+    }
+}
+
+type FrameMut<'a, K, V> = (slice::Iter<'a, K>, slice::IterMut<'a, V>, slice::IterMut<'a, Node<K, V>>);
+
+pub struct IterMut<'a, K, V> {
+    stack: Vec<FrameMut<'a, K, V>>,
+}
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    fn push_left_spine(&mut self, mut node: &'a mut Node<K, V>) {
+        loop {
+            let Node { keys, values, children } = node;
+            let keys: &'a Vec<K> = keys;
+            let mut children = children.iter_mut();
+            let first = children.next();
+            self.stack.push((keys.iter(), values.iter_mut(), children));
+
+            match first {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (keys, values, children) = self.stack.last_mut()?;
+
+            match keys.next().zip(values.next()) {
+                Some(entry) => {
+                    if let Some(child) = children.next() {
+                        self.push_left_spine(child);
+                    }
+                    return Some(entry);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_set, ModelSet};
+
+    fn keys<V, const B: usize>(tree: &BTree<i32, V, B>) -> Vec<i32> {
+        tree.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn test_insert_get_and_remove() {
+        let mut tree: BTree<i32, &str, 2> = BTree::new();
+        assert!(tree.is_empty());
+
+        for (key, value) in [(5, "five"), (3, "three"), (8, "eight"), (1, "one"), (4, "four")] {
+            assert_eq!(tree.insert(key, value), None);
+        }
+        assert_eq!(tree.insert(4, "FOUR"), Some("four"));
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.validate(), Ok(()));
+
+        assert_eq!(tree.get(&4), Some(&"FOUR"));
+        assert_eq!(tree.get(&6), None);
+        assert!(tree.contains_key(&8));
+        *tree.get_mut(&1).unwrap() = "ONE";
+
+        assert_eq!(tree.first_key_value(), Some((&1, &"ONE")));
+        assert_eq!(tree.last_key_value(), Some((&8, &"eight")));
+
+        assert_eq!(tree.remove(&5), Some("five"));
+        assert_eq!(tree.remove(&5), None);
+        assert_eq!(tree.remove_entry(&3), Some((3, "three")));
+        assert_eq!(keys(&tree), vec![1, 4, 8]);
+        assert_eq!(tree.validate(), Ok(()));
+
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.first_key_value(), None);
+
+        // keys don't need Display to be validated
+        let mut tree: BTree<(u8, u8), (), 2> = BTree::new();
+        for i in 0..20 {
+            tree.insert((i % 3, i), ());
+        }
+        assert!(tree.validate().is_ok());
+    }
+
+    #[test]
+    fn test_splits_and_merges_keep_every_leaf_level() {
+        let mut tree: BTree<i32, (), 2> = BTree::new();
+
+        for key in 0..1000 {
+            tree.insert(key, ());
+        }
+        assert_eq!(tree.validate(), Ok(()));
+        // at least two keys per node below the root when built in order
+        assert!(tree.height() <= 10, "height {}", tree.height());
+
+        // every other key first, that leaves lots of minimal nodes to merge later
+        for key in (0..1000).step_by(2) {
+            assert_eq!(tree.remove(&key), Some(()));
+        }
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.len(), 500);
+
+        for key in (1..1000).step_by(2).rev() {
+            assert_eq!(tree.remove(&key), Some(()));
+            if key % 50 == 1 {
+                assert_eq!(tree.validate(), Ok(()));
+            }
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 1);
+    }
+
+    #[test]
+    fn test_wider_nodes_are_flatter() {
+        let narrow: BTree<u32, (), 2> = (0..10_000).map(|key| (key, ())).collect();
+        let wide: BTree<u32, (), 32> = (0..10_000).map(|key| (key, ())).collect();
+
+        assert!(wide.height() < narrow.height());
+        assert!(wide.height() <= 4, "height {}", wide.height());
+        assert_eq!(wide.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_range_and_iter_mut() {
+        let mut tree: BTree<i32, i32, 2> = (0..40).map(|key| (key * 2, key)).collect();
+
+        let range = |tree: &BTree<i32, i32, 2>, low: i32, high: i32| {
+            tree.range(low..high).map(|(key, _)| *key).collect::<Vec<_>>()
+        };
+        assert_eq!(range(&tree, 5, 11), vec![6, 8, 10]);
+        assert_eq!(range(&tree, 7, 8), Vec::<i32>::new());
+        assert_eq!(range(&tree, 75, 100), vec![76, 78]);
+        assert_eq!(range(&tree, 10, 5), Vec::<i32>::new());
+        assert_eq!(tree.range(..=4).count(), 3);
+        assert_eq!(tree.range((Bound::Excluded(70), Bound::Unbounded)).count(), 4);
+        assert_eq!(tree.range::<i32, _>(..).count(), 40);
+
+        for (key, value) in tree.iter_mut() {
+            *value = -key;
+        }
+        assert!(tree.iter().all(|(key, value)| *value == -key));
+        assert_eq!((&tree).into_iter().count(), 40);
+    }
+
+    #[test]
+    fn test_validate_reports_underfull_node() {
+        let mut tree: BTree<i32, (), 2> = (0..10).map(|key| (key, ())).collect();
+
+        // a leaf below the root has to keep at least one key
+        let mut leaf = &mut tree.root.children[0];
+        while !leaf.is_leaf() {
+            leaf = &mut leaf.children[0];
+        }
+        leaf.keys.clear();
+        leaf.values.clear();
+
+        assert_eq!(
+            tree.validate(),
+            Err(InvariantViolation::Occupancy { node: None, keys: 0 })
+        );
+    }
+
+    impl ModelSet for BTree<i32, (), 2> {
+        fn insert(&mut self, value: i32) -> bool {
+            BTree::insert(self, value, ()).is_none()
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            BTree::remove(self, &value).is_some()
+        }
+
+        fn values(&self) -> Vec<i32> {
+            keys(self)
+        }
+
+        fn len(&self) -> usize {
+            BTree::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            BTree::validate(self)
+        }
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<BTree<i32, (), 2>>("b-tree matches BTreeSet");
+    }
+}
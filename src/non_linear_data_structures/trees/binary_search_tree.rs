@@ -1,121 +1,643 @@
 use core::fmt;
-use std::cmp::Ordering;
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::RangeBounds,
+    vec,
+};
 
+use super::{
+    bulk,
+    descent::{self, PlainNode, SearchNode},
+    invariant::{check_order, InvariantViolation},
+};
+
+// The splay tree and the treap are built from the same nodes, hence the fields open to the
+// module. Only the treap has a priority, everywhere else it is ()
 #[derive(Clone)]
-pub struct Node<T> {
-    value: T,
-    left: Option<Box<Node<T>>>,
-    right: Option<Box<Node<T>>>,
+pub struct Node<T, P = ()> {
+    pub(super) value: T,
+    pub(super) left: Option<Box<Node<T, P>>>,
+    pub(super) right: Option<Box<Node<T, P>>>,
+    pub(super) size: usize, // nodes in this subtree, for rank and select
+    pub(super) priority: P,
 }
 
 impl<T> Node<T> {
     pub fn new(value: T) -> Self {
-        Node { value, left: None, right: None }
+        Self::with_priority(value, ())
+    }
+}
+
+impl<T, P> Node<T, P> {
+    pub(super) fn with_priority(value: T, priority: P) -> Self {
+        Node { value, left: None, right: None, size: 1, priority }
+    }
+}
+
+impl<T, P> SearchNode for Node<T, P> {
+    type Value = T;
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn compare<Q>(&self, value: &Q) -> Ordering
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        value.cmp(self.value.borrow())
+    }
+}
+
+impl<T, P> PlainNode for Node<T, P> {
+    fn value(&self) -> &T {
+        &self.value
     }
 }
 
 pub struct BinarySearchTree<T> {
     root: Option<Box<Node<T>>>,
+    len: usize,
+    alpha: Option<f64>, // set in scapegoat mode, a plain tree never rebalances on its own
+    max_len: usize,     // most values since the last full rebuild, for scapegoat deletes
 }
 
-impl<T: Ord + Clone + fmt::Display> BinarySearchTree<T> {
+impl<T> BinarySearchTree<T> {
     pub fn new() -> Self {
-        BinarySearchTree { root: None }
+        BinarySearchTree { root: None, len: 0, alpha: None, max_len: 0 }
+    }
+
+    // Scapegoat mode: an insert that lands deeper than log base 1/alpha of len rebuilds the
+    // lowest subtree on its way where one side holds more than alpha of the nodes, and a
+    // delete that takes len below alpha times the most it has been rebuilds everything.
+    // Closer to 0.5 keeps the tree flatter at the price of more rebuilds
+    pub fn scapegoat(alpha: f64) -> Self {
+        assert!(alpha > 0.5 && alpha < 1.0, "alpha has to be between 0.5 and 1, got {}", alpha);
+
+        BinarySearchTree { alpha: Some(alpha), ..Self::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref();
+
+        while let Some(node) = current {
+            match value.cmp(node.value.borrow()) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Greater => current = node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+
+        None
+    }
+
+    // Only for the maps, which never touch the key part of a value through it. Anything else
+    // could move a value out of its place in the order
+    pub(super) fn get_mut<Q>(&mut self, value: &Q) -> Option<&mut T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref_mut();
+
+        while let Some(node) = current {
+            match value.cmp(node.value.borrow()) {
+                Ordering::Less => current = node.left.as_deref_mut(),
+                Ordering::Greater => current = node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            }
+        }
+
+        None
+    }
+
+    // Smallest value, the end of the left spine
+    pub fn first(&self) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+
+        Some(&node.value)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+
+        Some(&node.value)
+    }
+
+    // In order, smallest first
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.root.as_deref())
+    }
+
+    // Values inside `range` in order, `tree.range(3..7)` works like it does on BTreeSet
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new(self.root.as_deref(), range)
+    }
+
+    // Largest value that is <= `value`. Like the rest of the lookups these only walk one
+    // path, so they are O(h), which the unbalanced tree doesn't keep anywhere near log n
+    pub fn floor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::floor(self.root.as_deref(), value).map(|node| &node.value)
+    }
+
+    // Smallest value that is >= `value`
+    pub fn ceiling<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::ceiling(self.root.as_deref(), value).map(|node| &node.value)
+    }
+
+    // How many values are smaller than `value`, whether or not it is in the tree
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::rank(self.root.as_deref(), value)
+    }
+
+    // The k-th smallest value counting from 0, so select(rank(x)) is x when x is in the tree
+    pub fn select(&self, k: usize) -> Option<&T> {
+        descent::select(self.root.as_deref(), k).map(|node| &node.value)
+    }
+
+    pub(super) fn size_of<P>(node: Option<&Node<T, P>>) -> usize {
+        node.map_or(0, |n| n.size)
     }
 
-    pub fn insert(&mut self, value: T) {
-        Self::insert_node(&mut self.root, value);
+    // Day-Stout-Warren: straightens the tree into a vine hanging off to the right, then folds
+    // the vine back into a tree where only the bottom level is partly filled. O(n) time and
+    // nothing but a cursor on the side
+    pub fn rebalance(&mut self) {
+        Self::rebuild(&mut self.root);
+        self.max_len = self.len;
     }
 
-    fn insert_node(node: &mut Option<Box<Node<T>>>, value: T) {
+    // DSW on the subtree in `slot`, scapegoat mode runs it on single subtrees
+    fn rebuild(slot: &mut Option<Box<Node<T>>>) {
+        let len = Self::size_of(slot.as_deref());
+
+        Self::tree_to_vine(slot);
+
+        // first the nodes that don't fit in a perfect tree go to the bottom level, then
+        // every pass halves the length of the vine
+        let mut perfect = (1 << (len + 1).ilog2()) - 1;
+        Self::compress(slot, len - perfect);
+
+        while perfect > 1 {
+            perfect /= 2;
+            Self::compress(slot, perfect);
+        }
+    }
+
+    // Rotates every left child up until there are only right links left
+    fn tree_to_vine(mut slot: &mut Option<Box<Node<T>>>) {
+        loop {
+            match slot.as_ref().map(|n| n.left.is_some()) {
+                None => break,
+                Some(true) => Self::rotate_right(slot),
+                Some(false) => slot = &mut slot.as_mut().unwrap().right,
+            }
+        }
+    }
+
+    // Left rotates every other node of the vine `count` times, each one takes the node
+    // above it down as its left child
+    fn compress(mut slot: &mut Option<Box<Node<T>>>, count: usize) {
+        for _ in 0..count {
+            Self::rotate_left(slot);
+            slot = &mut slot.as_mut().unwrap().right;
+        }
+    }
+
+    // The rotations keep the sizes right: the node coming up takes over the size of the
+    // whole subtree, the one going down is recounted from its new children
+
+    pub(super) fn rotate_right<P>(slot: &mut Option<Box<Node<T, P>>>) {
+        let mut node = slot.take().unwrap();
+        let mut left = node.left.take().unwrap();
+
+        node.left = left.right.take();
+        left.size = node.size;
+        node.size = 1 + Self::size_of(node.left.as_deref()) + Self::size_of(node.right.as_deref());
+        left.right = Some(node);
+
+        *slot = Some(left);
+    }
+
+    pub(super) fn rotate_left<P>(slot: &mut Option<Box<Node<T, P>>>) {
+        let mut node = slot.take().unwrap();
+        let mut right = node.right.take().unwrap();
+
+        node.right = right.left.take();
+        right.size = node.size;
+        node.size = 1 + Self::size_of(node.left.as_deref()) + Self::size_of(node.right.as_deref());
+        right.left = Some(node);
+
+        *slot = Some(right);
+    }
+
+    fn select_mut(&mut self, mut k: usize) -> Option<&mut T> {
+        let mut current = self.root.as_deref_mut();
+
+        while let Some(node) = current {
+            let left = Self::size_of(node.left.as_deref());
+
+            match k.cmp(&left) {
+                Ordering::Less => current = node.left.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    current = node.right.as_deref_mut();
+                }
+            }
+        }
+
+        None
+    }
+
+    pub(super) fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let mut iter = IterMut { stack: vec![] };
+        iter.push_left_spine(&mut self.root);
+        iter
+    }
+}
+
+impl<T: Ord> BinarySearchTree<T> {
+    // Builds a balanced tree in O(n) instead of the list that inserting sorted values one
+    // by one makes. Equal neighbours are kept once, panics if the values aren't ascending
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted(bulk::expect_sorted(iter))
+    }
+
+    // `values` has to be ascending without duplicates already
+    fn from_sorted(values: Vec<T>) -> Self {
+        let len = values.len();
+        let root = Self::build(&mut values.into_iter(), len);
+
+        BinarySearchTree { root, len, alpha: None, max_len: len }
+    }
+
+    // Takes the next `len` values in order, left subtree first, then the root, then the right
+    fn build(values: &mut vec::IntoIter<T>, len: usize) -> Option<Box<Node<T>>> {
+        if len == 0 {
+            return None;
+        }
+
+        let left_len = bulk::left_len(len);
+        let left = Self::build(values, left_len);
+        let value = values.next().expect("build is never asked for more values than there are");
+        let right = Self::build(values, len - 1 - left_len);
+
+        Some(Box::new(Node { value, left, right, size: len, priority: () }))
+    }
+
+    // Returns false when the value was already there
+    pub fn insert(&mut self, value: T) -> bool {
+        // in scapegoat mode, how deep the new node may go before something gets rebuilt
+        let limit = self.alpha.map(|alpha| {
+            let max_depth = ((self.len + 1) as f64).ln() / (1.0 / alpha).ln();
+            (alpha, max_depth)
+        });
+
+        let inserted = Self::insert_node(&mut self.root, value, 0, limit).is_some();
+
+        if inserted {
+            self.len += 1;
+            self.max_len = self.max_len.max(self.len);
+        }
+
+        inserted
+    }
+
+    // None if the value was already there, otherwise whether the new node is still too deep
+    // and needs a scapegoat further up. The first node on the way back up with one side
+    // over alpha of its size gets rebuilt, which brings the new node back up
+    fn insert_node(node: &mut Option<Box<Node<T>>>, value: T, depth: usize, limit: Option<(f64, f64)>) -> Option<bool> {
         match node {
             Some(ref mut n) => {
-                match value.cmp(&n.value) {
-                    Ordering::Less => Self::insert_node(&mut n.left, value),
-                    Ordering::Greater => Self::insert_node(&mut n.right, value),
-                    Ordering::Equal => {} 
+                let too_deep = match value.cmp(&n.value) {
+                    Ordering::Less => Self::insert_node(&mut n.left, value, depth + 1, limit),
+                    Ordering::Greater => Self::insert_node(&mut n.right, value, depth + 1, limit),
+                    Ordering::Equal => None,
+                }?;
+
+                n.size += 1;
+
+                if let (true, Some((alpha, _))) = (too_deep, limit) {
+                    let heavier = Self::size_of(n.left.as_deref()).max(Self::size_of(n.right.as_deref()));
+
+                    if heavier as f64 > alpha * n.size as f64 {
+                        Self::rebuild(node);
+                        return Some(false);
+                    }
                 }
+
+                Some(too_deep)
+            }
+            None => {
+                *node = Some(Box::new(Node::new(value)));
+                Some(limit.is_some_and(|(_, max_depth)| depth as f64 > max_depth))
             }
-            None => *node = Some(Box::new(Node::new(value))),
         }
     }
 
+    // Inserts a value that is known not to be in the tree yet and lends it straight back,
+    // for the maps' entry API where the key has moved into the tree by then
+    pub(super) fn insert_new(&mut self, value: T) -> &mut T {
+        // a rebuild can move it anywhere, but its position in the order stays the same
+        if self.alpha.is_some() {
+            let rank = self.rank(&value);
+            self.insert(value);
+            return self.select_mut(rank).expect("the value was just inserted");
+        }
+
+        self.len += 1;
+        let mut node = &mut self.root;
+
+        while let Some(n) = node {
+            n.size += 1;
+            node = if value < n.value { &mut n.left } else { &mut n.right };
+        }
+
+        &mut node.insert(Box::new(Node::new(value))).value
+    }
+
     pub fn search(&self, value: T) -> bool {
-        Self::search_node(&self.root, &value)
+        self.get(&value).is_some()
     }
 
-    fn search_node(node: &Option<Box<Node<T>>>, value: &T) -> bool {
-        match node {
-            Some(n) => match value.cmp(&n.value) {
-                Ordering::Less => Self::search_node(&n.left, value),
-                Ordering::Greater => Self::search_node(&n.right, value),
-                Ordering::Equal => true,
-            },
-            None => false,
-        }
-    }
-    pub fn delete(&mut self, value: T) {
-        Self::delete_node(&mut self.root, value);
-    }
-
-    fn delete_node(node: &mut Option<Box<Node<T>>>, value: T) {
-        if let Some(ref mut n) = node {
-            match value.cmp(&n.value) {
-                Ordering::Less => Self::delete_node(&mut n.left, value),
-                Ordering::Greater => Self::delete_node(&mut n.right, value),
-                Ordering::Equal => {
-                    *node = match (n.left.take(), n.right.take()) {
-                        (None, None) => None,
-                        (Some(left), None) => Some(left),
-                        (None, Some(right)) => Some(right),
-                        (Some(left), Some(right)) => {
-                            let min = Self::find_min(&right);
-                            let mut new_node = Box::new(Node::new(min));
-                            new_node.left = Some(left);
-                            new_node.right = Self::delete_min(right);
-                            Some(new_node)
-                        }
-                    };
-                }
+    // Returns whether the value was in the tree
+    pub fn delete(&mut self, value: T) -> bool {
+        self.take(&value).is_some()
+    }
+
+    // Removes the value and hands it back
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = Self::take_node(&mut self.root, value);
+
+        if removed.is_some() {
+            self.len -= 1;
+
+            // scapegoat mode lets deletes pile up until a full rebuild pays for itself
+            if self.alpha.is_some_and(|alpha| (self.len as f64) < alpha * self.max_len as f64) {
+                self.rebalance();
             }
         }
+
+        removed
     }
 
-    fn find_min(node: &Box<Node<T>>) -> T
+    fn take_node<Q>(node: &mut Option<Box<Node<T>>>, value: &Q) -> Option<T>
     where
-        T: Clone,
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
     {
-        match &node.left {
-            Some(left) => Self::find_min(left),
-            None => node.value.clone(),
+        let n = node.as_mut()?;
+
+        let child = match value.cmp(n.value.borrow()) {
+            Ordering::Less => &mut n.left,
+            Ordering::Greater => &mut n.right,
+            Ordering::Equal => {
+                let Node { value, left, right, .. } = *node.take()?;
+
+                *node = match (left, right) {
+                    (None, None) => None,
+                    (Some(left), None) => Some(left),
+                    (None, Some(right)) => Some(right),
+                    (Some(left), Some(right)) => {
+                        // the successor node moves up into the gap, nothing gets cloned
+                        let (rest, mut min) = Self::remove_min(right);
+                        min.size = 1 + left.size + rest.as_ref().map_or(0, |rest| rest.size);
+                        min.left = Some(left);
+                        min.right = rest;
+                        Some(min)
+                    }
+                };
+
+                return Some(value);
+            }
+        };
+
+        let removed = Self::take_node(child, value);
+
+        if removed.is_some() {
+            n.size -= 1;
         }
+
+        removed
     }
 
-    fn delete_min(mut node: Box<Node<T>>) -> Option<Box<Node<T>>> {
-        if node.left.is_none() {
-            return node.right;
+    // Detaches the smallest node of the subtree, hands back what is left of it too
+    fn remove_min(mut node: Box<Node<T>>) -> (Option<Box<Node<T>>>, Box<Node<T>>) {
+        match node.left.take() {
+            Some(left) => {
+                let (rest, min) = Self::remove_min(left);
+                node.left = rest;
+                node.size -= 1;
+                (Some(node), min)
+            }
+            None => (node.right.take(), node),
+        }
+    }
+}
+
+impl<T: Ord + Clone + fmt::Display> BinarySearchTree<T> {
+    // Every value has to sit strictly between the bounds its ancestors put on it
+    // and every stored subtree size has to match
+    pub fn validate(&self) -> Result<(), InvariantViolation<T>> {
+        Self::validate_node(&self.root, None, None).map(|_| ())
+    }
+
+    // Hands back the real size so the parent can compare it with its stored one
+    pub(super) fn validate_node<P>(node: &Option<Box<Node<T, P>>>, low: Option<&T>, high: Option<&T>) -> Result<usize, InvariantViolation<T>> {
+        match node {
+            Some(n) => {
+                check_order(&n.value, low, high)?;
+                let size = 1
+                    + Self::validate_node(&n.left, low, Some(&n.value))?
+                    + Self::validate_node(&n.right, Some(&n.value), high)?;
+
+                if n.size != size {
+                    return Err(InvariantViolation::Size {
+                        node: n.value.clone(),
+                        stored: n.size,
+                        actual: size,
+                    });
+                }
+
+                Ok(size)
+            }
+            None => Ok(0),
         }
-        node.left = Self::delete_min(node.left.unwrap());
-        Some(node)
     }
 
     pub fn pretty_print(&self) {
-        Self::print_node(&self.root, 0);
+        print!("{}", Self::render(&self.root));
+    }
+
+    // The tree on its side, one value per line, right subtree above and left below
+    pub(super) fn render<P>(node: &Option<Box<Node<T, P>>>) -> String {
+        let mut out = String::new();
+        Self::render_node(node, 0, &mut out);
+        out
     }
 
-    fn print_node(node: &Option<Box<Node<T>>>, depth: usize) {
+    fn render_node<P>(node: &Option<Box<Node<T, P>>>, depth: usize, out: &mut String) {
         if let Some(n) = node {
-            Self::print_node(&n.right, depth + 1);
-            println!("{}{}", "    ".repeat(depth), n.value);
-            Self::print_node(&n.left, depth + 1);
+            Self::render_node(&n.right, depth + 1, out);
+            out.push_str(&format!("{}{}\n", "    ".repeat(depth), n.value));
+            Self::render_node(&n.left, depth + 1, out);
         }
     }
     
 }
 
+impl<T> Default for BinarySearchTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Sorted input into an empty tree takes the O(n) path, anything else is inserted one by one
+impl<T: Ord> FromIterator<T> for BinarySearchTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for BinarySearchTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        match bulk::into_sorted(iter.into_iter().collect()) {
+            Ok(sorted) if self.is_empty() => {
+                *self = BinarySearchTree { alpha: self.alpha, ..Self::from_sorted(sorted) }
+            }
+            Ok(values) | Err(values) => {
+                for value in values {
+                    self.insert(value);
+                }
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T, P = ()> {
+    stack: Vec<&'a Node<T, P>>, // left spine still to visit, the next value on top
+}
+
+impl<'a, T, P> Iter<'a, T, P> {
+    pub(super) fn new(root: Option<&'a Node<T, P>>) -> Self {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut node: Option<&'a Node<T, P>>) {
+        while let Some(n) = node {
+            self.stack.push(n);
+            node = n.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T, P> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right.as_deref());
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BinarySearchTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub type Range<'a, T> = descent::Range<'a, Node<T>>;
+
+// A node can't sit on the stack while its left child is borrowed, so the node is split
+// into its value and right subtree on the way down
+pub struct IterMut<'a, T> {
+    stack: Vec<(&'a mut T, &'a mut Option<Box<Node<T>>>)>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    fn push_left_spine(&mut self, mut node: &'a mut Option<Box<Node<T>>>) {
+        while let Some(n) = node {
+            let Node { value, left, right, .. } = &mut **n;
+            self.stack.push((value, right));
+            node = left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, right) = self.stack.pop()?;
+        self.push_left_spine(right);
+        Some(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_set, ModelSet};
 
     #[test]
     fn insert_and_search() {
@@ -123,6 +645,7 @@ mod tests {
         bst.insert(10);
         bst.insert(5);
         bst.insert(15);
+        assert_eq!(bst.validate(), Ok(()));
 
         assert!(bst.search(10));
         assert!(bst.search(5));
@@ -135,6 +658,7 @@ mod tests {
         let mut bst = BinarySearchTree::new();
         bst.insert(10);
         bst.insert(10);
+        assert_eq!(bst.validate(), Ok(()));
 
         assert!(bst.search(10));
     }
@@ -150,13 +674,41 @@ mod tests {
 
         assert!(bst.search(25));
         bst.delete(25);
+        assert_eq!(bst.validate(), Ok(()));
         assert!(!bst.search(25));
 
         assert!(bst.search(30));
         bst.delete(30);
+        assert_eq!(bst.validate(), Ok(()));
         assert!(!bst.search(30));
     }
 
+    #[test]
+    fn test_len_take_and_iter() {
+        let mut bst = BinarySearchTree::new();
+        assert!(bst.is_empty());
+
+        for value in [20, 10, 30, 25, 35, 5] {
+            assert!(bst.insert(value));
+        }
+        assert!(!bst.insert(25));
+        assert_eq!(bst.len(), 6);
+        assert_eq!(bst.iter().copied().collect::<Vec<_>>(), vec![5, 10, 20, 25, 30, 35]);
+
+        // two children, 25 moves up into its place
+        assert_eq!(bst.take(&20), Some(20));
+        assert_eq!(bst.take(&20), None);
+        assert!(!bst.delete(20));
+        assert_eq!(bst.len(), 5);
+        assert_eq!(bst.get(&25), Some(&25));
+        assert_eq!(bst.validate(), Ok(()));
+
+        for value in bst.iter_mut() {
+            *value *= 2;
+        }
+        assert_eq!((&bst).into_iter().copied().collect::<Vec<_>>(), vec![10, 20, 50, 60, 70]);
+    }
+
     #[test]
     fn test_pretty_print() {
         let mut bst = BinarySearchTree::new();
@@ -168,7 +720,195 @@ mod tests {
         bst.insert(1);
         bst.insert(14);
         bst.insert(9);
+        assert_eq!(bst.validate(), Ok(()));
 
         bst.pretty_print();
     }
+
+    #[test]
+    fn test_validate_reports_misplaced_node() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(10);
+        bst.insert(5);
+        bst.insert(15);
+
+        // 12 is bigger than the root, it can't live in the left subtree
+        bst.root.as_mut().unwrap().left.as_mut().unwrap().right = Some(Box::new(Node::new(12)));
+
+        assert_eq!(
+            bst.validate(),
+            Err(InvariantViolation::Order { node: 12, ancestor: 10 })
+        );
+    }
+
+    fn height<T>(node: &Option<Box<Node<T>>>) -> i64 {
+        node.as_ref().map_or(-1, |n| 1 + height(&n.left).max(height(&n.right)))
+    }
+
+    impl ModelSet for BinarySearchTree<i32> {
+        fn insert(&mut self, value: i32) -> bool {
+            BinarySearchTree::insert(self, value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            BinarySearchTree::delete(self, value)
+        }
+
+        fn values(&self) -> Vec<i32> {
+            self.iter().copied().collect()
+        }
+
+        fn contains(&self, value: i32) -> bool {
+            self.search(value)
+        }
+
+        fn len(&self) -> usize {
+            BinarySearchTree::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            BinarySearchTree::validate(self)
+        }
+    }
+
+    #[test]
+    fn test_from_sorted_iter_is_balanced() {
+        // inserted one by one this would be a 1000 deep list
+        let tree = BinarySearchTree::from_sorted_iter(0..1000);
+        assert_eq!(tree.len(), 1000);
+        assert_eq!(height(&tree.root), 9);
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), (0..1000).collect::<Vec<_>>());
+        assert_eq!(tree.select(500), Some(&500));
+
+        let tree = BinarySearchTree::from_sorted_iter([1, 1, 2, 3, 3]);
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(BinarySearchTree::<i32>::from_sorted_iter([]).is_empty());
+
+        // unsorted input still works through FromIterator, just one insert at a time
+        let tree: BinarySearchTree<i32> = [5, 1, 4, 1, 3].into_iter().collect();
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![1, 3, 4, 5]);
+
+        let mut tree: BinarySearchTree<i32> = (0..100).collect();
+        assert_eq!(height(&tree.root), 6);
+        tree.extend([150, 120, 200]);
+        assert_eq!(tree.len(), 103);
+        assert!(tree.validate().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "from_sorted_iter needs the values in ascending order")]
+    fn test_from_sorted_iter_rejects_unsorted_input() {
+        BinarySearchTree::from_sorted_iter([2, 1]);
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<BinarySearchTree<i32>>("BinarySearchTree against BTreeSet");
+    }
+
+    #[test]
+    fn test_rebalance_straightens_a_list() {
+        let mut tree = BinarySearchTree::new();
+        for i in 0..1000 {
+            tree.insert(i);
+        }
+        assert_eq!(height(&tree.root), 999);
+
+        tree.rebalance();
+        assert_eq!(height(&tree.root), 9);
+        assert!(tree.validate().is_ok());
+        assert!(tree.iter().copied().eq(0..1000));
+        assert_eq!(tree.select(123), Some(&123));
+
+        // perfect sizes fill every level
+        for len in [0, 1, 2, 3, 7, 15, 16] {
+            let mut tree = BinarySearchTree::new();
+            for i in (0..len).rev() {
+                tree.insert(i);
+            }
+
+            tree.rebalance();
+            assert!(tree.validate().is_ok());
+            assert_eq!(height(&tree.root), if len == 0 { -1 } else { (len as u32).ilog2() as i64 });
+        }
+    }
+
+    #[test]
+    fn test_scapegoat_keeps_sorted_inserts_shallow() {
+        let alpha = 0.7;
+        let mut tree = BinarySearchTree::scapegoat(alpha);
+
+        for i in 0..2000 {
+            assert!(tree.insert(i));
+
+            let max_depth = ((tree.len() as f64).ln() / (1.0 / alpha).ln()).floor() as i64;
+            assert!(height(&tree.root) <= max_depth + 1, "height {} after {} inserts", height(&tree.root), i + 1);
+        }
+
+        assert!(!tree.insert(10));
+        assert!(tree.validate().is_ok());
+
+        // deleting most of it rebuilds the rest once len drops under alpha of the peak
+        for i in 0..1500 {
+            assert_eq!(tree.take(&i), Some(i));
+        }
+        assert!(tree.validate().is_ok());
+        assert!(height(&tree.root) <= 10);
+        assert!(tree.iter().copied().eq(1500..2000));
+    }
+
+    #[test]
+    fn test_scapegoat_insert_new_finds_the_value_after_a_rebuild() {
+        let mut tree = BinarySearchTree::scapegoat(0.6);
+
+        for i in 0..300 {
+            assert_eq!(*tree.insert_new(i), i);
+        }
+
+        assert!(tree.validate().is_ok());
+        assert!(height(&tree.root) < 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "alpha has to be between 0.5 and 1")]
+    fn test_scapegoat_rejects_bad_alpha() {
+        BinarySearchTree::<i32>::scapegoat(1.0);
+    }
+
+    // Same model test with scapegoat rebuilds happening underneath
+    struct Scapegoat(BinarySearchTree<i32>);
+
+    impl Default for Scapegoat {
+        fn default() -> Self {
+            Scapegoat(BinarySearchTree::scapegoat(0.6))
+        }
+    }
+
+    impl ModelSet for Scapegoat {
+        fn insert(&mut self, value: i32) -> bool {
+            self.0.insert(value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            self.0.delete(value)
+        }
+
+        fn values(&self) -> Vec<i32> {
+            self.0.iter().copied().collect()
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            self.0.validate()
+        }
+    }
+
+    #[test]
+    fn test_scapegoat_matches_btree_set() {
+        check_set::<Scapegoat>("scapegoat BinarySearchTree against BTreeSet");
+    }
 }
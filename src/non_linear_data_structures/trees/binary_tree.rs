@@ -1,20 +1,97 @@
-pub struct BinaryTreeNode {
-    value: String,
-    left: Option<Box<BinaryTreeNode>>,
-    right: Option<Box<BinaryTreeNode>>,
-}
+use std::fmt::{Debug, Display};
 
-impl BinaryTreeNode {
-    pub fn new(value: &str) -> Self {
-        let final_value = value.to_owned();
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BinaryTreeNode<T> {
+    value: T,
+    left: Option<Box<BinaryTreeNode<T>>>,
+    right: Option<Box<BinaryTreeNode<T>>>,
+}
 
+impl<T> BinaryTreeNode<T> {
+    pub fn new(value: T) -> Self {
         BinaryTreeNode {
-            value: final_value,
+            value,
             left: None,
             right: None,
         }
     }
 
+    // Builders, so a whole tree can be written as one expression
+    pub fn with_left(mut self, left: impl Into<BinaryTreeNode<T>>) -> Self {
+        self.set_left(left);
+        self
+    }
+
+    pub fn with_right(mut self, right: impl Into<BinaryTreeNode<T>>) -> Self {
+        self.set_right(right);
+        self
+    }
+
+    // The setters and takers hand back the child that was there, subtree and all
+    pub fn set_left(&mut self, left: impl Into<BinaryTreeNode<T>>) -> Option<BinaryTreeNode<T>> {
+        self.left.replace(Box::new(left.into())).map(|node| *node)
+    }
+
+    pub fn set_right(&mut self, right: impl Into<BinaryTreeNode<T>>) -> Option<BinaryTreeNode<T>> {
+        self.right.replace(Box::new(right.into())).map(|node| *node)
+    }
+
+    pub fn take_left(&mut self) -> Option<BinaryTreeNode<T>> {
+        self.left.take().map(|node| *node)
+    }
+
+    pub fn take_right(&mut self) -> Option<BinaryTreeNode<T>> {
+        self.right.take().map(|node| *node)
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    pub fn left(&self) -> Option<&BinaryTreeNode<T>> {
+        self.left.as_deref()
+    }
+
+    pub fn left_mut(&mut self) -> Option<&mut BinaryTreeNode<T>> {
+        self.left.as_deref_mut()
+    }
+
+    pub fn right(&self) -> Option<&BinaryTreeNode<T>> {
+        self.right.as_deref()
+    }
+
+    pub fn right_mut(&mut self) -> Option<&mut BinaryTreeNode<T>> {
+        self.right.as_deref_mut()
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
+}
+
+// A bare value becomes a leaf, so `with_left(4)` works
+impl<T> From<T> for BinaryTreeNode<T> {
+    fn from(value: T) -> Self {
+        BinaryTreeNode::new(value)
+    }
+}
+
+// What `new` used to take before the node was generic
+impl From<&str> for BinaryTreeNode<String> {
+    fn from(value: &str) -> Self {
+        BinaryTreeNode::new(value.to_owned())
+    }
+}
+
+impl<T: Debug> BinaryTreeNode<T> {
     // Root -> Left -> Right
     pub fn preorder(&self) {
         println!("{:?}", self.value);
@@ -52,9 +129,11 @@ impl BinaryTreeNode {
 
         println!("{:?}", self.value);
     }
+}
 
+impl<T: Display> BinaryTreeNode<T> {
     pub fn pretty_print(&self) {
-        fn helper(node: &Option<Box<BinaryTreeNode>>, prefix: String, is_left: bool) {
+        fn helper<T: Display>(node: &Option<Box<BinaryTreeNode<T>>>, prefix: String, is_left: bool) {
             if let Some(n) = node {
                 println!(
                     "{}{}{}",
@@ -78,20 +157,10 @@ impl BinaryTreeNode {
 mod tests {
     use super::*;
 
-    fn build_expression_tree() -> BinaryTreeNode {
-        let node3 = BinaryTreeNode::new("3");
-        let node4 = BinaryTreeNode::new("4");
-        let node5 = BinaryTreeNode::new("5");
-
-        let mut mult = BinaryTreeNode::new("*");
-        mult.left = Some(Box::new(node4));
-        mult.right = Some(Box::new(node5));
-
-        let mut plus = BinaryTreeNode::new("+");
-        plus.left = Some(Box::new(node3));
-        plus.right = Some(Box::new(mult));
-
-        plus
+    fn build_expression_tree() -> BinaryTreeNode<&'static str> {
+        BinaryTreeNode::new("+")
+            .with_left("3")
+            .with_right(BinaryTreeNode::new("*").with_left("4").with_right("5"))
     }
 
     #[test]
@@ -117,4 +186,55 @@ mod tests {
         let root = build_expression_tree();
         root.pretty_print();
     }
+
+    #[test]
+    fn test_accessors() {
+        let mut root = build_expression_tree();
+
+        assert_eq!(*root.value(), "+");
+        assert_eq!(root.left().map(BinaryTreeNode::value), Some(&"3"));
+        assert!(root.left().unwrap().is_leaf());
+
+        let mult = root.right_mut().unwrap();
+        *mult.value_mut() = "-";
+        *mult.left_mut().unwrap().value_mut() = "9";
+
+        let right = root.right().unwrap();
+        assert_eq!(*right.value(), "-");
+        assert_eq!(right.left().map(BinaryTreeNode::value), Some(&"9"));
+        assert_eq!(right.right().and_then(BinaryTreeNode::left), None);
+    }
+
+    #[test]
+    fn test_set_and_take_children() {
+        let mut root = BinaryTreeNode::new(1);
+
+        assert_eq!(root.set_left(2), None);
+        assert_eq!(root.set_left(BinaryTreeNode::new(3).with_right(4)), Some(BinaryTreeNode::new(2)));
+        assert_eq!(root.take_right(), None);
+
+        let left = root.take_left().unwrap();
+        assert_eq!(left.into_value(), 3);
+        assert!(root.is_leaf());
+
+        root.set_right(5);
+        assert_eq!(root.take_right().map(BinaryTreeNode::into_value), Some(5));
+    }
+
+    #[test]
+    fn test_from_clone_and_eq() {
+        let leaf: BinaryTreeNode<i32> = 7.into();
+        assert_eq!(leaf, BinaryTreeNode::new(7));
+
+        let named = BinaryTreeNode::<String>::from("root").with_left("child");
+        assert_eq!(named.left().unwrap().value(), "child");
+
+        let tree = build_expression_tree();
+        let mut copy = tree.clone();
+        assert_eq!(copy, tree);
+
+        copy.right_mut().unwrap().set_right("6");
+        assert_ne!(copy, tree);
+        assert_eq!(format!("{:?}", BinaryTreeNode::new(1)), "BinaryTreeNode { value: 1, left: None, right: None }");
+    }
 }
@@ -0,0 +1,51 @@
+// Shared by the trees' from_sorted_iter and their FromIterator/Extend. Sorted input can be
+// laid out as a balanced tree in one in-order pass, the middle value of every stretch
+// becomes the root of its subtree, so there is nothing to compare or rotate.
+
+// Ascending input comes back with equal neighbours dropped (the first one stays, like
+// repeated inserts would keep it), anything else comes back untouched
+pub(super) fn into_sorted<T: Ord>(mut values: Vec<T>) -> Result<Vec<T>, Vec<T>> {
+    if !values.windows(2).all(|pair| pair[0] <= pair[1]) {
+        return Err(values);
+    }
+
+    values.dedup();
+    Ok(values)
+}
+
+// Same as into_sorted, for the constructors that promise sorted input
+pub(super) fn expect_sorted<T: Ord, I: IntoIterator<Item = T>>(iter: I) -> Vec<T> {
+    into_sorted(iter.into_iter().collect())
+        .unwrap_or_else(|_| panic!("from_sorted_iter needs the values in ascending order"))
+}
+
+// How many values go into the left subtree of a stretch of `len`, the rest minus the root
+// go right, so the two sides never differ by more than one
+pub(super) fn left_len(len: usize) -> usize {
+    (len - 1) / 2
+}
+
+// With the split above every empty leaf is at depth log2(len + 1) rounded down or one below.
+// Coloring the nodes on that last, partly filled level red gives every path the same
+// number of black nodes. A perfect tree has nothing on it and comes out all black
+pub(super) fn red_depth(len: usize) -> usize {
+    (len + 1).ilog2() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_sorted() {
+        assert_eq!(into_sorted(vec![1, 2, 2, 3, 3, 3]), Ok(vec![1, 2, 3]));
+        assert_eq!(into_sorted(Vec::<i32>::new()), Ok(vec![]));
+        assert_eq!(into_sorted(vec![2, 1, 3]), Err(vec![2, 1, 3]));
+    }
+
+    #[test]
+    #[should_panic(expected = "from_sorted_iter needs the values in ascending order")]
+    fn test_expect_sorted_panics_on_unsorted_input() {
+        expect_sorted(vec![3, 1]);
+    }
+}
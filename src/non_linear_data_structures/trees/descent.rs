@@ -0,0 +1,223 @@
+// Single path walks shared by the size-augmented trees. The BST, AVL and Rc red-black nodes
+// only differ in how a child is reached and how a value gets compared, so each tree
+// implements SearchNode and floor/ceiling/rank/select and the range bounds are written
+// once here. They hand back nodes, the tree decides how to lend the value out.
+//
+// The traits are pub because the trees' public Range iterators are built on them, the
+// module itself is private so nothing outside the crate can name or implement them.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+};
+
+pub trait SearchNode {
+    type Value;
+
+    fn left(&self) -> Option<&Self>;
+
+    fn right(&self) -> Option<&Self>;
+
+    // Nodes in the subtree under and including this one
+    fn size(&self) -> usize;
+
+    // How `value` compares to the value in this node
+    fn compare<Q>(&self, value: &Q) -> Ordering
+    where
+        Self::Value: Borrow<Q>,
+        Q: Ord + ?Sized;
+}
+
+// Nodes that can lend out a plain reference to their value, everything but the Rc tree
+pub trait PlainNode: SearchNode {
+    fn value(&self) -> &Self::Value;
+}
+
+pub(super) fn size_of<N: SearchNode>(node: Option<&N>) -> usize {
+    node.map_or(0, N::size)
+}
+
+// First node inside the lower bound
+pub(super) fn lower_bound<'a, N, Q>(mut current: Option<&'a N>, bound: Bound<&Q>) -> Option<&'a N>
+where
+    N: SearchNode,
+    N::Value: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let mut found = None;
+
+    while let Some(node) = current {
+        let inside = match bound {
+            Bound::Included(value) => node.compare(value) != Ordering::Greater,
+            Bound::Excluded(value) => node.compare(value) == Ordering::Less,
+            Bound::Unbounded => true,
+        };
+
+        if inside {
+            found = Some(node);
+            current = node.left();
+        } else {
+            current = node.right();
+        }
+    }
+
+    found
+}
+
+// Last node inside the upper bound
+pub(super) fn upper_bound<'a, N, Q>(mut current: Option<&'a N>, bound: Bound<&Q>) -> Option<&'a N>
+where
+    N: SearchNode,
+    N::Value: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let mut found = None;
+
+    while let Some(node) = current {
+        let inside = match bound {
+            Bound::Included(value) => node.compare(value) != Ordering::Less,
+            Bound::Excluded(value) => node.compare(value) == Ordering::Greater,
+            Bound::Unbounded => true,
+        };
+
+        if inside {
+            found = Some(node);
+            current = node.right();
+        } else {
+            current = node.left();
+        }
+    }
+
+    found
+}
+
+// Largest value that is <= `value`
+pub(super) fn floor<'a, N, Q>(root: Option<&'a N>, value: &Q) -> Option<&'a N>
+where
+    N: SearchNode,
+    N::Value: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    upper_bound(root, Bound::Included(value))
+}
+
+// Smallest value that is >= `value`
+pub(super) fn ceiling<'a, N, Q>(root: Option<&'a N>, value: &Q) -> Option<&'a N>
+where
+    N: SearchNode,
+    N::Value: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    lower_bound(root, Bound::Included(value))
+}
+
+// How many values are smaller than `value`, whether or not it is in the tree.
+// Every step right skips the left subtree and the node itself
+pub(super) fn rank<N, Q>(mut current: Option<&N>, value: &Q) -> usize
+where
+    N: SearchNode,
+    N::Value: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let mut rank = 0;
+
+    while let Some(node) = current {
+        match node.compare(value) {
+            Ordering::Less => current = node.left(),
+            Ordering::Greater => {
+                rank += size_of(node.left()) + 1;
+                current = node.right();
+            }
+            Ordering::Equal => return rank + size_of(node.left()),
+        }
+    }
+
+    rank
+}
+
+// The k-th smallest value counting from 0, so select(rank(x)) is x when x is in the tree
+pub(super) fn select<N: SearchNode>(mut current: Option<&N>, mut k: usize) -> Option<&N> {
+    while let Some(node) = current {
+        let left = size_of(node.left());
+
+        match k.cmp(&left) {
+            Ordering::Less => current = node.left(),
+            Ordering::Equal => return Some(node),
+            Ordering::Greater => {
+                k -= left + 1;
+                current = node.right();
+            }
+        }
+    }
+
+    None
+}
+
+// Values inside a range in order, for the trees without parent links. The stack holds
+// the way down to the next value, `last` is where it stops
+pub struct Range<'a, N> {
+    stack: Vec<&'a N>,
+    last: Option<&'a N>,
+}
+
+impl<'a, N: PlainNode> Range<'a, N> {
+    pub(super) fn new<Q, R>(root: Option<&'a N>, range: R) -> Self
+    where
+        N::Value: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut iter = Range { stack: vec![], last: upper_bound(root, range.end_bound()) };
+
+        // the way down to the first value inside, keeping the nodes still to come
+        let mut current = root;
+        while let Some(node) = current {
+            let inside = match range.start_bound() {
+                Bound::Included(low) => node.compare(low) != Ordering::Greater,
+                Bound::Excluded(low) => node.compare(low) == Ordering::Less,
+                Bound::Unbounded => true,
+            };
+
+            if inside {
+                iter.stack.push(node);
+                current = node.left();
+            } else {
+                current = node.right();
+            }
+        }
+
+        // an empty range leaves the last value before the first
+        let empty = match (iter.stack.last(), iter.last) {
+            (Some(first), Some(last)) => last.compare::<Q>(first.value().borrow()) == Ordering::Greater,
+            _ => true,
+        };
+
+        if empty {
+            iter.stack.clear();
+        }
+
+        iter
+    }
+}
+
+impl<'a, N: PlainNode> Iterator for Range<'a, N> {
+    type Item = &'a N::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        // the last node inside ends it, whatever is still on the stack is past the range
+        if self.last.is_some_and(|last| std::ptr::eq(last, node)) {
+            self.stack.clear();
+        } else {
+            let mut current = node.right();
+            while let Some(n) = current {
+                self.stack.push(n);
+                current = n.left();
+            }
+        }
+
+        Some(node.value())
+    }
+}
@@ -0,0 +1,212 @@
+// Decoded pages kept in memory, at most `capacity` of them. Past that the least recently
+// used one goes, and if it has changes the data file doesn't it's appended to the log first
+// and read back from there until the next flush. The data file is only written in
+// checkpoint, with pages the log already committed.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::{
+    page::{self, Meta, Node, PAGE_SIZE},
+    wal::Wal,
+    DiskError,
+};
+use crate::linear_data_structures::linked_lists::arena::{ArenaLinkedList, Handle};
+
+pub(super) struct BufferPool {
+    file: File,
+    wal: Wal,
+    frames: HashMap<u32, Frame>,
+    lru: ArenaLinkedList<u32>, // page ids, most recently used at the front
+    capacity: usize,
+    logged: HashMap<u32, u64>, // pages whose latest image is in the log, and where
+}
+
+struct Frame {
+    node: Node,
+    dirty: bool,
+    handle: Handle,
+}
+
+impl BufferPool {
+    // Opens the data file and its log next to it, finishing a flush that committed but
+    // didn't get to copy its pages over and dropping anything after the last commit
+    pub(super) fn open(path: &Path, capacity: usize) -> Result<Self, DiskError> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+
+        let mut wal_path = path.as_os_str().to_owned();
+        wal_path.push(".wal");
+        let mut wal = Wal::open(Path::new(&wal_path))?;
+        let logged = wal.committed_pages()?.into_iter().collect();
+
+        let mut pool = BufferPool {
+            file,
+            wal,
+            frames: HashMap::new(),
+            lru: ArenaLinkedList::new(),
+            capacity,
+            logged,
+        };
+
+        pool.checkpoint()?;
+        Ok(pool)
+    }
+
+    // A file that has never been flushed
+    pub(super) fn is_new(&self) -> Result<bool, DiskError> {
+        Ok(self.file.metadata()?.len() == 0)
+    }
+
+    pub(super) fn read_meta(&mut self) -> Result<Meta, DiskError> {
+        page::decode_meta(&self.read_page(0)?)
+    }
+
+    pub(super) fn get(&mut self, id: u32) -> Result<&Node, DiskError> {
+        Ok(&self.frame(id)?.node)
+    }
+
+    pub(super) fn get_mut(&mut self, id: u32) -> Result<&mut Node, DiskError> {
+        let frame = self.frame(id)?;
+        frame.dirty = true;
+        Ok(&mut frame.node)
+    }
+
+    // Sets a page without reading what was there, for pages that were just allocated
+    pub(super) fn put(&mut self, id: u32, node: Node) -> Result<(), DiskError> {
+        match self.frames.get_mut(&id) {
+            Some(frame) => {
+                frame.node = node;
+                frame.dirty = true;
+                self.lru.move_to_front(frame.handle);
+                Ok(())
+            }
+            None => self.insert_frame(id, node, true),
+        }
+    }
+
+    pub(super) fn flush(&mut self, meta: &Meta) -> Result<(), DiskError> {
+        self.commit(meta)?;
+        self.checkpoint()
+    }
+
+    // Logs every dirty page and the meta page and commits them. From here on a crash
+    // can't lose them
+    pub(super) fn commit(&mut self, meta: &Meta) -> Result<(), DiskError> {
+        for (&id, frame) in self.frames.iter_mut().filter(|(_, frame)| frame.dirty) {
+            let offset = self.wal.append_page(id, &page::encode(&frame.node))?;
+            self.logged.insert(id, offset);
+            frame.dirty = false;
+        }
+
+        let offset = self.wal.append_page(0, &page::encode_meta(meta))?;
+        self.logged.insert(0, offset);
+        self.wal.commit()
+    }
+
+    // Copies the logged pages into the data file and empties the log. Only right once
+    // they're committed. Until the copy is synced the log is the only place with the
+    // committed pages, so reads keep going there if any of it fails
+    fn checkpoint(&mut self) -> Result<(), DiskError> {
+        let mut pages: Vec<_> = self.logged.iter().map(|(&id, &offset)| (id, offset)).collect();
+        pages.sort_unstable();
+
+        for &(id, offset) in &pages {
+            let page = self.wal.read_page(offset)?;
+            self.file.seek(SeekFrom::Start(id as u64 * PAGE_SIZE as u64))?;
+            self.file.write_all(&page)?;
+        }
+
+        if !pages.is_empty() {
+            self.file.sync_data()?;
+        }
+        self.wal.clear()?;
+        self.logged.clear();
+        Ok(())
+    }
+
+    fn frame(&mut self, id: u32) -> Result<&mut Frame, DiskError> {
+        match self.frames.get(&id) {
+            Some(frame) => {
+                self.lru.move_to_front(frame.handle);
+            }
+            None => {
+                let node = page::decode(id, &self.read_page(id)?)?;
+                self.insert_frame(id, node, false)?;
+            }
+        }
+
+        Ok(self.frames.get_mut(&id).unwrap())
+    }
+
+    fn insert_frame(&mut self, id: u32, node: Node, dirty: bool) -> Result<(), DiskError> {
+        while self.frames.len() >= self.capacity {
+            self.evict()?;
+        }
+
+        let handle = self.lru.push_front(id);
+        self.frames.insert(id, Frame { node, dirty, handle });
+        Ok(())
+    }
+
+    fn evict(&mut self) -> Result<(), DiskError> {
+        let id = self.lru.pop_back().expect("evicting from an empty pool");
+        let frame = self.frames.remove(&id).unwrap();
+
+        if frame.dirty {
+            let offset = self.wal.append_page(id, &page::encode(&frame.node))?;
+            self.logged.insert(id, offset);
+        }
+        Ok(())
+    }
+
+    fn read_page(&mut self, id: u32) -> Result<Vec<u8>, DiskError> {
+        if let Some(&offset) = self.logged.get(&id) {
+            return self.wal.read_page(offset);
+        }
+
+        let mut page = vec![0; PAGE_SIZE];
+        self.file.seek(SeekFrom::Start(id as u64 * PAGE_SIZE as u64))?;
+        self.file.read_exact(&mut page)?;
+        Ok(page)
+    }
+
+    #[cfg(test)]
+    pub(super) fn cached(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_linear_data_structures::trees::disk_b_plus_tree::{page::NIL, tests::TempFile};
+
+    #[test]
+    fn test_failed_checkpoint_keeps_reading_the_log() {
+        let file = TempFile::new("failed_checkpoint");
+        let mut pool = BufferPool::open(&file.0, 4).unwrap();
+        let meta = Meta { root: 1, first: 1, free: NIL, pages: 2, len: 0 };
+
+        pool.put(1, Node::Leaf { entries: vec![], next: NIL }).unwrap();
+        pool.flush(&meta).unwrap();
+
+        let leaf = Node::Leaf { entries: vec![(b"key".to_vec(), b"value".to_vec())], next: NIL };
+        let meta = Meta { len: 1, ..meta };
+        pool.put(1, leaf.clone()).unwrap();
+        pool.commit(&meta).unwrap();
+
+        // a read-only handle makes every write to the data file fail
+        pool.file = File::open(&file.0).unwrap();
+        assert!(matches!(pool.checkpoint(), Err(DiskError::Io(_))));
+
+        // nothing cached, so these come from the log or the stale data file
+        pool.frames.clear();
+        pool.lru.clear();
+        assert_eq!(pool.get(1).unwrap(), &leaf);
+        assert_eq!(pool.read_meta().unwrap(), meta);
+    }
+}
@@ -0,0 +1,854 @@
+// B+ tree in a file, for an ordered key-value index that outlives the process. Keys and
+// values are byte strings, keys compare bytewise.
+//
+// The file is a row of PAGE_SIZE pages. Page 0 says where the root, the first leaf and the
+// free list are, every other page is a leaf, an inner node or free, see page.rs. Each page
+// starts with a CRC32 of the rest of it, so a torn or bit-flipped page comes back as an
+// error instead of garbage.
+//
+// Pages are decoded into nodes in a buffer pool with LRU eviction. The data file is only
+// written from the write-ahead log: a flush appends every changed page to the log, syncs
+// it with a commit record, and only then copies the pages into place. A crash before the
+// commit record leaves the file as of the last flush, a crash after it has the log
+// replayed on the next open. Flush is the only commit point, anything changed since the
+// last one is gone if the process dies or the tree is dropped without `close`.
+//
+// Same shape as the in-memory B+ tree, but entries vary in size, so a node splits when its
+// page overflows and counts as underfull below a quarter page, not by key count. Fixing an
+// underfull node merges it with a sibling if both fit one page and otherwise shares the
+// bytes out evenly between the two. A new separator can be longer than the one it
+// replaced, so on the way up after a delete a parent can overflow and split too.
+
+mod buffer_pool;
+mod page;
+mod wal;
+
+use std::{
+    error, fmt, io, mem,
+    ops::{Bound, RangeBounds},
+    path::Path,
+};
+
+use buffer_pool::BufferPool;
+use page::{Entry, Meta, Node, NIL, PAGE_SIZE};
+
+// Longest key plus value. Small enough that cutting an overflowing page in half leaves both
+// halves well above UNDERFULL
+pub const MAX_ENTRY: usize = PAGE_SIZE / 8;
+
+const UNDERFULL: usize = PAGE_SIZE / 4;
+const DEFAULT_FRAMES: usize = 256;
+
+// A node that grew past its page: the separator and the new page to its right
+type Split = Option<(Vec<u8>, u32)>;
+
+#[derive(Debug)]
+pub enum DiskError {
+    Io(io::Error),
+    // the page doesn't match its checksum, a torn write or a damaged disk
+    Checksum { page: u32 },
+    // the page is intact but not what the tree expects there
+    Corrupt { page: u32, reason: &'static str },
+    TooLarge { len: usize, max: usize },
+}
+
+impl fmt::Display for DiskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiskError::Io(error) => write!(f, "{}", error),
+            DiskError::Checksum { page } => write!(f, "page {} doesn't match its checksum", page),
+            DiskError::Corrupt { page, reason } => write!(f, "page {}: {}", page, reason),
+            DiskError::TooLarge { len, max } => {
+                write!(f, "an entry of {} bytes is over the {} byte limit", len, max)
+            }
+        }
+    }
+}
+
+impl error::Error for DiskError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DiskError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DiskError {
+    fn from(error: io::Error) -> Self {
+        DiskError::Io(error)
+    }
+}
+
+fn corrupt(page: u32, reason: &'static str) -> DiskError {
+    DiskError::Corrupt { page, reason }
+}
+
+// The child of an inner node whose keys `key` falls among, separators equal to it count as
+// to its left
+fn child_index(keys: &[Vec<u8>], key: &[u8]) -> usize {
+    keys.partition_point(|k| k.as_slice() <= key)
+}
+
+fn search(entries: &[Entry], key: &[u8]) -> Result<usize, usize> {
+    entries.binary_search_by(|(k, _)| k.as_slice().cmp(key))
+}
+
+pub struct DiskBPlusTree {
+    pool: BufferPool,
+    meta: Meta, // page 0, written on every flush
+}
+
+impl DiskBPlusTree {
+    // Opens the tree at `path`, creating the file if there isn't one. The log is kept next
+    // to it, at the same path with ".wal" added
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DiskError> {
+        Self::with_frames(path, DEFAULT_FRAMES)
+    }
+
+    // Keeps at most `frames` pages in memory
+    pub fn with_frames<P: AsRef<Path>>(path: P, frames: usize) -> Result<Self, DiskError> {
+        assert!(frames > 0, "the buffer pool needs at least one frame");
+
+        let mut pool = BufferPool::open(path.as_ref(), frames)?;
+        if !pool.is_new()? {
+            let meta = pool.read_meta()?;
+            return Ok(DiskBPlusTree { pool, meta });
+        }
+
+        pool.put(1, Node::Leaf { entries: vec![], next: NIL })?;
+        let mut tree = DiskBPlusTree { pool, meta: Meta { root: 1, first: 1, free: NIL, pages: 2, len: 0 } };
+        tree.flush()?;
+        Ok(tree)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.meta.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meta.len == 0
+    }
+
+    // Makes everything so far durable
+    pub fn flush(&mut self) -> Result<(), DiskError> {
+        self.pool.flush(&self.meta)
+    }
+
+    pub fn close(mut self) -> Result<(), DiskError> {
+        self.flush()
+    }
+
+    // Takes `&mut self` since reading pages in can evict others
+    pub fn get(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, DiskError> {
+        let leaf = self.find_leaf(key)?;
+        let entries = self.leaf(leaf)?;
+
+        Ok(search(entries, key).ok().map(|i| entries[i].1.clone()))
+    }
+
+    pub fn contains_key(&mut self, key: &[u8]) -> Result<bool, DiskError> {
+        let leaf = self.find_leaf(key)?;
+        Ok(search(self.leaf(leaf)?, key).is_ok())
+    }
+
+    pub fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, DiskError> {
+        let len = key.len() + value.len();
+        if len > MAX_ENTRY {
+            return Err(DiskError::TooLarge { len, max: MAX_ENTRY });
+        }
+
+        let (old, split) = self.insert_at(self.meta.root, key, value)?;
+        self.fix_root(split)?;
+
+        if old.is_none() {
+            self.meta.len += 1;
+        }
+        Ok(old)
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, DiskError> {
+        let (removed, split) = self.remove_at(self.meta.root, key)?;
+        self.fix_root(split)?;
+
+        if removed.is_some() {
+            self.meta.len -= 1;
+        }
+        Ok(removed)
+    }
+
+    // Entries in key order. Pages are read as it goes, so every item can fail
+    pub fn iter(&mut self) -> Range<'_> {
+        Range { leaf: self.meta.first, index: 0, end: Bound::Unbounded, tree: self }
+    }
+
+    pub fn range<K, R>(&mut self, range: R) -> Result<Range<'_>, DiskError>
+    where
+        K: AsRef<[u8]> + ?Sized,
+        R: RangeBounds<K>,
+    {
+        let (leaf, index) = match range.start_bound() {
+            Bound::Included(low) => {
+                let low = low.as_ref();
+                let leaf = self.find_leaf(low)?;
+                (leaf, self.leaf(leaf)?.partition_point(|(k, _)| k.as_slice() < low))
+            }
+            Bound::Excluded(low) => {
+                let low = low.as_ref();
+                let leaf = self.find_leaf(low)?;
+                (leaf, self.leaf(leaf)?.partition_point(|(k, _)| k.as_slice() <= low))
+            }
+            Bound::Unbounded => (self.meta.first, 0),
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(high) => Bound::Included(high.as_ref().to_vec()),
+            Bound::Excluded(high) => Bound::Excluded(high.as_ref().to_vec()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        Ok(Range { tree: self, leaf, index, end })
+    }
+
+    fn find_leaf(&mut self, key: &[u8]) -> Result<u32, DiskError> {
+        let mut id = self.meta.root;
+
+        while let Node::Inner { keys, children } = self.pool.get(id)? {
+            id = children[child_index(keys, key)];
+        }
+        Ok(id)
+    }
+
+    fn leaf(&mut self, id: u32) -> Result<&Vec<Entry>, DiskError> {
+        match self.pool.get(id)? {
+            Node::Leaf { entries, .. } => Ok(entries),
+            _ => Err(corrupt(id, "expected a leaf")),
+        }
+    }
+
+    fn leaf_mut(&mut self, id: u32) -> Result<&mut Vec<Entry>, DiskError> {
+        match self.pool.get_mut(id)? {
+            Node::Leaf { entries, .. } => Ok(entries),
+            _ => Err(corrupt(id, "expected a leaf")),
+        }
+    }
+
+    fn inner_mut(&mut self, id: u32) -> Result<(&mut Vec<Vec<u8>>, &mut Vec<u32>), DiskError> {
+        match self.pool.get_mut(id)? {
+            Node::Inner { keys, children } => Ok((keys, children)),
+            _ => Err(corrupt(id, "expected an inner node")),
+        }
+    }
+
+    // Takes a page off the free list, or adds one at the end of the file
+    fn alloc(&mut self, node: Node) -> Result<u32, DiskError> {
+        let id = match self.meta.free {
+            NIL => {
+                self.meta.pages += 1;
+                self.meta.pages - 1
+            }
+            id => {
+                self.meta.free = match self.pool.get(id)? {
+                    Node::Free { next } => *next,
+                    _ => return Err(corrupt(id, "on the free list but in use")),
+                };
+                id
+            }
+        };
+
+        self.pool.put(id, node)?;
+        Ok(id)
+    }
+
+    fn release(&mut self, id: u32) -> Result<(), DiskError> {
+        self.pool.put(id, Node::Free { next: self.meta.free })?;
+        self.meta.free = id;
+        Ok(())
+    }
+
+    fn insert_at(&mut self, id: u32, key: &[u8], value: &[u8]) -> Result<(Option<Vec<u8>>, Split), DiskError> {
+        let (i, child) = match self.pool.get(id)? {
+            Node::Inner { keys, children } => {
+                let i = child_index(keys, key);
+                (i, children[i])
+            }
+            _ => {
+                let entries = self.leaf_mut(id)?;
+                let old = match search(entries, key) {
+                    Ok(i) => Some(mem::replace(&mut entries[i].1, value.to_vec())),
+                    Err(i) => {
+                        entries.insert(i, (key.to_vec(), value.to_vec()));
+                        None
+                    }
+                };
+                // a longer value can overflow the leaf as well
+                return Ok((old, self.split_if_full(id)?));
+            }
+        };
+
+        let (old, split) = self.insert_at(child, key, value)?;
+        Ok((old, self.settle(id, i, child, split)?))
+    }
+
+    fn remove_at(&mut self, id: u32, key: &[u8]) -> Result<(Option<Vec<u8>>, Split), DiskError> {
+        let (i, child) = match self.pool.get(id)? {
+            Node::Inner { keys, children } => {
+                let i = child_index(keys, key);
+                (i, children[i])
+            }
+            _ => {
+                let Ok(i) = search(self.leaf(id)?, key) else {
+                    return Ok((None, None));
+                };
+                return Ok((Some(self.leaf_mut(id)?.remove(i).1), None));
+            }
+        };
+
+        let (removed, split) = self.remove_at(child, key)?;
+        if removed.is_none() {
+            return Ok((None, None));
+        }
+        Ok((removed, self.settle(id, i, child, split)?))
+    }
+
+    // Fixes up inner node `id` after child i changed: takes in its split or fixes it if it
+    // went underfull, then splits `id` itself if it no longer fits its page
+    fn settle(&mut self, id: u32, i: usize, child: u32, split: Split) -> Result<Split, DiskError> {
+        match split {
+            Some((separator, right)) => {
+                let (keys, children) = self.inner_mut(id)?;
+                keys.insert(i, separator);
+                children.insert(i + 1, right);
+            }
+            None if self.pool.get(child)?.size() < UNDERFULL => self.rebalance(id, i)?,
+            None => {}
+        }
+
+        self.split_if_full(id)
+    }
+
+    fn split_if_full(&mut self, id: u32) -> Result<Split, DiskError> {
+        if self.pool.get(id)?.size() <= PAGE_SIZE {
+            return Ok(None);
+        }
+
+        match self.pool.get_mut(id)? {
+            Node::Leaf { entries, next } => {
+                let sizes: Vec<_> = entries.iter().map(page::leaf_entry_size).collect();
+                let right_entries = entries.split_off(page::split_point(&sizes));
+                let separator = right_entries[0].0.clone();
+                let next = *next;
+
+                let right = self.alloc(Node::Leaf { entries: right_entries, next })?;
+                if let Node::Leaf { next, .. } = self.pool.get_mut(id)? {
+                    *next = right;
+                }
+                Ok(Some((separator, right)))
+            }
+            Node::Inner { keys, children } => {
+                let (separator, right_keys, right_children) = split_inner(keys, children);
+                let right = self.alloc(Node::Inner { keys: right_keys, children: right_children })?;
+                Ok(Some((separator, right)))
+            }
+            Node::Free { .. } => Err(corrupt(id, "a free page in the tree")),
+        }
+    }
+
+    // Child i of `parent` went underfull: merge it with a sibling, or if the two don't fit
+    // one page split their entries evenly between them
+    fn rebalance(&mut self, parent: u32, i: usize) -> Result<(), DiskError> {
+        let (l, left, right, separator) = match self.pool.get(parent)? {
+            Node::Inner { keys, children } => {
+                let l = i.saturating_sub(1);
+                if l + 1 >= children.len() {
+                    return Ok(());
+                }
+                (l, children[l], children[l + 1], keys[l].clone())
+            }
+            _ => return Err(corrupt(parent, "expected an inner node")),
+        };
+
+        // copies, so an error halfway through leaves both pages as they were
+        let left_node = self.pool.get(left)?.clone();
+        let right_node = self.pool.get(right)?.clone();
+
+        let separator = match (left_node, right_node) {
+            (Node::Leaf { entries: mut merged, .. }, Node::Leaf { entries, next }) => {
+                merged.extend(entries);
+                if page::leaf_size(&merged) <= PAGE_SIZE {
+                    self.pool.put(left, Node::Leaf { entries: merged, next })?;
+                    None
+                } else {
+                    let sizes: Vec<_> = merged.iter().map(page::leaf_entry_size).collect();
+                    let entries = merged.split_off(page::split_point(&sizes));
+                    let separator = entries[0].0.clone();
+
+                    self.pool.put(left, Node::Leaf { entries: merged, next: right })?;
+                    self.pool.put(right, Node::Leaf { entries, next })?;
+                    Some(separator)
+                }
+            }
+            (Node::Inner { keys: mut merged, children: mut merged_children }, Node::Inner { keys, children }) => {
+                merged.push(separator);
+                merged.extend(keys);
+                merged_children.extend(children);
+
+                if page::inner_size(&merged) <= PAGE_SIZE {
+                    self.pool.put(left, Node::Inner { keys: merged, children: merged_children })?;
+                    None
+                } else {
+                    let (separator, keys, children) = split_inner(&mut merged, &mut merged_children);
+                    self.pool.put(left, Node::Inner { keys: merged, children: merged_children })?;
+                    self.pool.put(right, Node::Inner { keys, children })?;
+                    Some(separator)
+                }
+            }
+            _ => return Err(corrupt(parent, "children of different kinds")),
+        };
+
+        match separator {
+            Some(separator) => self.inner_mut(parent)?.0[l] = separator,
+            None => {
+                self.release(right)?;
+                let (keys, children) = self.inner_mut(parent)?;
+                keys.remove(l);
+                children.remove(l + 1);
+            }
+        }
+        Ok(())
+    }
+
+    // A root that split gets a new root above it, an inner root down to one child is
+    // replaced by that child
+    fn fix_root(&mut self, split: Split) -> Result<(), DiskError> {
+        if let Some((separator, right)) = split {
+            let root = Node::Inner { keys: vec![separator], children: vec![self.meta.root, right] };
+            self.meta.root = self.alloc(root)?;
+        }
+
+        if let Node::Inner { keys, children } = self.pool.get(self.meta.root)? {
+            if keys.is_empty() {
+                let old = mem::replace(&mut self.meta.root, children[0]);
+                self.release(old)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Reads the whole file and checks the tree: every page decodes, keys are in order and
+    // inside their separators, leaves are all at one depth and chained in order, nodes
+    // other than the root are at least a quarter full, and every page is either in the
+    // tree or on the free list
+    pub fn validate(&mut self) -> Result<(), DiskError> {
+        let mut leaves = Vec::new();
+        let mut depth = None;
+        let (len, pages) = self.validate_node(self.meta.root, None, None, 0, &mut depth, &mut leaves)?;
+
+        if len != self.meta.len {
+            return Err(corrupt(0, "entry count doesn't match the tree"));
+        }
+
+        let mut leaf = self.meta.first;
+        for &expected in &leaves {
+            if leaf != expected {
+                return Err(corrupt(leaf, "the leaf chain is out of order"));
+            }
+            leaf = match self.pool.get(leaf)? {
+                Node::Leaf { next, .. } => *next,
+                _ => return Err(corrupt(leaf, "expected a leaf")),
+            };
+        }
+        if leaf != NIL {
+            return Err(corrupt(leaf, "the leaf chain runs past the last leaf"));
+        }
+
+        let mut free = 0;
+        let mut id = self.meta.free;
+        while id != NIL {
+            id = match self.pool.get(id)? {
+                Node::Free { next } => *next,
+                _ => return Err(corrupt(id, "on the free list but in use")),
+            };
+            free += 1;
+        }
+
+        if 1 + pages + free != self.meta.pages {
+            return Err(corrupt(0, "pages neither in the tree nor free"));
+        }
+        Ok(())
+    }
+
+    // Returns the entries and the pages under `id`. Keys must be in [low, high)
+    fn validate_node(
+        &mut self,
+        id: u32,
+        low: Option<&[u8]>,
+        high: Option<&[u8]>,
+        depth: usize,
+        leaf_depth: &mut Option<usize>,
+        leaves: &mut Vec<u32>,
+    ) -> Result<(u64, u32), DiskError> {
+        let node = self.pool.get(id)?.clone();
+
+        if id != self.meta.root && node.size() < UNDERFULL {
+            return Err(corrupt(id, "underfull"));
+        }
+
+        let in_bounds = |key: &[u8]| low.is_none_or(|low| low <= key) && high.is_none_or(|high| key < high);
+
+        match node {
+            Node::Leaf { entries, .. } => {
+                if !entries.iter().all(|(key, _)| in_bounds(key)) || !entries.windows(2).all(|w| w[0].0 < w[1].0) {
+                    return Err(corrupt(id, "keys out of order"));
+                }
+                if *leaf_depth.get_or_insert(depth) != depth {
+                    return Err(corrupt(id, "leaves at different depths"));
+                }
+
+                leaves.push(id);
+                Ok((entries.len() as u64, 1))
+            }
+            Node::Inner { keys, children } => {
+                if keys.is_empty() || children.len() != keys.len() + 1 {
+                    return Err(corrupt(id, "inner node without keys"));
+                }
+                if !keys.iter().all(|key| in_bounds(key)) || !keys.windows(2).all(|w| w[0] < w[1]) {
+                    return Err(corrupt(id, "keys out of order"));
+                }
+
+                let (mut len, mut pages) = (0, 1);
+                for (i, &child) in children.iter().enumerate() {
+                    let low = if i == 0 { low } else { Some(keys[i - 1].as_slice()) };
+                    let high = keys.get(i).map(Vec::as_slice).or(high);
+
+                    let (entries, child_pages) = self.validate_node(child, low, high, depth + 1, leaf_depth, leaves)?;
+                    len += entries;
+                    pages += child_pages;
+                }
+                Ok((len, pages))
+            }
+            Node::Free { .. } => Err(corrupt(id, "a free page in the tree")),
+        }
+    }
+}
+
+// Cuts an overflowing inner node about in half by bytes. Returns the key that moves up and
+// the right half, the left half stays behind
+fn split_inner(keys: &mut Vec<Vec<u8>>, children: &mut Vec<u32>) -> (Vec<u8>, Vec<Vec<u8>>, Vec<u32>) {
+    let sizes: Vec<_> = keys.iter().map(|key| page::inner_key_size(key)).collect();
+    // the middle key goes up, so leave at least one on the right
+    let at = page::split_point(&sizes).min(keys.len() - 2);
+
+    let right_keys = keys.split_off(at + 1);
+    let right_children = children.split_off(at + 1);
+    (keys.pop().unwrap(), right_keys, right_children)
+}
+
+pub struct Range<'a> {
+    tree: &'a mut DiskBPlusTree,
+    leaf: u32,
+    index: usize,
+    end: Bound<Vec<u8>>,
+}
+
+impl Iterator for Range<'_> {
+    type Item = Result<(Vec<u8>, Vec<u8>), DiskError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.leaf != NIL {
+            let step = match self.tree.pool.get(self.leaf) {
+                Ok(Node::Leaf { entries, next }) => match entries.get(self.index) {
+                    Some((key, value)) => {
+                        let past = match &self.end {
+                            Bound::Included(high) => key > high,
+                            Bound::Excluded(high) => key >= high,
+                            Bound::Unbounded => false,
+                        };
+                        self.index += 1;
+                        (!past).then(|| Ok((key.clone(), value.clone())))
+                    }
+                    None => {
+                        self.leaf = *next;
+                        self.index = 0;
+                        continue;
+                    }
+                },
+                Ok(_) => Some(Err(corrupt(self.leaf, "expected a leaf"))),
+                Err(error) => Some(Err(error)),
+            };
+
+            // past the end or failed, either way there's nothing more
+            if !matches!(step, Some(Ok(_))) {
+                self.leaf = NIL;
+            }
+            return step;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property::Rng;
+    use std::{
+        collections::BTreeMap,
+        fs::{self, OpenOptions},
+        io::Write,
+        path::PathBuf,
+    };
+
+    // A file under the temp dir, removed with its log when dropped
+    pub(super) struct TempFile(pub(super) PathBuf);
+
+    impl TempFile {
+        pub(super) fn new(name: &str) -> Self {
+            let file = TempFile(std::env::temp_dir().join(format!("disk_b_plus_tree_{}_{}", std::process::id(), name)));
+            file.remove();
+            file
+        }
+
+        fn wal(&self) -> PathBuf {
+            let mut path = self.0.clone().into_os_string();
+            path.push(".wal");
+            path.into()
+        }
+
+        fn remove(&self) {
+            let _ = fs::remove_file(&self.0);
+            let _ = fs::remove_file(self.wal());
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            self.remove();
+        }
+    }
+
+    fn key(n: usize) -> Vec<u8> {
+        format!("key{:06}", n).into_bytes()
+    }
+
+    fn entries(tree: &mut DiskBPlusTree) -> Vec<Entry> {
+        tree.iter().collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let file = TempFile::new("basic");
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        assert!(tree.is_empty());
+
+        assert_eq!(tree.insert(b"b", b"2").unwrap(), None);
+        assert_eq!(tree.insert(b"a", b"1").unwrap(), None);
+        assert_eq!(tree.insert(b"b", b"two").unwrap(), Some(b"2".to_vec()));
+        assert_eq!(tree.len(), 2);
+
+        assert_eq!(tree.get(b"b").unwrap(), Some(b"two".to_vec()));
+        assert_eq!(tree.get(b"c").unwrap(), None);
+        assert!(tree.contains_key(b"a").unwrap());
+
+        assert_eq!(tree.remove(b"a").unwrap(), Some(b"1".to_vec()));
+        assert_eq!(tree.remove(b"a").unwrap(), None);
+        assert_eq!(entries(&mut tree), vec![(b"b".to_vec(), b"two".to_vec())]);
+        tree.validate().unwrap();
+    }
+
+    #[test]
+    fn test_survives_reopen() {
+        let file = TempFile::new("reopen");
+
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        for n in 0..2000 {
+            tree.insert(&key(n), &n.to_le_bytes()).unwrap();
+        }
+        tree.close().unwrap();
+
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        assert_eq!(tree.len(), 2000);
+        assert_eq!(tree.get(&key(1234)).unwrap(), Some(1234usize.to_le_bytes().to_vec()));
+        tree.validate().unwrap();
+    }
+
+    #[test]
+    fn test_range() {
+        let file = TempFile::new("range");
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        for n in (0..1000).step_by(2) {
+            tree.insert(&key(n), b"").unwrap();
+        }
+
+        let keys = |range: Range| range.map(|entry| entry.unwrap().0).collect::<Vec<_>>();
+
+        assert_eq!(keys(tree.range(key(101)..key(107)).unwrap()), vec![key(102), key(104), key(106)]);
+        assert_eq!(keys(tree.range(key(102)..=key(106)).unwrap()), vec![key(102), key(104), key(106)]);
+        assert_eq!(
+            keys(tree.range::<Vec<u8>, _>((Bound::Excluded(key(102)), Bound::Excluded(key(106)))).unwrap()),
+            vec![key(104)]
+        );
+        assert_eq!(keys(tree.range(key(994)..).unwrap()), vec![key(994), key(996), key(998)]);
+        assert_eq!(keys(tree.range(..key(4)).unwrap()), vec![key(0), key(2)]);
+        assert!(keys(tree.range(key(500)..key(400)).unwrap()).is_empty());
+        assert_eq!(tree.range::<[u8], _>(..).unwrap().count(), 500);
+    }
+
+    #[test]
+    fn test_matches_btree_map_with_a_small_pool() {
+        let file = TempFile::new("model");
+        let mut tree = DiskBPlusTree::with_frames(&file.0, 4).unwrap();
+        let mut model = BTreeMap::new();
+        let mut rng = Rng::new(49);
+
+        for step in 0..6000 {
+            let k = key(rng.below(1500));
+            match rng.below(3) {
+                0 | 1 => {
+                    let value = vec![step as u8; rng.below(200)];
+                    assert_eq!(tree.insert(&k, &value).unwrap(), model.insert(k, value));
+                }
+                _ => assert_eq!(tree.remove(&k).unwrap(), model.remove(&k)),
+            }
+
+            if step % 2000 == 0 {
+                tree.validate().unwrap();
+                tree.flush().unwrap();
+            }
+        }
+        assert!(tree.pool.cached() <= 4);
+        tree.validate().unwrap();
+        tree.close().unwrap();
+
+        let mut tree = DiskBPlusTree::with_frames(&file.0, 4).unwrap();
+        assert_eq!(entries(&mut tree), model.into_iter().collect::<Vec<_>>());
+        tree.validate().unwrap();
+    }
+
+    #[test]
+    fn test_long_keys_split_parents_on_remove() {
+        let file = TempFile::new("long_keys");
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        let mut model = BTreeMap::new();
+        let mut rng = Rng::new(7);
+
+        for n in 0..3000 {
+            let mut k = key(rng.below(5000));
+            k.resize(rng.below(MAX_ENTRY - 20).max(k.len()), n as u8);
+            tree.insert(&k, b"v").unwrap();
+            model.insert(k, b"v".to_vec());
+        }
+        tree.validate().unwrap();
+
+        let keys: Vec<_> = model.keys().cloned().collect();
+        for k in keys.iter().step_by(3) {
+            assert_eq!(tree.remove(k).unwrap(), model.remove(k));
+        }
+        tree.validate().unwrap();
+        assert_eq!(entries(&mut tree), model.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_unflushed_changes_are_lost_like_a_crash() {
+        let file = TempFile::new("crash");
+
+        let mut tree = DiskBPlusTree::with_frames(&file.0, 2).unwrap();
+        tree.insert(b"kept", b"1").unwrap();
+        tree.flush().unwrap();
+        for n in 0..500 {
+            // enough to evict dirty pages into the log uncommitted
+            tree.insert(&key(n), &[0; 100]).unwrap();
+        }
+        drop(tree);
+
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        assert_eq!(entries(&mut tree), vec![(b"kept".to_vec(), b"1".to_vec())]);
+        tree.validate().unwrap();
+    }
+
+    #[test]
+    fn test_committed_log_is_replayed_on_open() {
+        let file = TempFile::new("replay");
+
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        for n in 0..500 {
+            tree.insert(&key(n), &[1; 50]).unwrap();
+        }
+        // crash after the commit record, before the pages were copied into the file
+        tree.pool.commit(&tree.meta).unwrap();
+        drop(tree);
+
+        // and a torn record after it, from a flush that never finished
+        OpenOptions::new().append(true).open(file.wal()).unwrap().write_all(&[1, 5, 0, 0, 0, 9, 9]).unwrap();
+
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        assert_eq!(tree.len(), 500);
+        assert_eq!(tree.get(&key(499)).unwrap(), Some(vec![1; 50]));
+        tree.validate().unwrap();
+        assert_eq!(fs::metadata(file.wal()).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_free_pages_are_reused() {
+        let file = TempFile::new("free");
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+
+        let mut pages = vec![];
+        for round in 0..3 {
+            for n in 0..1000 {
+                tree.insert(&key(n), &[round; 100]).unwrap();
+            }
+            pages.push(tree.meta.pages);
+            for n in 0..1000 {
+                tree.remove(&key(n)).unwrap();
+            }
+            tree.validate().unwrap();
+        }
+
+        // the later rounds fit in the pages the first one freed
+        assert!(pages[0] > 20);
+        assert_eq!(pages[1], pages[0]);
+        assert_eq!(pages[2], pages[0]);
+    }
+
+    #[test]
+    fn test_damaged_page_fails_its_checksum() {
+        let file = TempFile::new("damaged");
+
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        tree.insert(b"key", b"value").unwrap();
+        tree.close().unwrap();
+
+        let mut bytes = fs::read(&file.0).unwrap();
+        bytes[PAGE_SIZE + 100] ^= 1;
+        fs::write(&file.0, bytes).unwrap();
+
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+        assert!(matches!(tree.get(b"key"), Err(DiskError::Checksum { page: 1 })));
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let file = TempFile::new("other");
+        fs::write(&file.0, vec![7; PAGE_SIZE * 2]).unwrap();
+        assert!(matches!(DiskBPlusTree::open(&file.0), Err(DiskError::Checksum { page: 0 })));
+
+        let mut page = vec![0; PAGE_SIZE];
+        let crc = page::crc32(&page[4..]);
+        page[..4].copy_from_slice(&crc.to_le_bytes());
+        fs::write(&file.0, page).unwrap();
+        assert!(matches!(DiskBPlusTree::open(&file.0), Err(DiskError::Corrupt { page: 0, .. })));
+    }
+
+    #[test]
+    fn test_entry_too_large() {
+        let file = TempFile::new("too_large");
+        let mut tree = DiskBPlusTree::open(&file.0).unwrap();
+
+        assert!(matches!(
+            tree.insert(b"k", &vec![0; MAX_ENTRY]),
+            Err(DiskError::TooLarge { len, max: MAX_ENTRY }) if len == MAX_ENTRY + 1
+        ));
+        assert!(tree.is_empty());
+    }
+}
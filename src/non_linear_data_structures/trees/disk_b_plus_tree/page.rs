@@ -0,0 +1,317 @@
+// Page layout. Every page is PAGE_SIZE bytes and starts with the same header
+//   0..4    CRC32 of bytes 4..PAGE_SIZE
+//   4       kind: free, leaf, inner or meta
+//   8..12   next page: the next leaf for a leaf, the next free page for a free one
+//   12..14  how many entries or keys follow
+// then the body, lengths as u16 and page numbers as u32, all little endian
+//   leaf:   per entry key length | value length | key | value
+//   inner:  first child, then per key key length | key | child right of the key
+//   meta:   magic | page size | root | first leaf | free list | page count | entry count
+// and zeros up to the end of the page.
+
+use super::DiskError;
+
+pub(super) const PAGE_SIZE: usize = 4096;
+pub(super) const NIL: u32 = u32::MAX; // no page, the end of the leaf chain and the free list
+
+const HEADER: usize = 16;
+const MAGIC: &[u8; 8] = b"DSBPTREE";
+
+const FREE: u8 = 0;
+const LEAF: u8 = 1;
+const INNER: u8 = 2;
+const META: u8 = 3;
+
+pub(super) type Entry = (Vec<u8>, Vec<u8>);
+
+// A page decoded, the form it has in the buffer pool
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Node {
+    Leaf { entries: Vec<Entry>, next: u32 },
+    Inner { keys: Vec<Vec<u8>>, children: Vec<u32> },
+    Free { next: u32 },
+}
+
+impl Node {
+    // Bytes it takes on its page, a node over PAGE_SIZE has to split
+    pub(super) fn size(&self) -> usize {
+        match self {
+            Node::Leaf { entries, .. } => leaf_size(entries),
+            Node::Inner { keys, .. } => inner_size(keys),
+            Node::Free { .. } => HEADER,
+        }
+    }
+}
+
+pub(super) fn leaf_entry_size((key, value): &Entry) -> usize {
+    4 + key.len() + value.len()
+}
+
+pub(super) fn inner_key_size(key: &[u8]) -> usize {
+    2 + key.len() + 4
+}
+
+pub(super) fn leaf_size(entries: &[Entry]) -> usize {
+    HEADER + entries.iter().map(leaf_entry_size).sum::<usize>()
+}
+
+pub(super) fn inner_size(keys: &[Vec<u8>]) -> usize {
+    HEADER + 4 + keys.iter().map(|key| inner_key_size(key)).sum::<usize>()
+}
+
+// Where to cut a run of entries so both sides get about half the bytes, never leaving
+// either side empty
+pub(super) fn split_point(sizes: &[usize]) -> usize {
+    let total: usize = sizes.iter().sum();
+    let mut left = 0;
+
+    let at = sizes
+        .iter()
+        .position(|size| {
+            left += size;
+            left * 2 >= total
+        })
+        .map_or(0, |i| i + 1);
+
+    at.clamp(1, sizes.len() - 1)
+}
+
+// Where the root, the first leaf and the free list are, kept on page 0
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Meta {
+    pub(super) root: u32,
+    pub(super) first: u32,
+    pub(super) free: u32,
+    pub(super) pages: u32, // pages in the file, page 0 included
+    pub(super) len: u64,
+}
+
+pub(super) fn encode(node: &Node) -> Vec<u8> {
+    let mut page = vec![0; HEADER];
+
+    let (kind, next, count) = match node {
+        Node::Leaf { entries, next } => {
+            for (key, value) in entries {
+                page.extend((key.len() as u16).to_le_bytes());
+                page.extend((value.len() as u16).to_le_bytes());
+                page.extend(key);
+                page.extend(value);
+            }
+            (LEAF, *next, entries.len())
+        }
+        Node::Inner { keys, children } => {
+            page.extend(children[0].to_le_bytes());
+            for (key, child) in keys.iter().zip(&children[1..]) {
+                page.extend((key.len() as u16).to_le_bytes());
+                page.extend(key);
+                page.extend(child.to_le_bytes());
+            }
+            (INNER, NIL, keys.len())
+        }
+        Node::Free { next } => (FREE, *next, 0),
+    };
+
+    page[4] = kind;
+    page[8..12].copy_from_slice(&next.to_le_bytes());
+    page[12..14].copy_from_slice(&(count as u16).to_le_bytes());
+    seal(page)
+}
+
+pub(super) fn decode(id: u32, page: &[u8]) -> Result<Node, DiskError> {
+    check(id, page)?;
+
+    let mut reader = Reader { id, page, at: 8 };
+    let next = reader.u32()?;
+    let count = reader.u16()? as usize;
+    reader.at = HEADER;
+
+    match page[4] {
+        LEAF => {
+            let entries = (0..count)
+                .map(|_| {
+                    let (key, value) = (reader.u16()? as usize, reader.u16()? as usize);
+                    Ok((reader.take(key)?.to_vec(), reader.take(value)?.to_vec()))
+                })
+                .collect::<Result<_, DiskError>>()?;
+
+            Ok(Node::Leaf { entries, next })
+        }
+        INNER => {
+            let mut keys = Vec::with_capacity(count);
+            let mut children = vec![reader.u32()?];
+
+            for _ in 0..count {
+                let len = reader.u16()? as usize;
+                keys.push(reader.take(len)?.to_vec());
+                children.push(reader.u32()?);
+            }
+
+            Ok(Node::Inner { keys, children })
+        }
+        FREE => Ok(Node::Free { next }),
+        _ => Err(DiskError::Corrupt { page: id, reason: "not a tree page" }),
+    }
+}
+
+pub(super) fn encode_meta(meta: &Meta) -> Vec<u8> {
+    let mut page = vec![0; HEADER];
+    page[4] = META;
+
+    page.extend(MAGIC);
+    page.extend((PAGE_SIZE as u32).to_le_bytes());
+    page.extend(meta.root.to_le_bytes());
+    page.extend(meta.first.to_le_bytes());
+    page.extend(meta.free.to_le_bytes());
+    page.extend(meta.pages.to_le_bytes());
+    page.extend(meta.len.to_le_bytes());
+    seal(page)
+}
+
+pub(super) fn decode_meta(page: &[u8]) -> Result<Meta, DiskError> {
+    check(0, page)?;
+
+    let mut reader = Reader { id: 0, page, at: HEADER };
+    if page[4] != META || reader.take(MAGIC.len())? != MAGIC {
+        return Err(DiskError::Corrupt { page: 0, reason: "not a B+ tree file" });
+    }
+    if reader.u32()? != PAGE_SIZE as u32 {
+        return Err(DiskError::Corrupt { page: 0, reason: "written with a different page size" });
+    }
+
+    Ok(Meta {
+        root: reader.u32()?,
+        first: reader.u32()?,
+        free: reader.u32()?,
+        pages: reader.u32()?,
+        len: reader.u64()?,
+    })
+}
+
+// Pads the page out and puts the checksum in front
+fn seal(mut page: Vec<u8>) -> Vec<u8> {
+    // splitting by size is what keeps this from firing, padding a bigger page would cut it
+    // short under a valid checksum
+    assert!(page.len() <= PAGE_SIZE, "a node of {} bytes doesn't fit a page", page.len());
+
+    page.resize(PAGE_SIZE, 0);
+    let crc = crc32(&page[4..]);
+    page[..4].copy_from_slice(&crc.to_le_bytes());
+    page
+}
+
+fn check(id: u32, page: &[u8]) -> Result<(), DiskError> {
+    if page.len() != PAGE_SIZE || crc32(&page[4..]) != u32::from_le_bytes(page[..4].try_into().unwrap()) {
+        return Err(DiskError::Checksum { page: id });
+    }
+
+    Ok(())
+}
+
+// Reads the body front to back. The checksum already passed, so running past the end means
+// the page was written wrong, not damaged
+struct Reader<'a> {
+    id: u32,
+    page: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DiskError> {
+        let bytes = self.page.get(self.at..self.at + len).ok_or(DiskError::Corrupt {
+            page: self.id,
+            reason: "an entry runs past the end of the page",
+        })?;
+
+        self.at += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, DiskError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, DiskError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, DiskError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+// CRC-32 as in zlib and PNG, one table lookup per byte
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+};
+
+pub(super) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_pages_round_trip() {
+        let nodes = [
+            Node::Leaf {
+                entries: vec![(b"a".to_vec(), b"apple".to_vec()), (vec![], vec![]), (b"b".to_vec(), vec![0; 300])],
+                next: 7,
+            },
+            Node::Inner { keys: vec![b"m".to_vec(), b"t".to_vec()], children: vec![3, 4, 5] },
+            Node::Free { next: NIL },
+        ];
+
+        for node in nodes {
+            let page = encode(&node);
+            assert_eq!(page.len(), PAGE_SIZE);
+            assert_eq!(decode(9, &page).unwrap(), node);
+        }
+
+        let meta = Meta { root: 4, first: 1, free: NIL, pages: 12, len: 1 << 40 };
+        assert_eq!(decode_meta(&encode_meta(&meta)).unwrap(), meta);
+    }
+
+    #[test]
+    fn test_flipped_bit_fails_the_checksum() {
+        let mut page = encode(&Node::Leaf { entries: vec![(b"key".to_vec(), b"value".to_vec())], next: NIL });
+        page[HEADER + 5] ^= 0x10;
+
+        assert!(matches!(decode(3, &page), Err(DiskError::Checksum { page: 3 })));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit a page")]
+    fn test_oversized_node_is_not_truncated() {
+        encode(&Node::Leaf { entries: vec![(b"key".to_vec(), vec![0; PAGE_SIZE])], next: NIL });
+    }
+
+    #[test]
+    fn test_split_point() {
+        assert_eq!(split_point(&[10, 10, 10, 10]), 2);
+        assert_eq!(split_point(&[100, 1, 1, 1]), 1);
+        assert_eq!(split_point(&[1, 1, 1, 100]), 3);
+        assert_eq!(split_point(&[5, 5]), 1);
+    }
+}
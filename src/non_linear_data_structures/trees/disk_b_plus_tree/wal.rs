@@ -0,0 +1,122 @@
+// Write-ahead log. A flush appends every page it changed here, then a commit record, and
+// syncs, before any of those pages is written over in the data file. Records are
+//   kind | page | CRC32 of the record with these 4 bytes zeroed | payload
+// with a whole page as the payload of a page record and none for a commit. A crash halfway
+// through a record leaves a tail that fails its CRC, the log is read up to there.
+//
+// Dirty pages the buffer pool evicts between flushes land here too, uncommitted. Until a
+// commit record follows them they're only a place to read the page back from.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::{
+    page::{crc32, PAGE_SIZE},
+    DiskError,
+};
+
+const PAGE_RECORD: u8 = 1;
+const COMMIT_RECORD: u8 = 2;
+const RECORD_HEADER: usize = 9;
+
+pub(super) struct Wal {
+    file: File,
+    len: u64,
+}
+
+impl Wal {
+    pub(super) fn open(path: &Path) -> Result<Self, DiskError> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let len = file.metadata()?.len();
+
+        Ok(Wal { file, len })
+    }
+
+    // Returns where in the log the page image starts, for read_page
+    pub(super) fn append_page(&mut self, page: u32, bytes: &[u8]) -> Result<u64, DiskError> {
+        debug_assert_eq!(bytes.len(), PAGE_SIZE);
+
+        self.append(PAGE_RECORD, page, bytes)?;
+        Ok(self.len - PAGE_SIZE as u64)
+    }
+
+    // Makes every page appended so far survive a crash
+    pub(super) fn commit(&mut self) -> Result<(), DiskError> {
+        self.append(COMMIT_RECORD, 0, &[])?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    fn append(&mut self, kind: u8, page: u32, payload: &[u8]) -> Result<(), DiskError> {
+        let mut record = Vec::with_capacity(RECORD_HEADER + payload.len());
+        record.push(kind);
+        record.extend(page.to_le_bytes());
+        record.extend([0; 4]);
+        record.extend(payload);
+
+        let crc = crc32(&record);
+        record[5..9].copy_from_slice(&crc.to_le_bytes());
+
+        // reads move the cursor, so seek back to the end every time
+        self.file.seek(SeekFrom::Start(self.len))?;
+        self.file.write_all(&record)?;
+        self.len += record.len() as u64;
+        Ok(())
+    }
+
+    pub(super) fn read_page(&mut self, offset: u64) -> Result<Vec<u8>, DiskError> {
+        let mut page = vec![0; PAGE_SIZE];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut page)?;
+        Ok(page)
+    }
+
+    // Page images up to the last commit record with their offsets, in log order so a later
+    // image of the same page comes after an earlier one
+    pub(super) fn committed_pages(&mut self) -> Result<Vec<(u32, u64)>, DiskError> {
+        let mut log = Vec::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_end(&mut log)?;
+
+        let mut pages = Vec::new();
+        let mut committed = 0;
+        let mut at = 0;
+
+        while at + RECORD_HEADER <= log.len() {
+            let kind = log[at];
+            let end = at + RECORD_HEADER + if kind == PAGE_RECORD { PAGE_SIZE } else { 0 };
+            if !matches!(kind, PAGE_RECORD | COMMIT_RECORD) || end > log.len() {
+                break;
+            }
+
+            let mut record = log[at..end].to_vec();
+            let stored = u32::from_le_bytes(record[5..9].try_into().unwrap());
+            record[5..9].fill(0);
+            if crc32(&record) != stored {
+                break;
+            }
+
+            if kind == PAGE_RECORD {
+                let page = u32::from_le_bytes(record[1..5].try_into().unwrap());
+                pages.push((page, (at + RECORD_HEADER) as u64));
+            } else {
+                committed = pages.len();
+            }
+            at = end;
+        }
+
+        pages.truncate(committed);
+        Ok(pages)
+    }
+
+    // Once the data file has every committed page the log can start over
+    pub(super) fn clear(&mut self) -> Result<(), DiskError> {
+        self.file.set_len(0)?;
+        self.file.sync_data()?;
+        self.len = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+// What `validate` on the trees reports when the structure is broken. Every variant carries
+// the value of the node where the check failed, so a failing test points straight at it.
+
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation<T> {
+    // `node` sits on the wrong side of `ancestor`, or equals it
+    Order { node: T, ancestor: T },
+    // AVL: the stored height is not what the subtree actually has
+    Height { node: T, stored: i64, actual: i64 },
+    // AVL: the two subtrees differ in height by more than one
+    Balance { node: T, balance: i64 },
+    // Every tree but the arena one: the stored subtree size is not the number of nodes in it
+    Size { node: T, stored: usize, actual: usize },
+    // Treap: `child` has a higher priority than its parent `node`, the heap order is broken
+    Priority { node: T, child: T },
+    // B-trees: a node holds more keys than the order allows, or one below the root fewer.
+    // `node` is its first key, None if it has none at all
+    Occupancy { node: Option<T>, keys: usize },
+    // B-trees: not every leaf is at the same depth
+    LeafDepth { leaf: T, depth: usize, expected: usize },
+    // B+ tree: following the next links from `leaf` doesn't lead to the next leaf in order
+    LeafChain { leaf: T },
+    // Red-black: the root has to be black
+    RedRoot { root: T },
+    // Red-black: a red node with a red child
+    RedRed { node: T, child: T },
+    // Red-black: paths through the left and right subtree see a different number of black nodes
+    BlackHeight { node: T, left: usize, right: usize },
+    // Red-black: the child's parent link does not point back at `node` (None for the root)
+    Parent { node: Option<T>, child: T },
+}
+
+impl<T: fmt::Display> fmt::Display for InvariantViolation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::Order { node, ancestor } => {
+                write!(f, "{} is on the wrong side of its ancestor {}", node, ancestor)
+            }
+            InvariantViolation::Height { node, stored, actual } => {
+                write!(f, "{} stores height {} but its subtree is {} high", node, stored, actual)
+            }
+            InvariantViolation::Balance { node, balance } => {
+                write!(f, "{} has balance factor {}", node, balance)
+            }
+            InvariantViolation::Size { node, stored, actual } => {
+                write!(f, "{} stores size {} but its subtree has {} nodes", node, stored, actual)
+            }
+            InvariantViolation::Priority { node, child } => {
+                write!(f, "{} has a higher priority than its parent {}", child, node)
+            }
+            InvariantViolation::Occupancy { node: Some(node), keys } => {
+                write!(f, "the node starting at {} holds {} keys", node, keys)
+            }
+            InvariantViolation::Occupancy { node: None, keys } => {
+                write!(f, "a node below the root holds {} keys", keys)
+            }
+            InvariantViolation::LeafDepth { leaf, depth, expected } => write!(
+                f,
+                "the leaf starting at {} is at depth {} instead of {}",
+                leaf, depth, expected
+            ),
+            InvariantViolation::LeafChain { leaf } => {
+                write!(f, "the leaf starting at {} doesn't link to the next leaf", leaf)
+            }
+            InvariantViolation::RedRoot { root } => write!(f, "the root {} is red", root),
+            InvariantViolation::RedRed { node, child } => {
+                write!(f, "red node {} has a red child {}", node, child)
+            }
+            InvariantViolation::BlackHeight { node, left, right } => write!(
+                f,
+                "{} has black height {} on the left and {} on the right",
+                node, left, right
+            ),
+            InvariantViolation::Parent { node: Some(node), child } => {
+                write!(f, "{} is a child of {} but does not point back at it", child, node)
+            }
+            InvariantViolation::Parent { node: None, child } => {
+                write!(f, "the root {} has a parent", child)
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> std::error::Error for InvariantViolation<T> {}
+
+// Checks `value` against the bounds inherited from its ancestors, shared by all three trees
+pub(super) fn check_order<T: Ord + Clone>(
+    value: &T,
+    low: Option<&T>,
+    high: Option<&T>,
+) -> Result<(), InvariantViolation<T>> {
+    let bound = low
+        .filter(|low| value <= *low)
+        .or(high.filter(|high| value >= *high));
+
+    if let Some(bound) = bound {
+        return Err(InvariantViolation::Order {
+            node: value.clone(),
+            ancestor: bound.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_order() {
+        assert_eq!(check_order(&5, Some(&1), Some(&9)), Ok(()));
+        assert_eq!(check_order(&5, None, None), Ok(()));
+        assert_eq!(
+            check_order(&1, Some(&1), None),
+            Err(InvariantViolation::Order { node: 1, ancestor: 1 })
+        );
+        assert_eq!(
+            check_order(&12, Some(&1), Some(&9)),
+            Err(InvariantViolation::Order { node: 12, ancestor: 9 })
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let violation = InvariantViolation::RedRed { node: 4, child: 2 };
+        assert_eq!(violation.to_string(), "red node 4 has a red child 2");
+
+        let violation: InvariantViolation<i32> = InvariantViolation::Parent { node: None, child: 7 };
+        assert_eq!(violation.to_string(), "the root 7 has a parent");
+    }
+}
@@ -0,0 +1,553 @@
+// Key-value maps on top of the search trees. A map is a set of MapEntry, and an entry
+// compares by its key alone, so the trees do all the ordering and balancing exactly like
+// they do for plain values and the map only adds the key/value interface.
+//
+// The Rc red-black tree lends its values through Ref guards, and Index has to hand out a
+// plain reference, so RbMap goes through its unguarded iter instead of the usual one.
+//
+// The B-trees keep keys and values apart already, they back a map as they are.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt, iter,
+    marker::PhantomData,
+    mem,
+    ops::Index,
+};
+
+use super::{
+    avl_tree::{self, AvlTree},
+    b_plus_tree::{self, BPlusTree},
+    b_tree::{self, BTree},
+    binary_search_tree::{self, BinarySearchTree},
+    red_black_tree::{self, RedBlackTree},
+};
+
+pub type BstMap<K, V> = TreeMap<K, V, BinarySearchTree<MapEntry<K, V>>>;
+pub type AvlMap<K, V> = TreeMap<K, V, AvlTree<MapEntry<K, V>>>;
+pub type RbMap<K, V> = TreeMap<K, V, RedBlackTree<MapEntry<K, V>>>;
+pub type BMap<K, V, const B: usize = 6> = TreeMap<K, V, BTree<K, V, B>>;
+pub type BPlusMap<K, V, const B: usize = 6> = TreeMap<K, V, BPlusTree<K, V, B>>;
+
+// What the trees store for a map. There is no way to get at the key mutably, so handing
+// out the value can't break the order
+#[derive(Clone, Debug)]
+pub struct MapEntry<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K, V> MapEntry<K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+}
+
+impl<K: Ord, V> PartialEq for MapEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Ord, V> Eq for MapEntry<K, V> {}
+
+impl<K: Ord, V> PartialOrd for MapEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for MapEntry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+// Lets the trees look entries up by key with their usual get/take
+impl<K, V> Borrow<K> for MapEntry<K, V> {
+    fn borrow(&self) -> &K {
+        &self.key
+    }
+}
+
+fn pair<K, V>(entry: &MapEntry<K, V>) -> (&K, &V) {
+    (&entry.key, &entry.value)
+}
+
+fn pair_mut<K, V>(entry: &mut MapEntry<K, V>) -> (&K, &mut V) {
+    (&entry.key, &mut entry.value)
+}
+
+type Pairs<'a, I, K, V> = iter::Map<I, fn(&'a MapEntry<K, V>) -> (&'a K, &'a V)>;
+type PairsMut<'a, I, K, V> = iter::Map<I, fn(&'a mut MapEntry<K, V>) -> (&'a K, &'a mut V)>;
+
+// The handful of operations TreeMap needs from a tree of entries
+pub trait MapTree<K, V>: Default {
+    type Iter<'a>: Iterator<Item = (&'a K, &'a V)>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    type IterMut<'a>: Iterator<Item = (&'a K, &'a mut V)>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, key: &K) -> Option<&V>;
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+
+    // Only called with keys that aren't in the tree yet
+    fn insert_new(&mut self, key: K, value: V) -> &mut V;
+
+    fn remove(&mut self, key: &K) -> Option<(K, V)>;
+
+    // In key order, smallest first
+    fn iter(&self) -> Self::Iter<'_>;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
+}
+
+// The three trees have the same inherent methods for this, only the names differ
+macro_rules! map_tree {
+    ($tree:ident, $module:ident) => {
+        map_tree!($tree, $module, Iter, iter);
+    };
+    ($tree:ident, $module:ident, $iter:ident, $iter_fn:ident) => {
+        impl<K: Ord, V> MapTree<K, V> for $tree<MapEntry<K, V>> {
+            type Iter<'a> = Pairs<'a, $module::$iter<'a, MapEntry<K, V>>, K, V> where Self: 'a, K: 'a, V: 'a;
+            type IterMut<'a> = PairsMut<'a, $module::IterMut<'a, MapEntry<K, V>>, K, V> where Self: 'a, K: 'a, V: 'a;
+
+            fn len(&self) -> usize {
+                $tree::len(self)
+            }
+
+            fn get(&self, key: &K) -> Option<&V> {
+                $tree::get(self, key).map(|entry| &entry.value)
+            }
+
+            fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+                $tree::get_mut(self, key).map(|entry| &mut entry.value)
+            }
+
+            fn insert_new(&mut self, key: K, value: V) -> &mut V {
+                &mut $tree::insert_new(self, MapEntry { key, value }).value
+            }
+
+            fn remove(&mut self, key: &K) -> Option<(K, V)> {
+                $tree::take(self, key).map(|entry| (entry.key, entry.value))
+            }
+
+            fn iter(&self) -> Self::Iter<'_> {
+                $tree::$iter_fn(self).map(pair as fn(_) -> _)
+            }
+
+            fn iter_mut(&mut self) -> Self::IterMut<'_> {
+                $tree::iter_mut(self).map(pair_mut as fn(_) -> _)
+            }
+        }
+    };
+}
+
+map_tree!(BinarySearchTree, binary_search_tree);
+map_tree!(AvlTree, avl_tree);
+map_tree!(RedBlackTree, red_black_tree, UnguardedIter, iter_unguarded);
+
+// Same for the B-trees, which take keys and values separately and hand out pairs themselves
+macro_rules! b_tree_map {
+    ($tree:ident, $module:ident, $($key:tt)+) => {
+        impl<K: $($key)+, V, const B: usize> MapTree<K, V> for $tree<K, V, B> {
+            type Iter<'a> = $module::Iter<'a, K, V> where Self: 'a, K: 'a, V: 'a;
+            type IterMut<'a> = $module::IterMut<'a, K, V> where Self: 'a, K: 'a, V: 'a;
+
+            fn len(&self) -> usize {
+                $tree::len(self)
+            }
+
+            fn get(&self, key: &K) -> Option<&V> {
+                $tree::get(self, key)
+            }
+
+            fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+                $tree::get_mut(self, key)
+            }
+
+            fn insert_new(&mut self, key: K, value: V) -> &mut V {
+                $tree::insert_new(self, key, value)
+            }
+
+            fn remove(&mut self, key: &K) -> Option<(K, V)> {
+                $tree::remove_entry(self, key)
+            }
+
+            fn iter(&self) -> Self::Iter<'_> {
+                $tree::iter(self)
+            }
+
+            fn iter_mut(&mut self) -> Self::IterMut<'_> {
+                $tree::iter_mut(self)
+            }
+        }
+    };
+}
+
+b_tree_map!(BTree, b_tree, Ord);
+b_tree_map!(BPlusTree, b_plus_tree, Ord + Clone);
+
+pub struct TreeMap<K, V, S> {
+    tree: S,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K, V, S: MapTree<K, V>> TreeMap<K, V, S> {
+    pub fn new() -> Self {
+        TreeMap {
+            tree: S::default(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    // Hands back the old value if the key was there, the key itself stays the old one
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.tree.get_mut(&key) {
+            return Some(mem::replace(old, value));
+        }
+
+        self.tree.insert_new(key, value);
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry(&mut self, key: &K) -> Option<(K, V)> {
+        self.tree.remove(key)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        Entry { map: self, key }
+    }
+
+    // (key, value) pairs in key order
+    pub fn iter(&self) -> S::Iter<'_> {
+        self.tree.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> S::IterMut<'_> {
+        self.tree.iter_mut()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+}
+
+// A spot in the map for one key, filled or not
+pub struct Entry<'a, K, V, S> {
+    map: &'a mut TreeMap<K, V, S>,
+    key: K,
+}
+
+impl<'a, K, V, S: MapTree<K, V>> Entry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    // `default` only runs when the key is missing
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        let Entry { map, key } = self;
+
+        if map.tree.get(&key).is_some() {
+            return map.tree.get_mut(&key).expect("the key was just found");
+        }
+
+        map.tree.insert_new(key, default())
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, modify: F) -> Self {
+        if let Some(value) = self.map.tree.get_mut(&self.key) {
+            modify(value);
+        }
+
+        self
+    }
+}
+
+impl<K, V, S: MapTree<K, V>> Default for TreeMap<K, V, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Panics when the key is missing, like indexing a Vec out of bounds
+impl<K, V, S: MapTree<K, V>> Index<&K> for TreeMap<K, V, S> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S: MapTree<K, V>> FromIterator<(K, V)> for TreeMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// Later pairs win, like repeated insert
+impl<K, V, S: MapTree<K, V>> Extend<(K, V)> for TreeMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S: MapTree<K, V>> IntoIterator for &'a TreeMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = S::Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S: MapTree<K, V>> fmt::Debug for TreeMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_map, ModelMap};
+
+    // The same scenarios run against every map, each one only sees TreeMap
+
+    fn check_insert_get_remove<S: MapTree<i32, String>>() {
+        let mut map: TreeMap<i32, String, S> = TreeMap::new();
+        assert!(map.is_empty());
+
+        assert_eq!(map.insert(2, "two".to_string()), None);
+        assert_eq!(map.insert(1, "one".to_string()), None);
+        assert_eq!(map.insert(3, "three".to_string()), None);
+        assert_eq!(map.insert(2, "deux".to_string()), Some("two".to_string()));
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.get(&2).map(String::as_str), Some("deux"));
+        assert_eq!(map[&3], "three");
+        assert!(map.contains_key(&1));
+        assert!(!map.contains_key(&4));
+
+        map.get_mut(&1).unwrap().push('!');
+        assert_eq!(map[&1], "one!");
+
+        assert_eq!(map.remove(&2), Some("deux".to_string()));
+        assert_eq!(map.remove(&2), None);
+        assert_eq!(map.remove_entry(&3), Some((3, "three".to_string())));
+        assert_eq!(map.len(), 1);
+    }
+
+    fn check_entry<S: MapTree<String, usize>>() {
+        let mut counts: TreeMap<String, usize, S> = TreeMap::new();
+
+        for word in "the cat and the hat and the bat".split(' ') {
+            *counts.entry(word.to_string()).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts[&"the".to_string()], 3);
+        assert_eq!(counts[&"and".to_string()], 2);
+        assert_eq!(counts.len(), 5);
+
+        // the default only runs for missing keys
+        let mut calls = 0;
+        counts.entry("cat".to_string()).or_insert_with(|| {
+            calls += 1;
+            100
+        });
+        *counts.entry("dog".to_string()).or_insert_with(|| {
+            calls += 1;
+            100
+        }) += 1;
+        assert_eq!(calls, 1);
+        assert_eq!(counts[&"dog".to_string()], 101);
+
+        counts.entry("bat".to_string()).and_modify(|count| *count += 10).or_default();
+        counts.entry("eel".to_string()).and_modify(|count| *count += 10).or_default();
+        assert_eq!(counts[&"bat".to_string()], 11);
+        assert_eq!(counts[&"eel".to_string()], 0);
+
+        let entry = counts.entry("fox".to_string());
+        assert_eq!(entry.key(), "fox");
+    }
+
+    fn check_iteration<S: MapTree<i32, i32>>() {
+        let mut map: TreeMap<i32, i32, S> = [(5, 50), (1, 10), (3, 30), (4, 40), (2, 20)].into_iter().collect();
+
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), vec![10, 20, 30, 40, 50]);
+
+        for value in map.values_mut() {
+            *value += 1;
+        }
+        for (key, value) in map.iter_mut() {
+            *value += key;
+        }
+
+        assert_eq!(
+            (&map).into_iter().map(|(&key, &value)| (key, value)).collect::<Vec<_>>(),
+            vec![(1, 12), (2, 23), (3, 34), (4, 45), (5, 56)]
+        );
+        assert_eq!(format!("{:?}", map), "{1: 12, 2: 23, 3: 34, 4: 45, 5: 56}");
+    }
+
+    fn check_missing_index_panics<S: MapTree<i32, i32>>() {
+        let map: TreeMap<i32, i32, S> = [(1, 1)].into_iter().collect();
+        let _ = map[&2];
+    }
+
+    impl<S: MapTree<i32, i32>> ModelMap for TreeMap<i32, i32, S> {
+        fn insert(&mut self, key: i32, value: i32) -> Option<i32> {
+            TreeMap::insert(self, key, value)
+        }
+
+        fn remove(&mut self, key: i32) -> Option<i32> {
+            TreeMap::remove(self, &key)
+        }
+
+        // through entry, so the vacant and occupied paths both get hit
+        fn increment(&mut self, key: i32) {
+            *self.entry(key).or_insert(0) += 1;
+        }
+
+        fn get(&self, key: i32) -> Option<i32> {
+            TreeMap::get(self, &key).copied()
+        }
+
+        fn entries(&self) -> Vec<(i32, i32)> {
+            self.iter().map(|(&key, &value)| (key, value)).collect()
+        }
+
+        fn len(&self) -> usize {
+            TreeMap::len(self)
+        }
+    }
+
+    // `Tree<K, V>` is the tree behind the map, so the same tests can give it different keys
+    macro_rules! map_tests {
+        ($name:ident, $k:ident, $v:ident => $tree:ty) => {
+            mod $name {
+                use super::*;
+
+                type Tree<$k, $v> = $tree;
+
+                #[test]
+                fn insert_get_remove() {
+                    check_insert_get_remove::<Tree<i32, String>>();
+                }
+
+                #[test]
+                fn entry() {
+                    check_entry::<Tree<String, usize>>();
+                }
+
+                #[test]
+                fn iteration() {
+                    check_iteration::<Tree<i32, i32>>();
+                }
+
+                #[test]
+                #[should_panic(expected = "no entry found for key")]
+                fn missing_index_panics() {
+                    check_missing_index_panics::<Tree<i32, i32>>();
+                }
+
+                #[test]
+                fn matches_btree_map() {
+                    check_map::<TreeMap<i32, i32, Tree<i32, i32>>>(concat!(stringify!($name), " against BTreeMap"));
+                }
+            }
+        };
+    }
+
+    map_tests!(bst_map, K, V => BinarySearchTree<MapEntry<K, V>>);
+    map_tests!(avl_map, K, V => AvlTree<MapEntry<K, V>>);
+    map_tests!(rb_map, K, V => RedBlackTree<MapEntry<K, V>>);
+    // the smallest order, so even the short scenarios split and merge nodes
+    map_tests!(b_tree_map, K, V => BTree<K, V, 2>);
+    map_tests!(b_plus_tree_map, K, V => BPlusTree<K, V, 2>);
+
+    #[test]
+    fn test_aliases() {
+        let mut bst: BstMap<&str, i32> = BstMap::new();
+        let mut avl: AvlMap<&str, i32> = AvlMap::new();
+        let mut rb: RbMap<&str, i32> = RbMap::new();
+        let mut b: BMap<&str, i32> = BMap::new();
+        let mut b_plus: BPlusMap<&str, i32, 3> = BPlusMap::new();
+
+        bst.insert("a", 1);
+        avl.insert("a", 1);
+        rb.insert("a", 1);
+        b.insert("a", 1);
+        b_plus.insert("a", 1);
+
+        assert_eq!((bst[&"a"], avl[&"a"], rb[&"a"]), (1, 1, 1));
+        assert_eq!((b[&"a"], b_plus[&"a"]), (1, 1));
+    }
+}
@@ -1,4 +1,19 @@
 pub mod binary_tree;
 pub mod binary_search_tree;
 pub mod avl_tree;
-pub mod red_black_tree;
\ No newline at end of file
+pub mod red_black_tree;
+pub mod arena_red_black_tree;
+pub mod splay_tree;
+pub mod treap;
+pub mod b_tree;
+pub mod b_plus_tree;
+pub mod disk_b_plus_tree;
+pub mod ordered_set;
+pub mod invariant;
+pub mod map;
+
+mod bulk;
+mod descent;
+
+#[cfg(test)]
+mod model;
\ No newline at end of file
@@ -0,0 +1,211 @@
+// Model test shared by the search trees, each tree replays the same random inserts and
+// deletes as a BTreeSet and has to hold the same values in the same order afterwards,
+// with its own invariants still intact. The maps get the same treatment against a BTreeMap.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::property::{check, ensure_eq, shrink_value, Operation, Rng};
+
+use super::invariant::InvariantViolation;
+
+pub(super) trait ModelSet: Default {
+    // false if the value was already there
+    fn insert(&mut self, value: i32) -> bool;
+
+    // false if the value wasn't there
+    fn delete(&mut self, value: i32) -> bool;
+
+    // In order walk of the tree
+    fn values(&self) -> Vec<i32>;
+
+    fn contains(&self, value: i32) -> bool {
+        self.values().contains(&value)
+    }
+
+    fn len(&self) -> usize {
+        self.values().len()
+    }
+
+    fn min(&self) -> Option<i32> {
+        self.values().first().copied()
+    }
+
+    fn max(&self) -> Option<i32> {
+        self.values().last().copied()
+    }
+
+    // Values between low and high, both included
+    fn range(&self, low: i32, high: i32) -> Vec<i32> {
+        self.values().into_iter().filter(|value| (low..=high).contains(value)).collect()
+    }
+
+    fn validate(&self) -> Result<(), InvariantViolation<i32>>;
+}
+
+// Small enough range that deletes and duplicate inserts hit existing values
+const MAX_VALUE: i32 = 31;
+
+#[derive(Clone, Debug)]
+pub(super) enum SetOp {
+    Insert(i32),
+    Delete(i32),
+    Contains(i32),
+    // the bounds can come in either order, the low one is the smaller
+    Range(i32, i32),
+}
+
+impl Operation for SetOp {
+    fn generate(rng: &mut Rng) -> Self {
+        match rng.below(7) {
+            0..=2 => SetOp::Insert(rng.value(MAX_VALUE)),
+            3 | 4 => SetOp::Delete(rng.value(MAX_VALUE)),
+            5 => SetOp::Contains(rng.value(MAX_VALUE)),
+            _ => SetOp::Range(rng.value(MAX_VALUE), rng.value(MAX_VALUE)),
+        }
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        match *self {
+            SetOp::Insert(value) => shrink_value(value).into_iter().map(SetOp::Insert).collect(),
+            SetOp::Delete(value) => shrink_value(value).into_iter().map(SetOp::Delete).collect(),
+            SetOp::Contains(value) => shrink_value(value).into_iter().map(SetOp::Contains).collect(),
+            SetOp::Range(low, high) => shrink_value(low)
+                .into_iter()
+                .map(|low| SetOp::Range(low, high))
+                .chain(shrink_value(high).into_iter().map(|high| SetOp::Range(low, high)))
+                .collect(),
+        }
+    }
+}
+
+pub(super) fn check_set<S: ModelSet>(name: &str) {
+    check::<SetOp, _>(name, |ops| {
+        let mut tree = S::default();
+        let mut model: BTreeSet<i32> = BTreeSet::new();
+
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                SetOp::Insert(value) => {
+                    ensure_eq!(tree.insert(value), model.insert(value), "step {} insert({})", step, value);
+                }
+                SetOp::Delete(value) => {
+                    ensure_eq!(tree.delete(value), model.remove(&value), "step {} delete({})", step, value);
+                }
+                SetOp::Contains(value) => {
+                    ensure_eq!(tree.contains(value), model.contains(&value), "step {} contains({})", step, value);
+                }
+                SetOp::Range(low, high) => {
+                    let (low, high) = (low.min(high), low.max(high));
+                    ensure_eq!(
+                        tree.range(low, high),
+                        model.range(low..=high).copied().collect::<Vec<_>>(),
+                        "step {} range({}, {})",
+                        step,
+                        low,
+                        high
+                    );
+                }
+            }
+
+            ensure_eq!(tree.values(), model.iter().copied().collect::<Vec<_>>(), "step {} values", step);
+            ensure_eq!(tree.len(), model.len(), "step {} len", step);
+            ensure_eq!(tree.min(), model.first().copied(), "step {} min", step);
+            ensure_eq!(tree.max(), model.last().copied(), "step {} max", step);
+
+            if let Err(violation) = tree.validate() {
+                return Err(format!("step {} broke the tree: {}", step, violation));
+            }
+        }
+
+        Ok(())
+    });
+}
+
+pub(super) trait ModelMap: Default {
+    // The value that was there before, if any
+    fn insert(&mut self, key: i32, value: i32) -> Option<i32>;
+
+    fn remove(&mut self, key: i32) -> Option<i32>;
+
+    // Adds one to the value under key, a missing key starts from 0
+    fn increment(&mut self, key: i32);
+
+    fn get(&self, key: i32) -> Option<i32>;
+
+    // In key order
+    fn entries(&self) -> Vec<(i32, i32)>;
+
+    fn len(&self) -> usize {
+        self.entries().len()
+    }
+}
+
+// Fewer keys than values, so removes and overwrites actually hit something
+const MAX_KEY: i32 = 15;
+
+#[derive(Clone, Debug)]
+pub(super) enum MapOp {
+    Insert(i32, i32),
+    Remove(i32),
+    Increment(i32),
+    Get(i32),
+}
+
+impl Operation for MapOp {
+    fn generate(rng: &mut Rng) -> Self {
+        match rng.below(8) {
+            0..=2 => MapOp::Insert(rng.value(MAX_KEY), rng.value(99)),
+            3 | 4 => MapOp::Remove(rng.value(MAX_KEY)),
+            5 | 6 => MapOp::Increment(rng.value(MAX_KEY)),
+            _ => MapOp::Get(rng.value(MAX_KEY)),
+        }
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        match *self {
+            MapOp::Insert(key, value) => shrink_value(key)
+                .into_iter()
+                .map(|key| MapOp::Insert(key, value))
+                .chain(shrink_value(value).into_iter().map(|value| MapOp::Insert(key, value)))
+                .collect(),
+            MapOp::Remove(key) => shrink_value(key).into_iter().map(MapOp::Remove).collect(),
+            MapOp::Increment(key) => shrink_value(key).into_iter().map(MapOp::Increment).collect(),
+            MapOp::Get(key) => shrink_value(key).into_iter().map(MapOp::Get).collect(),
+        }
+    }
+}
+
+pub(super) fn check_map<M: ModelMap>(name: &str) {
+    check::<MapOp, _>(name, |ops| {
+        let mut map = M::default();
+        let mut model: BTreeMap<i32, i32> = BTreeMap::new();
+
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                MapOp::Insert(key, value) => {
+                    ensure_eq!(map.insert(key, value), model.insert(key, value), "step {} insert({})", step, key);
+                }
+                MapOp::Remove(key) => {
+                    ensure_eq!(map.remove(key), model.remove(&key), "step {} remove({})", step, key);
+                }
+                MapOp::Increment(key) => {
+                    map.increment(key);
+                    *model.entry(key).or_insert(0) += 1;
+                }
+                MapOp::Get(key) => {
+                    ensure_eq!(map.get(key), model.get(&key).copied(), "step {} get({})", step, key);
+                }
+            }
+
+            ensure_eq!(
+                map.entries(),
+                model.iter().map(|(&key, &value)| (key, value)).collect::<Vec<_>>(),
+                "step {} entries",
+                step
+            );
+            ensure_eq!(map.len(), model.len(), "step {} len", step);
+        }
+
+        Ok(())
+    });
+}
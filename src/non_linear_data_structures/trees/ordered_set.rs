@@ -0,0 +1,553 @@
+// One interface over the search trees, so code that only needs a sorted set can swap one
+// tree for another. Same idea as Sequence for the lists: the inherent methods keep their
+// own names (delete, take, first, last...), the trait is what makes them line up.
+//
+// The Rc red-black tree can only lend its values through Ref guards, so `min`, `max`,
+// `iter` and `range` yield anything that derefs to T instead of plain references.
+//
+// The B-trees are maps, as sets they hold () for every value.
+
+use std::{
+    cell::Ref,
+    iter,
+    ops::{Deref, RangeBounds},
+};
+
+use super::{
+    arena_red_black_tree::{self, ArenaRedBlackTree},
+    avl_tree::{self, AvlTree},
+    b_plus_tree::{self, BPlusTree},
+    b_tree::{self, BTree},
+    binary_search_tree::{self, BinarySearchTree},
+    red_black_tree::{self, RedBlackTree},
+};
+
+pub trait OrderedSet<T: Ord>: Default {
+    type Item<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    type Iter<'a>: Iterator<Item = Self::Item<'a>>
+    where
+        Self: 'a;
+
+    type Range<'a>: Iterator<Item = Self::Item<'a>>
+    where
+        Self: 'a;
+
+    // false if the value was already there, the set is left as it was
+    fn insert(&mut self, value: T) -> bool;
+
+    fn remove(&mut self, value: &T) -> Option<T>;
+
+    fn contains(&self, value: &T) -> bool;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn min(&self) -> Option<Self::Item<'_>>;
+
+    fn max(&self) -> Option<Self::Item<'_>>;
+
+    // Smallest first
+    fn iter(&self) -> Self::Iter<'_>;
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_>;
+
+    // Largest value that is <= `value`. These four walk the values by default, the trees
+    // that keep subtree sizes answer them with one descent instead
+    fn floor(&self, value: &T) -> Option<Self::Item<'_>> {
+        self.range(..=value).last()
+    }
+
+    // Smallest value that is >= `value`
+    fn ceiling(&self, value: &T) -> Option<Self::Item<'_>> {
+        self.range(value..).next()
+    }
+
+    // How many values are smaller than `value`, whether or not it is in the set
+    fn rank(&self, value: &T) -> usize {
+        self.range(..value).count()
+    }
+
+    // The k-th smallest value counting from 0
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        self.iter().nth(k)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for BinarySearchTree<T> {
+    type Item<'a> = &'a T where Self: 'a;
+    type Iter<'a> = binary_search_tree::Iter<'a, T> where Self: 'a;
+    type Range<'a> = binary_search_tree::Range<'a, T> where Self: 'a;
+
+    fn insert(&mut self, value: T) -> bool {
+        BinarySearchTree::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> Option<T> {
+        self.take(value)
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        self.get(value).is_some()
+    }
+
+    fn len(&self) -> usize {
+        BinarySearchTree::len(self)
+    }
+
+    fn min(&self) -> Option<&T> {
+        self.first()
+    }
+
+    fn max(&self) -> Option<&T> {
+        self.last()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        BinarySearchTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_> {
+        BinarySearchTree::range(self, range)
+    }
+
+    fn floor(&self, value: &T) -> Option<&T> {
+        BinarySearchTree::floor(self, value)
+    }
+
+    fn ceiling(&self, value: &T) -> Option<&T> {
+        BinarySearchTree::ceiling(self, value)
+    }
+
+    fn rank(&self, value: &T) -> usize {
+        BinarySearchTree::rank(self, value)
+    }
+
+    fn select(&self, k: usize) -> Option<&T> {
+        BinarySearchTree::select(self, k)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for AvlTree<T> {
+    type Item<'a> = &'a T where Self: 'a;
+    type Iter<'a> = avl_tree::Iter<'a, T> where Self: 'a;
+    type Range<'a> = avl_tree::Range<'a, T> where Self: 'a;
+
+    fn insert(&mut self, value: T) -> bool {
+        AvlTree::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> Option<T> {
+        self.take(value)
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        AvlTree::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        AvlTree::len(self)
+    }
+
+    fn min(&self) -> Option<&T> {
+        self.first()
+    }
+
+    fn max(&self) -> Option<&T> {
+        self.last()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        AvlTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_> {
+        AvlTree::range(self, range)
+    }
+
+    fn floor(&self, value: &T) -> Option<&T> {
+        AvlTree::floor(self, value)
+    }
+
+    fn ceiling(&self, value: &T) -> Option<&T> {
+        AvlTree::ceiling(self, value)
+    }
+
+    fn rank(&self, value: &T) -> usize {
+        AvlTree::rank(self, value)
+    }
+
+    fn select(&self, k: usize) -> Option<&T> {
+        AvlTree::select(self, k)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for RedBlackTree<T> {
+    type Item<'a> = Ref<'a, T> where Self: 'a;
+    type Iter<'a> = red_black_tree::Iter<'a, T> where Self: 'a;
+    type Range<'a> = red_black_tree::Range<'a, T> where Self: 'a;
+
+    fn insert(&mut self, value: T) -> bool {
+        RedBlackTree::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> Option<T> {
+        self.take(value)
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        RedBlackTree::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        RedBlackTree::len(self)
+    }
+
+    fn min(&self) -> Option<Ref<'_, T>> {
+        RedBlackTree::min(self)
+    }
+
+    fn max(&self) -> Option<Ref<'_, T>> {
+        RedBlackTree::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        RedBlackTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_> {
+        RedBlackTree::range(self, range)
+    }
+
+    fn floor(&self, value: &T) -> Option<Ref<'_, T>> {
+        RedBlackTree::floor(self, value)
+    }
+
+    fn ceiling(&self, value: &T) -> Option<Ref<'_, T>> {
+        RedBlackTree::ceiling(self, value)
+    }
+
+    fn rank(&self, value: &T) -> usize {
+        RedBlackTree::rank(self, value)
+    }
+
+    fn select(&self, k: usize) -> Option<Ref<'_, T>> {
+        RedBlackTree::select(self, k)
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for ArenaRedBlackTree<T> {
+    type Item<'a> = &'a T where Self: 'a;
+    type Iter<'a> = arena_red_black_tree::Iter<'a, T> where Self: 'a;
+    type Range<'a> = arena_red_black_tree::Range<'a, T> where Self: 'a;
+
+    fn insert(&mut self, value: T) -> bool {
+        ArenaRedBlackTree::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> Option<T> {
+        self.take(value)
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        ArenaRedBlackTree::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        ArenaRedBlackTree::len(self)
+    }
+
+    fn min(&self) -> Option<&T> {
+        ArenaRedBlackTree::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        ArenaRedBlackTree::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        ArenaRedBlackTree::iter(self)
+    }
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_> {
+        ArenaRedBlackTree::range(self, range)
+    }
+}
+
+// A B-tree's entries with the () dropped
+type Keys<'a, I, T> = iter::Map<I, fn((&'a T, &'a ())) -> &'a T>;
+
+fn key<'a, T>((key, _): (&'a T, &'a ())) -> &'a T {
+    key
+}
+
+macro_rules! b_tree_set {
+    ($tree:ident, $module:ident, $($value:tt)+) => {
+        impl<T: $($value)+, const B: usize> OrderedSet<T> for $tree<T, (), B> {
+            type Item<'a> = &'a T where Self: 'a;
+            type Iter<'a> = Keys<'a, $module::Iter<'a, T, ()>, T> where Self: 'a;
+            type Range<'a> = Keys<'a, $module::Range<'a, T, ()>, T> where Self: 'a;
+
+            fn insert(&mut self, value: T) -> bool {
+                $tree::insert(self, value, ()).is_none()
+            }
+
+            fn remove(&mut self, value: &T) -> Option<T> {
+                self.remove_entry(value).map(|(value, _)| value)
+            }
+
+            fn contains(&self, value: &T) -> bool {
+                self.contains_key(value)
+            }
+
+            fn len(&self) -> usize {
+                $tree::len(self)
+            }
+
+            fn min(&self) -> Option<&T> {
+                self.first_key_value().map(|(value, _)| value)
+            }
+
+            fn max(&self) -> Option<&T> {
+                self.last_key_value().map(|(value, _)| value)
+            }
+
+            fn iter(&self) -> Self::Iter<'_> {
+                $tree::iter(self).map(key as fn(_) -> _)
+            }
+
+            fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_> {
+                $tree::range(self, range).map(key as fn(_) -> _)
+            }
+        }
+    };
+}
+
+b_tree_set!(BTree, b_tree, Ord);
+b_tree_set!(BPlusTree, b_plus_tree, Ord + Clone);
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::*;
+    use crate::non_linear_data_structures::trees::{
+        invariant::InvariantViolation,
+        model::{check_set, ModelSet},
+    };
+
+    // The same scenarios run against every tree, each one only sees the trait
+
+    fn collect<S: OrderedSet<i32>>(set: &S) -> Vec<i32> {
+        set.iter().map(|item| *item).collect()
+    }
+
+    fn collect_range<S: OrderedSet<i32>, R: RangeBounds<i32>>(set: &S, range: R) -> Vec<i32> {
+        set.range(range).map(|item| *item).collect()
+    }
+
+    fn check_empty<S: OrderedSet<i32>>() {
+        let mut set = S::default();
+
+        assert!(set.is_empty());
+        assert_eq!(set.len(), 0);
+        assert!(!set.contains(&1));
+        assert_eq!(set.remove(&1), None);
+        assert!(set.min().is_none());
+        assert!(set.max().is_none());
+        assert_eq!(collect(&set), Vec::<i32>::new());
+        assert_eq!(collect_range(&set, ..), Vec::<i32>::new());
+    }
+
+    fn check_insert_and_remove<S: OrderedSet<i32>>() {
+        let mut set = S::default();
+
+        for i in [5, 3, 8, 1, 4, 7, 9] {
+            assert!(set.insert(i));
+        }
+
+        assert!(!set.insert(4));
+        assert_eq!(set.len(), 7);
+        assert_eq!(collect(&set), vec![1, 3, 4, 5, 7, 8, 9]);
+
+        assert!(set.contains(&7));
+        assert!(!set.contains(&6));
+
+        assert_eq!(set.remove(&5), Some(5));
+        assert_eq!(set.remove(&5), None);
+        assert_eq!(set.remove(&1), Some(1));
+        assert_eq!(set.len(), 5);
+        assert_eq!(collect(&set), vec![3, 4, 7, 8, 9]);
+
+        for i in [3, 4, 7, 8, 9] {
+            assert_eq!(set.remove(&i), Some(i));
+        }
+
+        assert!(set.is_empty());
+
+        // still usable after being emptied
+        set.insert(2);
+        assert_eq!(collect(&set), vec![2]);
+    }
+
+    fn check_min_and_max<S: OrderedSet<i32>>() {
+        let mut set = S::default();
+
+        for i in [50, 20, 80, 10, 90, 60] {
+            set.insert(i);
+        }
+
+        assert_eq!(set.min().map(|min| *min), Some(10));
+        assert_eq!(set.max().map(|max| *max), Some(90));
+
+        set.remove(&10);
+        set.remove(&90);
+        assert_eq!(set.min().map(|min| *min), Some(20));
+        assert_eq!(set.max().map(|max| *max), Some(80));
+    }
+
+    fn check_range<S: OrderedSet<i32>>() {
+        let mut set = S::default();
+
+        for i in (0..20).map(|i| i * 2) {
+            set.insert(i);
+        }
+
+        assert_eq!(collect_range(&set, 5..11), vec![6, 8, 10]);
+        assert_eq!(collect_range(&set, 6..=10), vec![6, 8, 10]);
+        assert_eq!(collect_range(&set, ..4), vec![0, 2]);
+        assert_eq!(collect_range(&set, 35..), vec![36, 38]);
+        assert_eq!(collect_range(&set, 7..8), Vec::<i32>::new());
+        assert_eq!(collect_range(&set, 50..), Vec::<i32>::new());
+        assert_eq!(
+            collect_range(&set, (Bound::Excluded(30), Bound::Unbounded)),
+            vec![32, 34, 36, 38]
+        );
+        assert_eq!(collect_range(&set, ..).len(), 20);
+    }
+
+    fn check_floor_ceiling_rank_select<S: OrderedSet<i32>>() {
+        let mut set = S::default();
+        // scattered so the tree gets some depth on both sides, then a few holes
+        for i in 0..40 {
+            set.insert((i * 17) % 40 * 2);
+        }
+        for value in [0, 22, 40, 78] {
+            set.remove(&value);
+        }
+
+        let sorted = collect(&set);
+
+        for x in -1..81 {
+            let floor = sorted.iter().rev().find(|&&value| value <= x).copied();
+            let ceiling = sorted.iter().find(|&&value| value >= x).copied();
+            let rank = sorted.iter().filter(|&&value| value < x).count();
+
+            assert_eq!(set.floor(&x).map(|value| *value), floor, "floor({})", x);
+            assert_eq!(set.ceiling(&x).map(|value| *value), ceiling, "ceiling({})", x);
+            assert_eq!(set.rank(&x), rank, "rank({})", x);
+        }
+
+        for (k, value) in sorted.iter().enumerate() {
+            assert_eq!(set.select(k).map(|value| *value), Some(*value));
+            assert_eq!(set.rank(value), k);
+        }
+        assert!(set.select(sorted.len()).is_none());
+    }
+
+    // Drives the tree through the trait alone, so the model test checks the trait methods
+    // and not the inherent ones. The invariants still come from the tree's own ModelSet.
+    #[derive(Default)]
+    struct ThroughTrait<S>(S);
+
+    impl<S: OrderedSet<i32> + ModelSet> ModelSet for ThroughTrait<S> {
+        fn insert(&mut self, value: i32) -> bool {
+            OrderedSet::insert(&mut self.0, value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            OrderedSet::remove(&mut self.0, &value).is_some()
+        }
+
+        fn values(&self) -> Vec<i32> {
+            collect(&self.0)
+        }
+
+        fn contains(&self, value: i32) -> bool {
+            OrderedSet::contains(&self.0, &value)
+        }
+
+        fn len(&self) -> usize {
+            OrderedSet::len(&self.0)
+        }
+
+        fn min(&self) -> Option<i32> {
+            OrderedSet::min(&self.0).map(|min| *min)
+        }
+
+        fn max(&self) -> Option<i32> {
+            OrderedSet::max(&self.0).map(|max| *max)
+        }
+
+        fn range(&self, low: i32, high: i32) -> Vec<i32> {
+            collect_range(&self.0, low..=high)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            ModelSet::validate(&self.0)
+        }
+    }
+
+    macro_rules! ordered_set_tests {
+        ($name:ident, $set:ty) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn empty() {
+                    check_empty::<$set>();
+                }
+
+                #[test]
+                fn insert_and_remove() {
+                    check_insert_and_remove::<$set>();
+                }
+
+                #[test]
+                fn min_and_max() {
+                    check_min_and_max::<$set>();
+                }
+
+                #[test]
+                fn range() {
+                    check_range::<$set>();
+                }
+
+                #[test]
+                fn floor_ceiling_rank_select() {
+                    check_floor_ceiling_rank_select::<$set>();
+                }
+
+                #[test]
+                fn against_btree_set() {
+                    check_set::<ThroughTrait<$set>>(concat!(
+                        stringify!($name),
+                        " against BTreeSet"
+                    ));
+                }
+            }
+        };
+    }
+
+    ordered_set_tests!(binary_search_tree, BinarySearchTree<i32>);
+    ordered_set_tests!(avl_tree, AvlTree<i32>);
+    ordered_set_tests!(red_black_tree, RedBlackTree<i32>);
+    ordered_set_tests!(arena_red_black_tree, ArenaRedBlackTree<i32>);
+    ordered_set_tests!(b_tree, BTree<i32, (), 2>);
+    ordered_set_tests!(b_plus_tree, BPlusTree<i32, (), 2>);
+}
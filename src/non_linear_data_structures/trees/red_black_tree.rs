@@ -1,10 +1,19 @@
-use core::fmt;
 use std::{
-    cell::RefCell,
+    cell::{Ref, RefCell},
     cmp::Ordering,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
     rc::{Rc, Weak},
+    vec,
 };
 
+use super::{
+    bulk,
+    descent::{self, SearchNode},
+    invariant::{check_order, InvariantViolation},
+};
+use crate::node_ref::node_ref;
+
 #[derive(Clone)]
 pub struct Node<T> {
     value: T,
@@ -12,6 +21,7 @@ pub struct Node<T> {
     left: Option<Rc<RefCell<Node<T>>>>,
     right: Option<Rc<RefCell<Node<T>>>>,
     parent: Option<Weak<RefCell<Node<T>>>>,
+    size: usize, // nodes in this subtree, for rank and select
 }
 
 impl<T> Node<T> {
@@ -22,30 +32,316 @@ impl<T> Node<T> {
             right: None,
             is_red: true,
             parent: None,
+            size: 1,
         }
     }
 }
 
+// The descents get at the children and values through short lived borrows, like the
+// rest of the tree does
+impl<T> SearchNode for RefCell<Node<T>> {
+    type Value = T;
+
+    fn left(&self) -> Option<&Self> {
+        left_ref(self)
+    }
+
+    fn right(&self) -> Option<&Self> {
+        right_ref(self)
+    }
+
+    fn size(&self) -> usize {
+        self.borrow().size
+    }
+
+    fn compare<Q>(&self, value: &Q) -> Ordering
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        value.cmp(&key(self))
+    }
+}
+
 pub struct RedBlackTree<T> {
     root: Option<Rc<RefCell<Node<T>>>>,
+    len: usize,
 }
 
-impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
+impl<T> RedBlackTree<T> {
     pub fn new() -> Self {
-        RedBlackTree { root: None }
+        RedBlackTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(value).is_some()
+    }
+
+    // Only for the maps, which need a plain reference for Index
+    pub(super) fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(value).map(value_unguarded)
+    }
+
+    // Only for the maps, which never touch the key part of a value through it. Anything else
+    // could move a value out of its place in the order
+    pub(super) fn get_mut<Q>(&mut self, value: &Q) -> Option<&mut T>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // SAFETY: `&mut self` means no Ref guard or plain reference into the tree is alive
+        self.find(value).map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn min(&self) -> Option<Ref<'_, T>> {
+        self.root_ref().map(leftmost).map(value_ref)
+    }
+
+    pub fn max(&self) -> Option<Ref<'_, T>> {
+        self.root_ref().map(rightmost).map(value_ref)
+    }
+
+    // Smallest value greater than `value`, which doesn't have to be in the tree itself
+    pub fn successor<Q>(&self, value: &Q) -> Option<Ref<'_, T>>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::lower_bound(self.root_ref(), Bound::Excluded(value)).map(value_ref)
+    }
+
+    // Largest value less than `value`
+    pub fn predecessor<Q>(&self, value: &Q) -> Option<Ref<'_, T>>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::upper_bound(self.root_ref(), Bound::Excluded(value)).map(value_ref)
+    }
+
+    // Largest value that is <= `value`
+    pub fn floor<Q>(&self, value: &Q) -> Option<Ref<'_, T>>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::floor(self.root_ref(), value).map(value_ref)
+    }
+
+    // Smallest value that is >= `value`
+    pub fn ceiling<Q>(&self, value: &Q) -> Option<Ref<'_, T>>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::ceiling(self.root_ref(), value).map(value_ref)
+    }
+
+    // How many values are smaller than `value`, whether or not it is in the tree.
+    // Sizes make that O(log n)
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        descent::rank(self.root_ref(), value)
+    }
+
+    // The k-th smallest value counting from 0, so select(rank(x)) is x when x is in the tree
+    pub fn select(&self, k: usize) -> Option<Ref<'_, T>> {
+        descent::select(self.root_ref(), k).map(value_ref)
+    }
+
+    // In order, walks the parent links so no stack is needed
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.root_ref().map(leftmost),
+            next_back: self.root_ref().map(rightmost),
+            remaining: self.len,
+        }
+    }
+
+    // Values inside `range` in order, `tree.range(3..7)` works like it does on BTreeSet
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let first = descent::lower_bound(self.root_ref(), range.start_bound());
+        let last = descent::upper_bound(self.root_ref(), range.end_bound());
+
+        // an empty range leaves `last` before `first`
+        let empty = match (first, last) {
+            (Some(first), Some(last)) => *key::<T, Q>(first) > *key(last),
+            _ => true,
+        };
+
+        if empty {
+            Range { next: None, next_back: None }
+        } else {
+            Range { next: first, next_back: last }
+        }
+    }
+
+    // Same order as `iter` with plain references, only for the maps
+    pub(super) fn iter_unguarded(&self) -> UnguardedIter<'_, T> {
+        UnguardedIter(self.iter())
+    }
+
+    // The order gets worked out first and only then are the values handed out, so no node
+    // gets borrowed again to find the next one while its value is lent out mutably
+    pub(super) fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut iter = self.iter();
+
+        while let Some(node) = iter.step() {
+            nodes.push(node.as_ptr());
+        }
+
+        IterMut {
+            nodes: nodes.into_iter(),
+            marker: PhantomData,
+        }
+    }
+
+    fn root_ref(&self) -> Option<&RefCell<Node<T>>> {
+        self.root.as_deref()
+    }
+
+    fn find<Q>(&self, value: &Q) -> Option<&RefCell<Node<T>>>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root_ref();
+
+        while let Some(node) = current {
+            current = match value.cmp(&key(node)) {
+                Ordering::Less => left_ref(node),
+                Ordering::Greater => right_ref(node),
+                Ordering::Equal => return Some(node),
+            };
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+impl<T: Clone> RedBlackTree<T> {
+    // Pre-order values with their colors, lets the arena tree tests compare shapes
+    pub(super) fn shape(&self) -> Vec<(T, bool)> {
+        let mut shape = vec![];
+        let mut stack = vec![self.root_ref()];
+
+        while let Some(node) = stack.pop() {
+            if let Some(node) = node {
+                shape.push((node.borrow().value.clone(), node.borrow().is_red));
+                stack.push(right_ref(node));
+                stack.push(left_ref(node));
+            }
+        }
+
+        shape
+    }
+}
+
+impl<T: Ord> RedBlackTree<T> {
+    // Builds a balanced tree in O(n) with no fix-ups, only the last partly filled level is
+    // red. Equal neighbours are kept once, panics if the values aren't ascending
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted(bulk::expect_sorted(iter))
+    }
+
+    // `values` has to be ascending without duplicates already
+    fn from_sorted(values: Vec<T>) -> Self {
+        let len = values.len();
+        let root = Self::build(&mut values.into_iter(), len, 0, bulk::red_depth(len));
+
+        RedBlackTree { root, len }
+    }
+
+    // Takes the next `len` values in order, left subtree first, then the root, then the right
+    fn build(values: &mut vec::IntoIter<T>, len: usize, depth: usize, red_depth: usize) -> Option<Rc<RefCell<Node<T>>>> {
+        if len == 0 {
+            return None;
+        }
+
+        let left_len = bulk::left_len(len);
+        let left = Self::build(values, left_len, depth + 1, red_depth);
+        let value = values.next().expect("build is never asked for more values than there are");
+        let right = Self::build(values, len - 1 - left_len, depth + 1, red_depth);
+
+        let node = Rc::new(RefCell::new(Node::new(value)));
+
+        for child in [&left, &right].into_iter().flatten() {
+            child.borrow_mut().parent = Some(Rc::downgrade(&node));
+        }
+
+        {
+            let mut node_mut = node.borrow_mut();
+            node_mut.left = left;
+            node_mut.right = right;
+            node_mut.is_red = depth == red_depth;
+            node_mut.size = len;
+        }
+
+        Some(node)
     }
 
-    pub fn insert(&mut self, value: T) {
-        if let Some(root_node) = self.root.as_ref() {
-            self.insert_node(root_node.clone(), value);
+    // Returns false when the value was already there
+    pub fn insert(&mut self, value: T) -> bool {
+        self.insert_value(value).is_some()
+    }
+
+    // Inserts a value that is known not to be in the tree yet and lends it straight back,
+    // for the maps' entry API where the key has moved into the tree by then
+    pub(super) fn insert_new(&mut self, value: T) -> &mut T {
+        let node = self.insert_value(value).expect("value was already in the tree");
+        let node = Rc::as_ptr(&node);
+
+        // SAFETY: the tree still owns the node, and `&mut self` keeps anything else from
+        // getting at it for as long as the value is lent out
+        unsafe { &mut (*(*node).as_ptr()).value }
+    }
+
+    // The node the value went into, rotations only relink nodes so it is the same one after
+    // the fix up
+    fn insert_value(&mut self, value: T) -> Option<Rc<RefCell<Node<T>>>> {
+        let inserted = if let Some(root_node) = self.root.as_ref() {
+            self.insert_node(root_node.clone(), value)
         } else {
             let root = Rc::new(RefCell::new(Node::new(value)));
             root.borrow_mut().is_red = false;
-            self.root = Some(root);
+            self.root = Some(root.clone());
+            Some(root)
+        };
+
+        if inserted.is_some() {
+            self.len += 1;
         }
+
+        inserted
     }
 
-    fn insert_node(&mut self, node: Rc<RefCell<Node<T>>>, value: T) {
+    fn insert_node(&mut self, node: Rc<RefCell<Node<T>>>, value: T) -> Option<Rc<RefCell<Node<T>>>> {
         let ordering = {
             let node_ref = node.borrow();
             value.cmp(&node_ref.value)
@@ -59,7 +355,7 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
                 };
 
                 if let Some(left_node) = left_child {
-                    self.insert_node(left_node, value);
+                    self.insert_node(left_node, value)
                 } else {
                     let new_node = Rc::new(RefCell::new(Node::new(value)));
                     
@@ -70,7 +366,9 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
                         node_mut.left = Some(Rc::clone(&new_node));
                     }
 
-                    self.insert_fix(new_node);
+                    Self::resize_up(Some(node));
+                    self.insert_fix(new_node.clone());
+                    Some(new_node)
                 }
             }
             Ordering::Greater => {
@@ -80,7 +378,7 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
                 };
 
                 if let Some(right_node) = right_child {
-                    self.insert_node(right_node, value);
+                    self.insert_node(right_node, value)
                 } else {
                     let new_node = Rc::new(RefCell::new(Node::new(value)));
                     
@@ -91,22 +389,18 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
                         node_mut.right = Some(Rc::clone(&new_node));
                     }
 
-                    self.insert_fix(new_node);
+                    Self::resize_up(Some(node));
+                    self.insert_fix(new_node.clone());
+                    Some(new_node)
                 }
             }
-            Ordering::Equal => {
-                return;
-            }
+            Ordering::Equal => None,
         }
     }
 
     fn insert_fix(&mut self, mut new_node: Rc<RefCell<Node<T>>>) {
-        while let Some(parent_weak) = new_node.clone().borrow().parent.clone() {
-            let parent_rc = match parent_weak.upgrade() {
-                None => break, 
-                Some(rc) => rc,
-            };
-      
+        // a borrow in the `while let` itself would live through the rotations below
+        while let Some(mut parent_rc) = Self::parent_of(&new_node) {
             if !parent_rc.borrow().is_red {
                 break;
             }
@@ -142,11 +436,17 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
                     }
                 }
 
-                if let Some(ref parent_right) = parent_rc.borrow().right {
-                    if Rc::ptr_eq(parent_right, &new_node) {
-                        self.left_rotation(parent_rc.clone());
-                        new_node = parent_rc.clone();
-                    }
+                // the check has to finish before rotating, the rotation borrows parent mutably
+                let is_inner_child = parent_rc
+                    .borrow()
+                    .right
+                    .as_ref()
+                    .is_some_and(|right| Rc::ptr_eq(right, &new_node));
+
+                if is_inner_child {
+                    self.left_rotation(parent_rc.clone());
+                    // new_node moved above its old parent, so the two swap roles
+                    std::mem::swap(&mut new_node, &mut parent_rc);
                 }
 
                 parent_rc.borrow_mut().is_red = false;
@@ -168,11 +468,15 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
                     }
                 }
               
-                if let Some(ref parent_left) = parent_rc.borrow().left {
-                    if Rc::ptr_eq(parent_left, &new_node) {
-                        self.right_rotation(parent_rc.clone());
-                        new_node = parent_rc.clone();
-                    }
+                let is_inner_child = parent_rc
+                    .borrow()
+                    .left
+                    .as_ref()
+                    .is_some_and(|left| Rc::ptr_eq(left, &new_node));
+
+                if is_inner_child {
+                    self.right_rotation(parent_rc.clone());
+                    std::mem::swap(&mut new_node, &mut parent_rc);
                 }
 
                 parent_rc.borrow_mut().is_red = false;
@@ -187,6 +491,10 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
         }
     }
 
+    fn parent_of(node: &Rc<RefCell<Node<T>>>) -> Option<Rc<RefCell<Node<T>>>> {
+        node.borrow().parent.as_ref().and_then(|weak| weak.upgrade())
+    }
+
     fn right_rotation(&mut self, node: Rc<RefCell<Node<T>>>) {
         let left_node = node.borrow().left.as_ref().unwrap().clone();
         node.borrow_mut().left = left_node.borrow().right.clone();
@@ -200,7 +508,13 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
 
         if let Some(parent_weak) = parent_opt {
             let parent_rc = parent_weak.upgrade().unwrap();
-            if Rc::ptr_eq(&node, parent_rc.borrow().right.as_ref().unwrap()) {
+            let is_right_child = parent_rc
+                .borrow()
+                .right
+                .as_ref()
+                .is_some_and(|right| Rc::ptr_eq(right, &node));
+
+            if is_right_child {
                 parent_rc.borrow_mut().right = Some(Rc::clone(&left_node));
             } else {
                 parent_rc.borrow_mut().left = Some(Rc::clone(&left_node));
@@ -211,6 +525,11 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
 
         left_node.borrow_mut().right = Some(Rc::clone(&node));
         node.borrow_mut().parent = Some(Rc::downgrade(&left_node));
+
+        // the subtree as a whole didn't change, left_node just took it over
+        let size = node.borrow().size;
+        left_node.borrow_mut().size = size;
+        update_size(&node);
     }
 
     fn left_rotation(&mut self, node: Rc<RefCell<Node<T>>>) {
@@ -226,7 +545,13 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
 
         if let Some(parent_weak) = parent_opt {
             let parent_rc = parent_weak.upgrade().unwrap();
-            if Rc::ptr_eq(&node, parent_rc.borrow().left.as_ref().unwrap()) {
+            let is_left_child = parent_rc
+                .borrow()
+                .left
+                .as_ref()
+                .is_some_and(|left| Rc::ptr_eq(left, &node));
+
+            if is_left_child {
                 parent_rc.borrow_mut().left = Some(Rc::clone(&right_node));
             } else {
                 parent_rc.borrow_mut().right = Some(Rc::clone(&right_node));
@@ -237,10 +562,29 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
 
         right_node.borrow_mut().left = Some(Rc::clone(&node));
         node.borrow_mut().parent = Some(Rc::downgrade(&right_node));
+
+        let size = node.borrow().size;
+        right_node.borrow_mut().size = size;
+        update_size(&node);
     }
 
-    pub fn delete(&mut self, value: T) {
-        self.delete_node(self.root.clone(), value);
+    // Returns whether the value was in the tree
+    pub fn delete(&mut self, value: T) -> bool {
+        self.take(&value).is_some()
+    }
+
+    // Removes the value and hands it back
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = self.delete_node(self.root.clone(), value)?;
+        self.len -= 1;
+
+        // the tree has let go of the node, this is the last strong reference to it
+        let node = Rc::try_unwrap(removed).ok().expect("removed node is still linked");
+        Some(node.into_inner().value)
     }
 
     fn transplant(&mut self, u: Option<Rc<RefCell<Node<T>>>>, v: Option<Rc<RefCell<Node<T>>>>) {
@@ -292,11 +636,21 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
         }
     }
 
-    fn delete_node(&mut self, mut node: Option<Rc<RefCell<Node<T>>>>, value: T) {
+    // Unlinks the node holding `value` and hands it back
+    fn delete_node<Q>(
+        &mut self,
+        mut node: Option<Rc<RefCell<Node<T>>>>,
+        value: &Q,
+    ) -> Option<Rc<RefCell<Node<T>>>>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         let mut target: Option<Rc<RefCell<Node<T>>>> = None;
 
         while let Some(current) = node.clone() {
-            match value.cmp(&current.borrow().value) {
+            let ordering = value.cmp(&key(&current));
+            match ordering {
                 Ordering::Less => node = current.borrow().left.clone(),
                 Ordering::Greater => node = current.borrow().right.clone(),
                 Ordering::Equal => {
@@ -306,172 +660,185 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
             }
         }
 
-        let z = match target {
-            Some(n) => n,
-            None => return,
-        };
+        let z = target?;
 
-        let mut y = z.clone();
-        let y_original_red = y.borrow().is_red;
+        let mut y_original_red = z.borrow().is_red;
         let x_opt: Option<Rc<RefCell<Node<T>>>>;
+        // x can be None (an empty leaf), so where it sits has to be tracked separately
+        let x_parent: Option<Rc<RefCell<Node<T>>>>;
+
+        let z_left = z.borrow().left.clone();
+        let z_right = z.borrow().right.clone();
 
-        if z.borrow().left.is_none() {
-            x_opt = z.borrow().right.clone();
+        if z_left.is_none() {
+            x_opt = z_right;
+            x_parent = Self::parent_of(&z);
             self.transplant(Some(z.clone()), x_opt.clone());
         }
-        else if z.borrow().right.is_none() {
-            x_opt = z.borrow().left.clone();
+        else if z_right.is_none() {
+            x_opt = z_left;
+            x_parent = Self::parent_of(&z);
             self.transplant(Some(z.clone()), x_opt.clone());
         }
 
         else {
-            let mut y_opt = z.borrow().right.clone();
-            
-            while let Some(ref y_node) = y_opt.clone() {
-                if y_node.borrow().left.is_none() {
-                    break;
+            let mut y = z_right.unwrap();
+
+            loop {
+                let left = y.borrow().left.clone();
+                match left {
+                    Some(left) => y = left,
+                    None => break,
                 }
-                y_opt = y_node.borrow().left.clone();
             }
 
-            y = y_opt.unwrap();
-            let mut y_right = y.borrow().right.clone();
-            let y_parent = y.borrow().parent.clone().unwrap().upgrade().unwrap();
+            y_original_red = y.borrow().is_red;
+            x_opt = y.borrow().right.clone();
 
-            if !Rc::ptr_eq(&y_parent, &z) {
-                self.transplant(Some(y.clone()), y_right.clone());
-                
-                y.borrow_mut().right = z.borrow().right.clone();
-                
-                if let Some(ref right) = y.borrow().right {
-                    right.borrow_mut().parent = Some(Rc::downgrade(&y));
-                }
+            if Self::parent_of(&y).is_some_and(|parent| Rc::ptr_eq(&parent, &z)) {
+                x_parent = Some(y.clone());
             } else {
-                if let Some(ref mut right) = y_right {
+                x_parent = Self::parent_of(&y);
+                self.transplant(Some(y.clone()), x_opt.clone());
+
+                let right = z.borrow().right.clone();
+                if let Some(ref right) = right {
                     right.borrow_mut().parent = Some(Rc::downgrade(&y));
                 }
+                y.borrow_mut().right = right;
             }
 
             self.transplant(Some(z.clone()), Some(y.clone()));
-            y.borrow_mut().left = z.borrow().left.clone();
-            
-            if let Some(ref left) = y.borrow().left {
+
+            let left = z.borrow().left.clone();
+            if let Some(ref left) = left {
                 left.borrow_mut().parent = Some(Rc::downgrade(&y));
             }
+            y.borrow_mut().left = left;
 
             y.borrow_mut().is_red = z.borrow().is_red;
-            x_opt = y_right;
         }
 
-        if y_original_red == false {
-            self.delete_fix(x_opt);
+        // everything that lost a node hangs above x, the rotations in the fixup keep sizes right
+        Self::resize_up(x_parent.clone());
+
+        if !y_original_red {
+            self.delete_fix(x_opt, x_parent);
         }
+
+        Some(z)
     }
 
-    fn delete_fix(&mut self, mut x_opt: Option<Rc<RefCell<Node<T>>>>) {
-        while let Some(x) = x_opt.clone() {
-            if let Some(ref root_rc) = self.root {
-                if Rc::ptr_eq(&x, root_rc) {
-                    break;
-                }
-            }
+    // Recomputes the sizes from `node` up to the root, after a node came or went below it
+    fn resize_up(mut node: Option<Rc<RefCell<Node<T>>>>) {
+        while let Some(n) = node {
+            update_size(&n);
+            node = Self::parent_of(&n);
+        }
+    }
 
-            let parent_weak = x.borrow().parent.clone();
-            if parent_weak.is_none() {
-                break;
-            }
-            let parent = parent_weak.unwrap().upgrade().unwrap();
+    fn is_red(node: &Option<Rc<RefCell<Node<T>>>>) -> bool {
+        node.as_ref().is_some_and(|n| n.borrow().is_red)
+    }
 
-            let is_left_child = if let Some(ref left) = parent.borrow().left {
-                Rc::ptr_eq(&x, left)
-            } else {
-                false
-            };
+    fn same_node(a: &Option<Rc<RefCell<Node<T>>>>, b: &Option<Rc<RefCell<Node<T>>>>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
 
-            let mut sibling_opt = if is_left_child {
-                parent.borrow().right.clone()
-            } else {
-                parent.borrow().left.clone()
+    // x carries an extra black, it gets pushed up the tree until a red node or the root
+    // can absorb it. The sibling always exists, the extra black means its side is at
+    // least one black node deep
+    fn delete_fix(&mut self, mut x_opt: Option<Rc<RefCell<Node<T>>>>, mut parent_opt: Option<Rc<RefCell<Node<T>>>>) {
+        while !Self::same_node(&x_opt, &self.root) && !Self::is_red(&x_opt) {
+            let parent = match parent_opt.clone() {
+                Some(parent) => parent,
+                None => break,
             };
 
-            if let Some(sibling) = sibling_opt.clone() {
+            let is_left_child = Self::same_node(&parent.borrow().left, &x_opt);
+
+            if is_left_child {
+                let mut sibling = parent.borrow().right.clone().unwrap();
+
                 if sibling.borrow().is_red {
                     sibling.borrow_mut().is_red = false;
                     parent.borrow_mut().is_red = true;
-                   
-                    if is_left_child {
-                        self.left_rotation(Rc::clone(&parent));
-                        sibling_opt = parent.borrow().right.clone();
-                    } else {
-                        self.right_rotation(Rc::clone(&parent));
-                        sibling_opt = parent.borrow().left.clone();
+
+                    self.left_rotation(Rc::clone(&parent));
+                    sibling = parent.borrow().right.clone().unwrap();
+                }
+
+                let sibling_left_red = Self::is_red(&sibling.borrow().left);
+                let sibling_right_red = Self::is_red(&sibling.borrow().right);
+
+                if !sibling_left_red && !sibling_right_red {
+                    sibling.borrow_mut().is_red = true;
+                    x_opt = Some(parent.clone());
+                    parent_opt = Self::parent_of(&parent);
+                } else {
+                    if !sibling_right_red {
+                        sibling.borrow().left.as_ref().unwrap().borrow_mut().is_red = false;
+                        sibling.borrow_mut().is_red = true;
+
+                        self.right_rotation(Rc::clone(&sibling));
+                        sibling = parent.borrow().right.clone().unwrap();
+                    }
+
+                    let parent_red = parent.borrow().is_red;
+                    sibling.borrow_mut().is_red = parent_red;
+                    parent.borrow_mut().is_red = false;
+
+                    if let Some(ref right) = sibling.borrow().right {
+                        right.borrow_mut().is_red = false;
                     }
+
+                    self.left_rotation(Rc::clone(&parent));
+                    x_opt = self.root.clone();
+                    parent_opt = None;
                 }
+            } else {
+                let mut sibling = parent.borrow().left.clone().unwrap();
 
-                let sibling = sibling_opt.clone().unwrap();
+                if sibling.borrow().is_red {
+                    sibling.borrow_mut().is_red = false;
+                    parent.borrow_mut().is_red = true;
 
-                let sibling_left_red = sibling
-                    .borrow()
-                    .left
-                    .as_ref()
-                    .map_or(false, |left| left.borrow().is_red);
-                
-                let sibling_right_red = sibling
-                    .borrow()
-                    .right
-                    .as_ref()
-                    .map_or(false, |right| right.borrow().is_red);
+                    self.right_rotation(Rc::clone(&parent));
+                    sibling = parent.borrow().left.clone().unwrap();
+                }
+
+                let sibling_left_red = Self::is_red(&sibling.borrow().left);
+                let sibling_right_red = Self::is_red(&sibling.borrow().right);
 
                 if !sibling_left_red && !sibling_right_red {
                     sibling.borrow_mut().is_red = true;
-                    x_opt = parent.borrow().parent.as_ref().and_then(|p| p.upgrade());
-                }
+                    x_opt = Some(parent.clone());
+                    parent_opt = Self::parent_of(&parent);
+                } else {
+                    if !sibling_left_red {
+                        sibling.borrow().right.as_ref().unwrap().borrow_mut().is_red = false;
+                        sibling.borrow_mut().is_red = true;
 
-                else {
-                    if is_left_child {
-                        if !sibling_right_red && sibling_left_red {
-                            sibling.borrow().left.as_ref().unwrap().borrow_mut().is_red = false;
-                            sibling.borrow_mut().is_red = true;
-                            
-                            self.right_rotation(Rc::clone(&sibling));
-                            
-                            sibling_opt = parent.borrow().right.clone();
-                        }
+                        self.left_rotation(Rc::clone(&sibling));
+                        sibling = parent.borrow().left.clone().unwrap();
+                    }
 
-                        let sibling = sibling_opt.unwrap();
-                        sibling.borrow_mut().is_red = parent.borrow().is_red;
-                        parent.borrow_mut().is_red = false;
-                        
-                        if let Some(ref right) = sibling.borrow().right {
-                            right.borrow_mut().is_red = false;
-                        }
-                        
-                        self.left_rotation(Rc::clone(&parent));
-                        x_opt = self.root.clone();
-                    } else {
-                        if !sibling_left_red && sibling_right_red {
-                            sibling.borrow().right.as_ref().unwrap().borrow_mut().is_red = false;
-                            sibling.borrow_mut().is_red = true;
-                           
-                            self.left_rotation(Rc::clone(&sibling));
-                          
-                            sibling_opt = parent.borrow().left.clone();
-                        }
+                    let parent_red = parent.borrow().is_red;
+                    sibling.borrow_mut().is_red = parent_red;
+                    parent.borrow_mut().is_red = false;
 
-                        let sibling = sibling_opt.unwrap();
-                        sibling.borrow_mut().is_red = parent.borrow().is_red;
-                        parent.borrow_mut().is_red = false;
-                        
-                        if let Some(ref left) = sibling.borrow().left {
-                            left.borrow_mut().is_red = false;
-                        }
-                       
-                       self.right_rotation(Rc::clone(&parent));
-                        x_opt = self.root.clone();
+                    if let Some(ref left) = sibling.borrow().left {
+                        left.borrow_mut().is_red = false;
                     }
+
+                    self.right_rotation(Rc::clone(&parent));
+                    x_opt = self.root.clone();
+                    parent_opt = None;
                 }
-            } else {
-                x_opt = parent.borrow().parent.as_ref().and_then(|p| p.upgrade());
             }
         }
 
@@ -481,19 +848,344 @@ impl<T: Ord + Clone + fmt::Display> RedBlackTree<T> {
     }
 }
 
+impl<T: Ord + Clone> RedBlackTree<T> {
+    // Checks BST order, the coloring rules and that every child points back at its parent
+    pub fn validate(&self) -> Result<(), InvariantViolation<T>> {
+        if let Some(root) = self.root.as_ref() {
+            let root_ref = root.borrow();
+
+            if root_ref.is_red {
+                return Err(InvariantViolation::RedRoot {
+                    root: root_ref.value.clone(),
+                });
+            }
+
+            if root_ref.parent.as_ref().and_then(|weak| weak.upgrade()).is_some() {
+                return Err(InvariantViolation::Parent {
+                    node: None,
+                    child: root_ref.value.clone(),
+                });
+            }
+        }
+
+        Self::validate_node(&self.root, None, None).map(|_| ())
+    }
+
+    // Hands back how many black nodes every path below `node` passes, empty leaves count as one,
+    // and how many nodes are below it
+    fn validate_node(node: &Option<Rc<RefCell<Node<T>>>>, low: Option<&T>, high: Option<&T>) -> Result<(usize, usize), InvariantViolation<T>> {
+        let n = match node {
+            Some(n) => n,
+            None => return Ok((1, 0)),
+        };
+        let n_ref = n.borrow();
+
+        check_order(&n_ref.value, low, high)?;
+
+        for child in [&n_ref.left, &n_ref.right].into_iter().flatten() {
+            let child_ref = child.borrow();
+
+            let points_back = child_ref
+                .parent
+                .as_ref()
+                .and_then(|weak| weak.upgrade())
+                .is_some_and(|parent| Rc::ptr_eq(&parent, n));
+
+            if !points_back {
+                return Err(InvariantViolation::Parent {
+                    node: Some(n_ref.value.clone()),
+                    child: child_ref.value.clone(),
+                });
+            }
+
+            if n_ref.is_red && child_ref.is_red {
+                return Err(InvariantViolation::RedRed {
+                    node: n_ref.value.clone(),
+                    child: child_ref.value.clone(),
+                });
+            }
+        }
+
+        let (left, left_size) = Self::validate_node(&n_ref.left, low, Some(&n_ref.value))?;
+        let (right, right_size) = Self::validate_node(&n_ref.right, Some(&n_ref.value), high)?;
+        let size = 1 + left_size + right_size;
+
+        if n_ref.size != size {
+            return Err(InvariantViolation::Size {
+                node: n_ref.value.clone(),
+                stored: n_ref.size,
+                actual: size,
+            });
+        }
+
+        if left != right {
+            return Err(InvariantViolation::BlackHeight {
+                node: n_ref.value.clone(),
+                left,
+                right,
+            });
+        }
+
+        Ok((left + if n_ref.is_red { 0 } else { 1 }, size))
+    }
+}
+
+impl<T> Default for RedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Sorted input into an empty tree takes the O(n) path, anything else is inserted one by one
+impl<T: Ord> FromIterator<T> for RedBlackTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for RedBlackTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        match bulk::into_sorted(iter.into_iter().collect()) {
+            Ok(sorted) if self.is_empty() => *self = Self::from_sorted(sorted),
+            Ok(values) | Err(values) => {
+                for value in values {
+                    self.insert(value);
+                }
+            }
+        }
+    }
+}
+
+// The iterators hand out plain references to nodes so each step doesn't have to hold on to
+// a Ref guard of the node before it, the same trick the doubly linked list uses.
+
+// SAFETY for all of these: every node is owned by the tree (through `root` and the child
+// links) and the links are only rewritten by methods taking `&mut self`, so for as long as
+// `node` is borrowed out of the tree its parent and children can't be freed either
+fn left_ref<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    node.borrow().left.as_ref().map(|rc| unsafe { node_ref(Rc::as_ptr(rc)) })
+}
+
+fn right_ref<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    node.borrow().right.as_ref().map(|rc| unsafe { node_ref(Rc::as_ptr(rc)) })
+}
+
+fn parent_ref<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    node.borrow().parent.as_ref().map(|weak| unsafe { node_ref(weak.as_ptr()) })
+}
+
+fn value_ref<T>(node: &RefCell<Node<T>>) -> Ref<'_, T> {
+    Ref::map(node.borrow(), |node| &node.value)
+}
+
+// The value without a guard. Nodes are only borrowed mutably inside methods taking
+// `&mut self`, so nothing can write to it for as long as `node` is borrowed out of the tree
+fn value_unguarded<T>(node: &RefCell<Node<T>>) -> &T {
+    let node = unsafe { node.try_borrow_unguarded() };
+    &node.expect("nodes are only borrowed mutably through &mut self").value
+}
+
+// The node's value as the borrowed key type of a lookup. Spelled out in full because
+// importing Borrow would clash with RefCell::borrow on the Rc links
+fn key<T, Q>(node: &RefCell<Node<T>>) -> Ref<'_, Q>
+where
+    T: std::borrow::Borrow<Q>,
+    Q: ?Sized,
+{
+    Ref::map(node.borrow(), |node| node.value.borrow())
+}
+
+fn subtree_size<T>(node: Option<&RefCell<Node<T>>>) -> usize {
+    node.map_or(0, |node| node.borrow().size)
+}
+
+fn update_size<T>(node: &RefCell<Node<T>>) {
+    let size = 1 + subtree_size(left_ref(node)) + subtree_size(right_ref(node));
+    node.borrow_mut().size = size;
+}
+
+fn leftmost<T>(mut node: &RefCell<Node<T>>) -> &RefCell<Node<T>> {
+    while let Some(left) = left_ref(node) {
+        node = left;
+    }
+    node
+}
+
+fn rightmost<T>(mut node: &RefCell<Node<T>>) -> &RefCell<Node<T>> {
+    while let Some(right) = right_ref(node) {
+        node = right;
+    }
+    node
+}
+
+// Next node in order: the leftmost of the right subtree, or else the first ancestor we
+// reach from its left side
+fn next_node<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    if let Some(right) = right_ref(node) {
+        return Some(leftmost(right));
+    }
+
+    let mut child = node;
+    while let Some(parent) = parent_ref(child) {
+        if left_ref(parent).is_some_and(|left| std::ptr::eq(left, child)) {
+            return Some(parent);
+        }
+        child = parent;
+    }
+
+    None
+}
+
+fn prev_node<T>(node: &RefCell<Node<T>>) -> Option<&RefCell<Node<T>>> {
+    if let Some(left) = left_ref(node) {
+        return Some(rightmost(left));
+    }
+
+    let mut child = node;
+    while let Some(parent) = parent_ref(child) {
+        if right_ref(parent).is_some_and(|right| std::ptr::eq(right, child)) {
+            return Some(parent);
+        }
+        child = parent;
+    }
+
+    None
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a RefCell<Node<T>>>,
+    next_back: Option<&'a RefCell<Node<T>>>,
+    remaining: usize, // stops the two ends from walking past each other
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn step(&mut self) -> Option<&'a RefCell<Node<T>>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.next?;
+        self.next = next_node(node);
+        self.remaining -= 1;
+        Some(node)
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step().map(value_ref)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.next_back?;
+        self.next_back = prev_node(node);
+        self.remaining -= 1;
+        Some(value_ref(node))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a RedBlackTree<T> {
+    type Item = Ref<'a, T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct UnguardedIter<'a, T>(Iter<'a, T>);
+
+impl<'a, T> Iterator for UnguardedIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.step().map(value_unguarded)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+pub struct IterMut<'a, T> {
+    nodes: vec::IntoIter<*mut Node<T>>, // in order, each one handed out once
+    marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY: the pointers are all different and the tree stays mutably borrowed
+        self.nodes.next().map(|node| unsafe { &mut (*node).value })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+// There's no count up front, the two ends are done once they meet on the same node
+pub struct Range<'a, T> {
+    next: Option<&'a RefCell<Node<T>>>,
+    next_back: Option<&'a RefCell<Node<T>>>,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+
+        if self.next_back.is_some_and(|back| std::ptr::eq(back, node)) {
+            self.next = None;
+            self.next_back = None;
+        } else {
+            self.next = next_node(node);
+        }
+
+        Some(value_ref(node))
+    }
+}
+
+impl<T> DoubleEndedIterator for Range<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.next_back?;
+
+        if self.next.is_some_and(|front| std::ptr::eq(front, node)) {
+            self.next = None;
+            self.next_back = None;
+        } else {
+            self.next_back = prev_node(node);
+        }
+
+        Some(value_ref(node))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_set, ModelSet};
 
-    fn in_order_values<T: Ord + Clone + fmt::Display>(
-        node: &Option<Rc<RefCell<Node<T>>>>,
-        vals: &mut Vec<T>,
-    ) {
-        if let Some(n) = node {
-            in_order_values(&n.borrow().left, vals);
-            vals.push(n.borrow().value.clone());
-            in_order_values(&n.borrow().right, vals);
-        }
+    fn values(tree: &RedBlackTree<i32>) -> Vec<i32> {
+        tree.iter().map(|value| *value).collect()
     }
 
     #[test]
@@ -504,10 +1196,9 @@ mod tests {
         tree.insert(15);
         tree.insert(12);
         tree.insert(1);
+        assert_eq!(tree.validate(), Ok(()));
 
-        let mut vals = vec![];
-        in_order_values(&tree.root, &mut vals);
-        assert_eq!(vals, vec![1, 5, 10, 12, 15]);
+        assert_eq!(values(&tree), vec![1, 5, 10, 12, 15]);
     }
 
     #[test]
@@ -518,9 +1209,8 @@ mod tests {
         tree.insert(15);
 
         tree.delete(5); // Leaf
-        let mut vals = vec![];
-        in_order_values(&tree.root, &mut vals);
-        assert_eq!(vals, vec![10, 15]);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(values(&tree), vec![10, 15]);
     }
 
     #[test]
@@ -531,9 +1221,8 @@ mod tests {
         tree.insert(2); // 5 has one child
 
         tree.delete(5);
-        let mut vals = vec![];
-        in_order_values(&tree.root, &mut vals);
-        assert_eq!(vals, vec![2, 10]);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(values(&tree), vec![2, 10]);
     }
 
     #[test]
@@ -546,9 +1235,8 @@ mod tests {
         tree.insert(35);
 
         tree.delete(30); // has two children
-        let mut vals = vec![];
-        in_order_values(&tree.root, &mut vals);
-        assert_eq!(vals, vec![10, 20, 25, 35]);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(values(&tree), vec![10, 20, 25, 35]);
     }
 
     #[test]
@@ -559,8 +1247,314 @@ mod tests {
         tree.insert(15);
 
         tree.delete(10); // root
-        let mut vals = vec![];
-        in_order_values(&tree.root, &mut vals);
-        assert_eq!(vals, vec![5, 15]);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(values(&tree), vec![5, 15]);
+    }
+
+    #[test]
+    fn test_insert_and_delete_report_changes() {
+        let mut tree = RedBlackTree::new();
+        assert!(tree.is_empty());
+
+        assert!(tree.insert(3));
+        assert!(tree.insert(1));
+        assert!(!tree.insert(3));
+        assert_eq!(tree.len(), 2);
+
+        assert!(tree.delete(3));
+        assert!(!tree.delete(3));
+        assert_eq!(tree.len(), 1);
+        assert!(tree.contains(&1));
+        assert!(!tree.contains(&3));
+    }
+
+    #[test]
+    fn test_min_max_successor_predecessor() {
+        let mut tree = RedBlackTree::new();
+        assert!(tree.min().is_none());
+        assert!(tree.successor(&1).is_none());
+
+        for value in [40, 10, 30, 20, 50] {
+            tree.insert(value);
+        }
+
+        assert_eq!(*tree.min().unwrap(), 10);
+        assert_eq!(*tree.max().unwrap(), 50);
+
+        // the value asked about doesn't have to be in the tree
+        assert_eq!(*tree.successor(&20).unwrap(), 30);
+        assert_eq!(*tree.successor(&25).unwrap(), 30);
+        assert_eq!(*tree.successor(&0).unwrap(), 10);
+        assert!(tree.successor(&50).is_none());
+
+        assert_eq!(*tree.predecessor(&20).unwrap(), 10);
+        assert_eq!(*tree.predecessor(&99).unwrap(), 50);
+        assert!(tree.predecessor(&10).is_none());
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let mut tree = RedBlackTree::new();
+        for word in ["pear", "apple", "fig"] {
+            tree.insert(word.to_string());
+        }
+
+        assert!(tree.contains("fig"));
+        assert!(!tree.contains("kiwi"));
+        assert_eq!(*tree.successor("b").unwrap(), "fig");
+        assert_eq!(tree.range::<str, _>((Bound::Included("b"), Bound::Excluded("z"))).count(), 2);
+    }
+
+    #[test]
+    fn test_iter_both_ends() {
+        let mut tree = RedBlackTree::new();
+        for value in (0..100).rev() {
+            tree.insert(value);
+        }
+
+        assert_eq!(values(&tree), (0..100).collect::<Vec<_>>());
+        assert_eq!(
+            tree.iter().rev().map(|value| *value).collect::<Vec<_>>(),
+            (0..100).rev().collect::<Vec<_>>()
+        );
+
+        // the two ends stop when they meet
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 100);
+        assert_eq!(*iter.next().unwrap(), 0);
+        assert_eq!(*iter.next_back().unwrap(), 99);
+        assert_eq!(iter.len(), 98);
+        assert_eq!(iter.count(), 98);
+
+        let mut sum = 0;
+        for value in &tree {
+            sum += *value;
+        }
+        assert_eq!(sum, 4950);
+    }
+
+    // Every bound combination over a small tree, checked against BTreeSet
+    #[test]
+    fn test_range_matches_btree_set() {
+        use std::{collections::BTreeSet, ops::Bound::*};
+
+        let mut tree = RedBlackTree::new();
+        let mut model = BTreeSet::new();
+        for value in [2, 4, 6, 8, 10, 12] {
+            tree.insert(value);
+            model.insert(value);
+        }
+
+        for low in 0..14 {
+            for high in low..14 {
+                for bounds in [
+                    (Included(low), Excluded(high)),
+                    (Included(low), Included(high)),
+                    (Excluded(low), Included(high)),
+                    (Unbounded, Included(high)),
+                    (Included(low), Unbounded),
+                ] {
+                    if bounds == (Excluded(low), Included(high)) && low == high {
+                        continue; // BTreeSet panics on this one
+                    }
+
+                    let expected: Vec<i32> = model.range(bounds).copied().collect();
+                    let forward: Vec<i32> = tree.range(bounds).map(|value| *value).collect();
+                    let mut backward: Vec<i32> = tree.range(bounds).rev().map(|value| *value).collect();
+                    backward.reverse();
+
+                    assert_eq!(forward, expected, "{:?}", bounds);
+                    assert_eq!(backward, expected, "{:?}", bounds);
+                }
+            }
+        }
+
+        assert_eq!(tree.range(..).count(), 6);
+        assert_eq!(tree.range(5..5).count(), 0);
+
+        let mut range = tree.range(3..11);
+        assert_eq!(*range.next().unwrap(), 4);
+        assert_eq!(*range.next_back().unwrap(), 10);
+        assert_eq!(*range.next().unwrap(), 6);
+        assert_eq!(*range.next_back().unwrap(), 8);
+        assert!(range.next().is_none());
+        assert!(range.next_back().is_none());
+    }
+
+    #[test]
+    fn test_validate_reports_broken_coloring_and_links() {
+        let mut tree = RedBlackTree::new();
+        for value in [10, 5, 15, 1] {
+            tree.insert(value);
+        }
+        assert_eq!(tree.validate(), Ok(()));
+
+        // 1 is red under a black 5, making 5 red as well gives two reds in a row
+        let five = tree.root.as_ref().unwrap().borrow().left.clone().unwrap();
+        five.borrow_mut().is_red = true;
+        assert_eq!(tree.validate(), Err(InvariantViolation::RedRed { node: 5, child: 1 }));
+
+        // now black only on the right side
+        let one = five.borrow().left.clone().unwrap();
+        one.borrow_mut().is_red = false;
+        assert_eq!(
+            tree.validate(),
+            Err(InvariantViolation::BlackHeight { node: 5, left: 2, right: 1 })
+        );
+
+        five.borrow_mut().is_red = false;
+        one.borrow_mut().is_red = true;
+        one.borrow_mut().parent = None;
+        assert_eq!(
+            tree.validate(),
+            Err(InvariantViolation::Parent { node: Some(5), child: 1 })
+        );
+
+        tree.root.as_ref().unwrap().borrow_mut().is_red = true;
+        assert_eq!(tree.validate(), Err(InvariantViolation::RedRoot { root: 10 }));
+    }
+
+    // Black nodes on every path down from `node`, None if the paths disagree or a red node
+    // has a red child
+    fn black_height(node: &Option<Rc<RefCell<Node<i32>>>>) -> Option<usize> {
+        let Some(node) = node else { return Some(1) };
+        let node = node.borrow();
+
+        if node.is_red && (RedBlackTree::is_red(&node.left) || RedBlackTree::is_red(&node.right)) {
+            return None;
+        }
+
+        let left = black_height(&node.left)?;
+        let right = black_height(&node.right)?;
+        (left == right).then_some(left + usize::from(!node.is_red))
+    }
+
+    fn assert_red_black(tree: &RedBlackTree<i32>, expected: &[i32]) {
+        assert_eq!(values(tree), expected);
+        assert!(!RedBlackTree::is_red(&tree.root), "red root");
+        assert!(black_height(&tree.root).is_some(), "red-black rules broken");
+    }
+
+    #[test]
+    fn test_insert_inner_child_rotates() {
+        // 2 lands as the inner child of 3, used to panic on a RefCell borrow held over the rotation
+        let mut tree = RedBlackTree::new();
+        for value in [1, 3, 2] {
+            tree.insert(value);
+        }
+        assert_red_black(&tree, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_delete_black_leaf_keeps_black_height() {
+        // the deleted leaf has no node to carry the extra black, delete_fix used to skip it
+        let mut tree = RedBlackTree::new();
+        for value in 1..=4 {
+            tree.insert(value);
+        }
+        tree.delete(1);
+        assert_red_black(&tree, &[2, 3, 4]);
+    }
+
+    #[test]
+    fn test_delete_sequence_after_rotations() {
+        // used to panic unwrapping a sibling that an earlier bad fix-up had left out
+        let mut tree = RedBlackTree::new();
+        for value in 1..=8 {
+            tree.insert(value);
+        }
+        for value in [4, 7, 2, 5] {
+            tree.delete(value);
+        }
+        assert_red_black(&tree, &[1, 3, 6, 8]);
+    }
+
+    impl ModelSet for RedBlackTree<i32> {
+        fn insert(&mut self, value: i32) -> bool {
+            RedBlackTree::insert(self, value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            RedBlackTree::delete(self, value)
+        }
+
+        fn values(&self) -> Vec<i32> {
+            values(self)
+        }
+
+        fn contains(&self, value: i32) -> bool {
+            RedBlackTree::contains(self, &value)
+        }
+
+        fn len(&self) -> usize {
+            RedBlackTree::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            RedBlackTree::validate(self)
+        }
+    }
+
+    // What the maps use. Ascending inserts rotate at almost every step, the value still has
+    // to come back
+    #[test]
+    fn test_insert_new_and_plain_references() {
+        let mut tree = RedBlackTree::new();
+
+        for i in 0..64 {
+            let value = tree.insert_new(i * 2);
+            assert_eq!(*value, i * 2);
+            *value += 1;
+        }
+        assert_eq!(tree.len(), 64);
+        assert!(tree.validate().is_ok());
+
+        let guard = tree.min().unwrap();
+        assert_eq!(tree.get(&31), Some(&31));
+        assert_eq!(tree.get(&32), None);
+        assert_eq!(*guard, 1);
+        drop(guard);
+
+        assert_eq!(tree.get_mut(&31).map(|value| *value), Some(31));
+        assert!(tree.get_mut(&32).is_none());
+
+        for value in tree.iter_mut() {
+            *value -= 1;
+        }
+        assert!(tree.iter_unguarded().copied().eq((0..64).map(|i| i * 2)));
+        assert_eq!(values(&tree), tree.iter_unguarded().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for len in [0, 1, 2, 3, 4, 6, 7, 8, 100, 1023, 1024] {
+            let tree = RedBlackTree::from_sorted_iter(0..len);
+
+            assert_eq!(tree.len(), len as usize);
+            assert!(tree.validate().is_ok(), "len {}", len);
+            assert_eq!(values(&tree), (0..len).collect::<Vec<_>>());
+            assert_eq!(tree.rank(&(len / 2)), (len / 2) as usize);
+        }
+
+        // a perfect tree has no red level
+        let tree = RedBlackTree::from_sorted_iter(0..7);
+        assert!(tree.shape().iter().all(|&(_, is_red)| !is_red));
+
+        let tree = RedBlackTree::from_sorted_iter([1, 1, 2, 3, 3]);
+        assert_eq!(values(&tree), vec![1, 2, 3]);
+
+        // the bulk built tree is still a normal red-black tree afterwards
+        let mut tree: RedBlackTree<i32> = (0..200).collect();
+        for i in (0..200).step_by(3) {
+            tree.delete(i);
+        }
+        tree.extend([500, 300, 400]);
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.len(), 136);
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<RedBlackTree<i32>>("RedBlackTree against BTreeSet");
     }
 }
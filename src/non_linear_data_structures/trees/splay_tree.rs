@@ -0,0 +1,457 @@
+use core::fmt;
+use std::{borrow::Borrow, cmp::Ordering};
+
+use super::{
+    binary_search_tree::{BinarySearchTree, Iter, Node},
+    invariant::InvariantViolation,
+};
+
+// Same nodes as the plain BST, no balance information at all. Every access splays the node
+// it ends on up to the root, which keeps the whole thing amortized O(log n) and makes
+// repeated lookups of the same few values close to free. The catch is that lookups need
+// `&mut self` too, and a single operation can still walk a long path
+pub struct SplayTree<T> {
+    root: Option<Box<Node<T>>>,
+    len: usize,
+}
+
+impl<T> SplayTree<T> {
+    pub fn new() -> Self {
+        SplayTree { root: None, len: 0 }
+    }
+
+    // The root's size is the length, split and join only know it that way
+    fn from_root(root: Option<Box<Node<T>>>) -> Self {
+        let len = BinarySearchTree::size_of(root.as_deref());
+        SplayTree { root, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Smallest value, splayed up to the root on the way
+    pub fn first(&mut self) -> Option<&T> {
+        let root = splay(self.root.take()?, |_| Ordering::Less);
+        Some(&self.root.insert(root).value)
+    }
+
+    pub fn last(&mut self) -> Option<&T> {
+        let root = splay(self.root.take()?, |_| Ordering::Greater);
+        Some(&self.root.insert(root).value)
+    }
+
+    // In order, smallest first. Doesn't splay anything
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.root.as_deref())
+    }
+}
+
+impl<T: Ord> SplayTree<T> {
+    // Returns false if the value was already there, it ends up at the root either way
+    pub fn insert(&mut self, value: T) -> bool {
+        let Some(root) = self.root.take() else {
+            self.root = Some(Box::new(Node::new(value)));
+            self.len += 1;
+            return true;
+        };
+
+        let mut root = splay(root, |v| value.cmp(v));
+
+        // the old root is the closest value, the new node goes on top of it and takes over
+        // the side of it that lies beyond the new value
+        let mut node = Box::new(Node::new(value));
+        match node.value.cmp(&root.value) {
+            Ordering::Less => {
+                node.left = root.left.take();
+                update_size(&mut root);
+                node.right = Some(root);
+            }
+            Ordering::Greater => {
+                node.right = root.right.take();
+                update_size(&mut root);
+                node.left = Some(root);
+            }
+            Ordering::Equal => {
+                self.root = Some(root);
+                return false;
+            }
+        }
+
+        update_size(&mut node);
+        self.root = Some(node);
+        self.len += 1;
+        true
+    }
+
+    // A hit moves the value to the root, a miss moves the last node on the search path there
+    pub fn get<Q>(&mut self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let root = splay(self.root.take()?, |v| value.cmp(v.borrow()));
+        let root = self.root.insert(root);
+
+        (root.value.borrow() == value).then_some(&root.value)
+    }
+
+    pub fn search(&mut self, value: T) -> bool {
+        self.get(&value).is_some()
+    }
+
+    // Returns whether the value was in the tree
+    pub fn delete(&mut self, value: T) -> bool {
+        self.take(&value).is_some()
+    }
+
+    // Removes the value and hands it back
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let root = splay(self.root.take()?, |v| value.cmp(v.borrow()));
+
+        if root.value.borrow() != value {
+            self.root = Some(root);
+            return None;
+        }
+
+        let Node { value, left, right, .. } = *root;
+        self.root = join_nodes(left, right);
+        self.len -= 1;
+        Some(value)
+    }
+
+    // Everything below `value` goes left, everything above goes right, and the value itself
+    // comes back in the middle if it was there. One splay, so amortized O(log n)
+    pub fn split<Q>(mut self, value: &Q) -> (SplayTree<T>, Option<T>, SplayTree<T>)
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let Some(root) = self.root.take() else {
+            return (SplayTree::new(), None, SplayTree::new());
+        };
+
+        let mut root = splay(root, |v| value.cmp(v.borrow()));
+
+        match value.cmp(root.value.borrow()) {
+            Ordering::Less => {
+                let left = root.left.take();
+                update_size(&mut root);
+                (Self::from_root(left), None, Self::from_root(Some(root)))
+            }
+            Ordering::Greater => {
+                let right = root.right.take();
+                update_size(&mut root);
+                (Self::from_root(Some(root)), None, Self::from_root(right))
+            }
+            Ordering::Equal => {
+                let Node { value, left, right, .. } = *root;
+                (Self::from_root(left), Some(value), Self::from_root(right))
+            }
+        }
+    }
+
+    // The other way round, every value in `left` has to be below every value in `right`.
+    // After splaying the largest value of `left` to its root that root has no right child,
+    // `right` goes there
+    pub fn join(mut left: SplayTree<T>, mut right: SplayTree<T>) -> SplayTree<T> {
+        let (low, high) = match (left.root.take(), right.root.take()) {
+            (Some(low), Some(high)) => (low, high),
+            (low, high) => return Self::from_root(low.or(high)),
+        };
+
+        let mut low = splay(low, |_| Ordering::Greater);
+        let high = splay(high, |_| Ordering::Less);
+        assert!(low.value < high.value, "join needs every value on the left below every value on the right");
+
+        low.right = Some(high);
+        update_size(&mut low);
+        Self::from_root(Some(low))
+    }
+}
+
+impl<T: Ord + Clone + fmt::Display> SplayTree<T> {
+    // Only order and sizes, a splay tree keeps no balance to check
+    pub fn validate(&self) -> Result<(), InvariantViolation<T>> {
+        BinarySearchTree::validate_node(&self.root, None, None).map(|_| ())
+    }
+
+    pub fn pretty_print(&self) {
+        print!("{}", BinarySearchTree::render(&self.root));
+    }
+}
+
+impl<T> Default for SplayTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Sorted keys turn a splay tree into one long path (until something deep gets splayed), the
+// generated drop would recurse once per node on it
+impl<T> Drop for SplayTree<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+// No sorted fast path needed, each value in ascending order lands at the root in O(1)
+impl<T: Ord> FromIterator<T> for SplayTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for SplayTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SplayTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+fn update_size<T>(node: &mut Node<T>) {
+    node.size = 1 + BinarySearchTree::size_of(node.left.as_deref()) + BinarySearchTree::size_of(node.right.as_deref());
+}
+
+// Top-down splay. `direction` says which way the target lies from a value, Equal stops there.
+// Walking down, the nodes passed on the way are cut off into two piles, the ones that end
+// up left of the new root and the ones that end up right of it. Two steps the same way
+// rotate first, that is what halves the path and pays for the walk. At the bottom the piles
+// are hung back under the new root, each node below the one before it in its pile.
+// A loop and two stacks instead of recursion, a splay tree can be as deep as it is long
+fn splay<T, F>(mut root: Box<Node<T>>, mut direction: F) -> Box<Node<T>>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let mut smaller: Vec<Box<Node<T>>> = vec![];
+    let mut larger: Vec<Box<Node<T>>> = vec![];
+
+    loop {
+        match direction(&root.value) {
+            Ordering::Less => {
+                let Some(mut left) = root.left.take() else { break };
+
+                if direction(&left.value) == Ordering::Less {
+                    root.left = left.right.take();
+                    update_size(&mut root);
+                    left.right = Some(root);
+                    root = left;
+
+                    let Some(next) = root.left.take() else { break };
+                    larger.push(std::mem::replace(&mut root, next));
+                } else {
+                    larger.push(std::mem::replace(&mut root, left));
+                }
+            }
+            Ordering::Greater => {
+                let Some(mut right) = root.right.take() else { break };
+
+                if direction(&right.value) == Ordering::Greater {
+                    root.right = right.left.take();
+                    update_size(&mut root);
+                    right.left = Some(root);
+                    root = right;
+
+                    let Some(next) = root.right.take() else { break };
+                    smaller.push(std::mem::replace(&mut root, next));
+                } else {
+                    smaller.push(std::mem::replace(&mut root, right));
+                }
+            }
+            Ordering::Equal => break,
+        }
+    }
+
+    let mut left = root.left.take();
+    while let Some(mut node) = smaller.pop() {
+        node.right = left;
+        update_size(&mut node);
+        left = Some(node);
+    }
+
+    let mut right = root.right.take();
+    while let Some(mut node) = larger.pop() {
+        node.left = right;
+        update_size(&mut node);
+        right = Some(node);
+    }
+
+    root.left = left;
+    root.right = right;
+    update_size(&mut root);
+    root
+}
+
+// Every value in `left` below every value in `right`
+fn join_nodes<T>(left: Option<Box<Node<T>>>, right: Option<Box<Node<T>>>) -> Option<Box<Node<T>>> {
+    let Some(left) = left else { return right };
+
+    let mut root = splay(left, |_| Ordering::Greater);
+    root.right = right;
+    update_size(&mut root);
+    Some(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_set, ModelSet};
+
+    fn root<T: Copy>(tree: &SplayTree<T>) -> Option<T> {
+        tree.root.as_ref().map(|n| n.value)
+    }
+
+    fn height<T>(node: &Option<Box<Node<T>>>) -> i64 {
+        node.as_ref().map_or(-1, |n| 1 + height(&n.left).max(height(&n.right)))
+    }
+
+    #[test]
+    fn test_insert_search_and_delete() {
+        let mut tree = SplayTree::new();
+        for value in [20, 10, 30, 25, 35, 5] {
+            assert!(tree.insert(value));
+            assert_eq!(root(&tree), Some(value));
+        }
+        assert!(!tree.insert(25));
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.validate(), Ok(()));
+
+        assert!(tree.search(10));
+        assert_eq!(root(&tree), Some(10));
+
+        // a miss still splays, the last node looked at comes up
+        assert!(!tree.search(27));
+        assert!(matches!(root(&tree), Some(25) | Some(30)));
+        assert_eq!(tree.validate(), Ok(()));
+
+        assert!(tree.delete(20));
+        assert!(!tree.delete(20));
+        assert_eq!(tree.take(&35), Some(35));
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![5, 10, 25, 30]);
+        assert_eq!(tree.validate(), Ok(()));
+
+        assert_eq!(tree.first(), Some(&5));
+        assert_eq!(tree.last(), Some(&30));
+        assert_eq!(root(&tree), Some(30));
+    }
+
+    #[test]
+    fn test_splaying_a_long_path_halves_it() {
+        // ascending inserts leave one path hanging to the left of the last value
+        let mut tree: SplayTree<i32> = (0..1000).collect();
+        assert_eq!(height(&tree.root), 999);
+
+        assert!(tree.search(0));
+        assert!(height(&tree.root) <= 501);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_dropping_a_long_path() {
+        // a million nodes take ages under Miri, a few thousand still make a long path
+        let len = if cfg!(miri) { 5_000 } else { 1_000_000 };
+        let tree: SplayTree<u32> = (0..len).collect();
+        assert_eq!(tree.len(), len as usize);
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        let tree: SplayTree<i32> = [8, 3, 12, 1, 5, 10, 14].into_iter().collect();
+
+        let (left, middle, right) = tree.split(&5);
+        assert_eq!(left.iter().copied().collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(middle, Some(5));
+        assert_eq!(right.iter().copied().collect::<Vec<_>>(), vec![8, 10, 12, 14]);
+        assert_eq!((left.len(), right.len()), (2, 4));
+        assert_eq!(left.validate(), Ok(()));
+        assert_eq!(right.validate(), Ok(()));
+
+        let (low, middle, high) = right.split(&11);
+        assert_eq!(middle, None);
+        assert_eq!(low.iter().copied().collect::<Vec<_>>(), vec![8, 10]);
+        assert_eq!(high.iter().copied().collect::<Vec<_>>(), vec![12, 14]);
+
+        let joined = SplayTree::join(left, SplayTree::join(low, high));
+        assert_eq!(joined.len(), 6);
+        assert_eq!(joined.iter().copied().collect::<Vec<_>>(), vec![1, 3, 8, 10, 12, 14]);
+        assert_eq!(joined.validate(), Ok(()));
+
+        let joined = SplayTree::join(SplayTree::new(), joined);
+        assert_eq!(joined.len(), 6);
+        let (everything, _, nothing) = joined.split(&100);
+        assert_eq!((everything.len(), nothing.len()), (6, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "join needs every value on the left below every value on the right")]
+    fn test_join_out_of_order_panics() {
+        let left: SplayTree<i32> = [1, 5].into_iter().collect();
+        let right: SplayTree<i32> = [3, 9].into_iter().collect();
+        SplayTree::join(left, right);
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        // every insert splays the new value up, so ascending inserts hang off to the left
+        let tree: SplayTree<i32> = [1, 2, 3].into_iter().collect();
+        assert_eq!(BinarySearchTree::render(&tree.root), "3\n    2\n        1\n");
+
+        // looking 1 up is a zig-zig, the path flips over to the right
+        let mut tree = tree;
+        assert!(tree.search(1));
+        assert_eq!(BinarySearchTree::render(&tree.root), "        3\n    2\n1\n");
+    }
+
+    impl ModelSet for SplayTree<i32> {
+        fn insert(&mut self, value: i32) -> bool {
+            SplayTree::insert(self, value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            SplayTree::delete(self, value)
+        }
+
+        fn values(&self) -> Vec<i32> {
+            self.iter().copied().collect()
+        }
+
+        fn len(&self) -> usize {
+            SplayTree::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            SplayTree::validate(self)
+        }
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<SplayTree<i32>>("splay tree matches BTreeSet");
+    }
+}
@@ -0,0 +1,427 @@
+use core::fmt;
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use super::{
+    binary_search_tree::{BinarySearchTree, Iter, Node},
+    invariant::InvariantViolation,
+};
+
+// Where a treap gets its priorities from. Any closure handing out u64s works too, which is
+// the easy way to force a particular shape in a test
+pub trait PriorityRng {
+    fn next_priority(&mut self) -> u64;
+}
+
+impl<F: FnMut() -> u64> PriorityRng for F {
+    fn next_priority(&mut self) -> u64 {
+        self()
+    }
+}
+
+// SplitMix64, tiny and good enough to shuffle priorities. The same seed always builds the
+// same tree from the same inserts
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+}
+
+impl PriorityRng for SplitMix64 {
+    fn next_priority(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+// A BST ordered by value and a max-heap by random priority at the same time. With random
+// priorities the shape is the one random insertion order would give, expected O(log n)
+// deep whatever order the values come in. The nodes are the BST's with a u64 priority,
+// no child has a higher one than its parent
+pub struct Treap<T, R = SplitMix64> {
+    root: Option<Box<Node<T, u64>>>,
+    len: usize,
+    rng: R,
+}
+
+impl<T> Treap<T> {
+    // Seeded differently every time, nobody can line values up against the priorities
+    pub fn new() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(SplitMix64::new(seed))
+    }
+}
+
+impl<T, R: PriorityRng> Treap<T, R> {
+    pub fn with_rng(rng: R) -> Self {
+        Treap { root: None, len: 0, rng }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut current = self.root.as_deref();
+
+        while let Some(node) = current {
+            match value.cmp(node.value.borrow()) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Greater => current = node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+
+        None
+    }
+
+    // In order, smallest first
+    pub fn iter(&self) -> Iter<'_, T, u64> {
+        Iter::new(self.root.as_deref())
+    }
+
+    // Zips two subtrees into one, every value in `left` below every value in `right`. The
+    // higher priority of the two roots stays on top
+    fn merge(left: Option<Box<Node<T, u64>>>, right: Option<Box<Node<T, u64>>>) -> Option<Box<Node<T, u64>>> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(mut l), Some(mut r)) => {
+                if l.priority >= r.priority {
+                    l.size += r.size;
+                    l.right = Self::merge(l.right.take(), Some(r));
+                    Some(l)
+                } else {
+                    r.size += l.size;
+                    r.left = Self::merge(Some(l), r.left.take());
+                    Some(r)
+                }
+            }
+        }
+    }
+}
+
+impl<T: Ord, R: PriorityRng> Treap<T, R> {
+    // Returns false if the value was already there
+    pub fn insert(&mut self, value: T) -> bool {
+        let priority = self.rng.next_priority();
+        let inserted = Self::insert_node(&mut self.root, value, priority);
+
+        if inserted {
+            self.len += 1;
+        }
+
+        inserted
+    }
+
+    // Goes in as a leaf like in the BST, then rotates up with the BST's rotations while it
+    // outranks its parent
+    fn insert_node(slot: &mut Option<Box<Node<T, u64>>>, value: T, priority: u64) -> bool {
+        let Some(node) = slot else {
+            *slot = Some(Box::new(Node::with_priority(value, priority)));
+            return true;
+        };
+
+        match value.cmp(&node.value) {
+            Ordering::Less => {
+                if !Self::insert_node(&mut node.left, value, priority) {
+                    return false;
+                }
+                node.size += 1;
+
+                if node.left.as_ref().is_some_and(|left| left.priority > node.priority) {
+                    BinarySearchTree::rotate_right(slot);
+                }
+            }
+            Ordering::Greater => {
+                if !Self::insert_node(&mut node.right, value, priority) {
+                    return false;
+                }
+                node.size += 1;
+
+                if node.right.as_ref().is_some_and(|right| right.priority > node.priority) {
+                    BinarySearchTree::rotate_left(slot);
+                }
+            }
+            Ordering::Equal => return false,
+        }
+
+        true
+    }
+
+    pub fn search(&self, value: T) -> bool {
+        self.get(&value).is_some()
+    }
+
+    // Returns whether the value was in the tree
+    pub fn delete(&mut self, value: T) -> bool {
+        self.take(&value).is_some()
+    }
+
+    // Removes the value and hands it back
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = Self::take_node(&mut self.root, value);
+
+        if removed.is_some() {
+            self.len -= 1;
+        }
+
+        removed
+    }
+
+    // The node found is replaced by its two subtrees merged, which keeps both orders
+    fn take_node<Q>(slot: &mut Option<Box<Node<T, u64>>>, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = slot.as_mut()?;
+
+        let removed = match value.cmp(node.value.borrow()) {
+            Ordering::Less => Self::take_node(&mut node.left, value),
+            Ordering::Greater => Self::take_node(&mut node.right, value),
+            Ordering::Equal => {
+                let Node { value, left, right, .. } = *slot.take().unwrap();
+                *slot = Self::merge(left, right);
+                return Some(value);
+            }
+        };
+
+        if removed.is_some() {
+            node.size -= 1;
+        }
+
+        removed
+    }
+}
+
+impl<T: Ord + Clone + fmt::Display, R> Treap<T, R> {
+    // Order and sizes as in any BST, and no child may outrank its parent
+    pub fn validate(&self) -> Result<(), InvariantViolation<T>> {
+        BinarySearchTree::validate_node(&self.root, None, None)?;
+        Self::validate_priorities(&self.root)
+    }
+
+    fn validate_priorities(node: &Option<Box<Node<T, u64>>>) -> Result<(), InvariantViolation<T>> {
+        let Some(n) = node else {
+            return Ok(());
+        };
+
+        for child in [&n.left, &n.right].into_iter().flatten() {
+            if child.priority > n.priority {
+                return Err(InvariantViolation::Priority {
+                    node: n.value.clone(),
+                    child: child.value.clone(),
+                });
+            }
+        }
+
+        Self::validate_priorities(&n.left)?;
+        Self::validate_priorities(&n.right)
+    }
+
+    pub fn pretty_print(&self) {
+        print!("{}", BinarySearchTree::render(&self.root));
+    }
+}
+
+impl<T> Default for Treap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Treap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord, R: PriorityRng> Extend<T> for Treap<T, R> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T, R: PriorityRng> IntoIterator for &'a Treap<T, R> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, u64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_linear_data_structures::trees::model::{check_set, ModelSet};
+
+    fn height<T>(node: &Option<Box<Node<T, u64>>>) -> i64 {
+        node.as_ref().map_or(-1, |n| 1 + height(&n.left).max(height(&n.right)))
+    }
+
+    // Values top down, a missing child as None, enough to tell two shapes apart
+    fn shape<T: Copy>(node: &Option<Box<Node<T, u64>>>, out: &mut Vec<Option<T>>) {
+        out.push(node.as_ref().map(|n| n.value));
+        if let Some(n) = node {
+            shape(&n.left, out);
+            shape(&n.right, out);
+        }
+    }
+
+    #[test]
+    fn test_insert_search_and_delete() {
+        let mut treap = Treap::with_seed(47);
+        for value in [20, 10, 30, 25, 35, 5] {
+            assert!(treap.insert(value));
+        }
+        assert!(!treap.insert(25));
+        assert_eq!(treap.len(), 6);
+        assert_eq!(treap.validate(), Ok(()));
+
+        assert!(treap.search(10));
+        assert!(!treap.search(27));
+        assert_eq!(treap.get(&35), Some(&35));
+
+        assert!(treap.delete(20));
+        assert!(!treap.delete(20));
+        assert_eq!(treap.take(&5), Some(5));
+        assert_eq!(treap.len(), 4);
+        assert_eq!(treap.iter().copied().collect::<Vec<_>>(), vec![10, 25, 30, 35]);
+        assert_eq!(treap.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_same_seed_same_shape() {
+        let shape_for = |seed| {
+            let mut treap = Treap::with_seed(seed);
+            treap.extend([8, 3, 12, 1, 5, 10, 14, 7, 2]);
+            let mut out = vec![];
+            shape(&treap.root, &mut out);
+            out
+        };
+
+        assert_eq!(shape_for(1), shape_for(1));
+        assert_ne!(shape_for(1), shape_for(2));
+    }
+
+    #[test]
+    fn test_priorities_from_a_closure() {
+        // every new value outranks all before it and rotates all the way up
+        let mut next = 0;
+        let mut treap = Treap::with_rng(move || {
+            next += 1;
+            next
+        });
+        treap.extend(1..=5);
+
+        assert_eq!(treap.root.as_ref().map(|n| n.value), Some(5));
+        assert_eq!(height(&treap.root), 4);
+        assert_eq!(treap.validate(), Ok(()));
+
+        // taking the root merges what hung under it back together
+        assert!(treap.delete(5));
+        assert_eq!(treap.root.as_ref().map(|n| n.value), Some(4));
+        assert_eq!(treap.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_sorted_inserts_stay_shallow() {
+        let mut treap = Treap::with_seed(7);
+        treap.extend(0..10_000);
+
+        assert_eq!(treap.len(), 10_000);
+        assert!(height(&treap.root) < 50, "height {}", height(&treap.root));
+        assert_eq!(treap.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_priority() {
+        let mut treap = Treap::with_seed(3);
+        treap.extend([10, 5, 15]);
+
+        // the root's child now outranks it
+        let root = treap.root.as_mut().unwrap();
+        let child = root.left.as_mut().or(root.right.as_mut()).unwrap();
+        child.priority = u64::MAX;
+        let child = child.value;
+
+        assert_eq!(
+            treap.validate(),
+            Err(InvariantViolation::Priority { node: treap.root.as_ref().unwrap().value, child })
+        );
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        // 1 rotates above 2, then 3 outranks both and rotates up to the root
+        let mut next = 0;
+        let mut treap = Treap::with_rng(move || {
+            next += 1;
+            next
+        });
+        treap.extend([2, 1, 3]);
+
+        assert_eq!(treap.validate(), Ok(()));
+        assert_eq!(BinarySearchTree::render(&treap.root), "3\n        2\n    1\n");
+    }
+
+    impl ModelSet for Treap<i32> {
+        fn insert(&mut self, value: i32) -> bool {
+            Treap::insert(self, value)
+        }
+
+        fn delete(&mut self, value: i32) -> bool {
+            Treap::delete(self, value)
+        }
+
+        fn values(&self) -> Vec<i32> {
+            self.iter().copied().collect()
+        }
+
+        fn len(&self) -> usize {
+            Treap::len(self)
+        }
+
+        fn validate(&self) -> Result<(), InvariantViolation<i32>> {
+            Treap::validate(self)
+        }
+    }
+
+    #[test]
+    fn test_matches_btree_set() {
+        check_set::<Treap<i32>>("treap matches BTreeSet");
+    }
+}
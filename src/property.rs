@@ -0,0 +1,265 @@
+// Small property testing harness for the model tests in the container modules, no crates
+// needed so it runs offline with a plain `cargo test`.
+//
+// A test describes one operation type (push, pop, delete...), the harness generates random
+// sequences of them and hands each sequence to a closure that replays it against the
+// container and a std collection used as the model. When a sequence fails it gets shrunk,
+// first by cutting operations out and then by making the remaining ones simpler, until
+// nothing smaller still fails. The panic message has the minimal sequence and the seed.
+//
+// PROPERTY_SEED=<n> reruns with another seed, PROPERTY_CASES=<n> changes how many
+// sequences are tried. Under Miri the default drops to a handful of sequences.
+
+use std::{
+    fmt::Debug,
+    panic::{self, AssertUnwindSafe},
+};
+
+// Miri runs everything a few thousand times slower, so it only gets a handful of cases
+const DEFAULT_CASES: usize = if cfg!(miri) { 8 } else { 256 };
+const MAX_LEN: usize = 64;
+
+// xorshift64*, plenty for picking operations and small values
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state can't be 0 or it stays 0 forever
+        Rng {
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Uniform enough in 0..n, n has to be above 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn value(&mut self, max: i32) -> i32 {
+        self.below(max as usize + 1) as i32
+    }
+}
+
+pub trait Operation: Clone + Debug {
+    fn generate(rng: &mut Rng) -> Self;
+
+    // Simpler versions of this operation, tried in order while shrinking
+    fn shrink(&self) -> Vec<Self> {
+        vec![]
+    }
+}
+
+// Candidates for shrinking a small non negative value towards 0
+pub fn shrink_value(value: i32) -> Vec<i32> {
+    let mut candidates = vec![];
+
+    if value > 0 {
+        candidates.push(0);
+    }
+    if value > 2 {
+        candidates.push(value / 2);
+    }
+    if value > 1 {
+        candidates.push(value - 1);
+    }
+
+    candidates
+}
+
+fn env_or(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+// Each property gets its own fixed seed so adding a test does not reshuffle the others
+fn seed_for(name: &str) -> u64 {
+    let base = env_or("PROPERTY_SEED", 0);
+
+    name.bytes()
+        .fold(0xCBF2_9CE4_8422_2325u64 ^ base, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+        })
+}
+
+// Runs the sequence, a panic inside counts as a failure too
+fn run_case<O, F>(ops: &[O], run: &F) -> Result<(), String>
+where
+    F: Fn(&[O]) -> Result<(), String>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(|| run(ops))) {
+        Ok(result) => result,
+        Err(payload) => Err(match payload.downcast_ref::<&str>() {
+            Some(message) => format!("panicked: {}", message),
+            None => match payload.downcast_ref::<String>() {
+                Some(message) => format!("panicked: {}", message),
+                None => "panicked".to_string(),
+            },
+        }),
+    }
+}
+
+fn shrink<O, F>(mut ops: Vec<O>, mut error: String, run: &F) -> (Vec<O>, String)
+where
+    O: Operation,
+    F: Fn(&[O]) -> Result<(), String>,
+{
+    loop {
+        let mut smaller = false;
+
+        // cut out chunks, big ones first
+        let mut chunk = ops.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+
+            while start + chunk <= ops.len() {
+                let mut candidate = ops.clone();
+                candidate.drain(start..start + chunk);
+
+                match run_case(&candidate, run) {
+                    Err(candidate_error) => {
+                        ops = candidate;
+                        error = candidate_error;
+                        smaller = true;
+                    }
+                    Ok(()) => start += 1,
+                }
+            }
+
+            chunk /= 2;
+        }
+
+        // then simplify what is left one operation at a time
+        for i in 0..ops.len() {
+            for simpler in ops[i].shrink() {
+                let mut candidate = ops.clone();
+                candidate[i] = simpler;
+
+                if let Err(candidate_error) = run_case(&candidate, run) {
+                    ops = candidate;
+                    error = candidate_error;
+                    smaller = true;
+                    break;
+                }
+            }
+        }
+
+        if !smaller {
+            return (ops, error);
+        }
+    }
+}
+
+// Generates random operation sequences and panics with a minimal failing one
+pub fn check<O, F>(name: &str, run: F)
+where
+    O: Operation,
+    F: Fn(&[O]) -> Result<(), String>,
+{
+    let seed = seed_for(name);
+    let cases = env_or("PROPERTY_CASES", DEFAULT_CASES as u64) as usize;
+    let mut rng = Rng::new(seed);
+
+    for case in 0..cases {
+        let len = rng.below(MAX_LEN + 1);
+        let ops: Vec<O> = (0..len).map(|_| O::generate(&mut rng)).collect();
+
+        if let Err(error) = run_case(&ops, &run) {
+            let (ops, error) = shrink(ops, error, &run);
+
+            panic!(
+                "property `{}` failed on case {} (seed {})\nminimal operations: {:#?}\nerror: {}",
+                name, case, seed, ops, error
+            );
+        }
+    }
+}
+
+// Like assert_eq but returns the mismatch so the harness can keep shrinking
+macro_rules! ensure_eq {
+    ($left:expr, $right:expr, $($context:tt)+) => {
+        let (left, right) = (&$left, &$right);
+
+        if left != right {
+            return Err(format!(
+                "{}: container gave {:?}, model gave {:?}",
+                format!($($context)+),
+                left,
+                right
+            ));
+        }
+    };
+}
+
+pub(crate) use ensure_eq;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        Add(i32),
+    }
+
+    impl Operation for Op {
+        fn generate(rng: &mut Rng) -> Self {
+            Op::Add(rng.value(20))
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            match self {
+                Op::Add(value) => shrink_value(*value).into_iter().map(Op::Add).collect(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        assert!((0..1000).all(|_| a.below(10) < 10));
+    }
+
+    #[test]
+    fn test_passing_property() {
+        check::<Op, _>("sum never negative", |ops| {
+            let sum: i32 = ops.iter().map(|Op::Add(value)| value).sum();
+            ensure_eq!(sum >= 0, true, "sum");
+            Ok(())
+        });
+    }
+
+    // A sum above 30 fails, the smallest reproduction is two operations adding up to 31
+    #[test]
+    fn test_failure_is_shrunk() {
+        let result = panic::catch_unwind(|| {
+            check::<Op, _>("sum stays small", |ops| {
+                let sum: i32 = ops.iter().map(|Op::Add(value)| value).sum();
+                ensure_eq!(sum <= 30, true, "sum {}", sum);
+                Ok(())
+            })
+        });
+
+        let payload = result.unwrap_err();
+        let message = payload.downcast_ref::<String>().unwrap();
+
+        assert!(message.contains("property `sum stays small` failed"));
+        assert_eq!(message.matches("Add(").count(), 2, "{}", message);
+    }
+}